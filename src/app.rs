@@ -1,31 +1,236 @@
-use crate::character::Character;
-use crate::ui::{FileDialog, CharacterView, ApAnalysis};
+use crate::character::{ApCalculator, Character, CharacterDiff, CharacterFormat, CompendiumIndex, FoundryExport, FoundryWorld, HeroSummary, OptolithImport, Rules, SourceComparison, WorldActor};
+use crate::ui::{FileDialog, CharacterView, ApAnalysis, DiffView, CrossCheckView, PartyView, WorldPicker};
 use eframe::egui;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// A loaded character of the party
+struct PartyMember {
+    character: Character,
+    /// The loaded export, used to write edits back
+    export: Option<FoundryExport>,
+    /// Format of the loaded file
+    format: CharacterFormat,
+    /// What could not be mapped when importing an Optolith hero
+    import_warnings: Vec<String>,
+    /// File name of the loaded character, or the actor and its world
+    loaded_from: String,
+    /// Full path of the file, or world and actor id; loading the same character again replaces it
+    key: String,
+    /// Tabs, planner, filters and editor of this character
+    view: CharacterView,
+    /// AP overview for the party panel and dashboard; rebuilt when the character is edited or other rules are loaded
+    summary: HeroSummary,
+}
+
+impl PartyMember {
+    fn new(character: Character, export: Option<FoundryExport>, format: CharacterFormat, import_warnings: Vec<String>, loaded_from: String, key: String, calculator: &ApCalculator) -> Self {
+        Self {
+            summary: HeroSummary::new(&character, calculator),
+            character,
+            export,
+            format,
+            import_warnings,
+            loaded_from,
+            key,
+            view: CharacterView::new(),
+        }
+    }
+
+    fn refresh_summary(&mut self, calculator: &ApCalculator) {
+        self.summary = HeroSummary::new(&self.character, calculator);
+    }
+}
 
 pub struct App {
     file_dialog: FileDialog,
-    character_view: CharacterView,
     ap_analysis: ApAnalysis,
-    current_character: Option<Character>,
-    selected_file: Option<PathBuf>,
+    diff_view: DiffView,
+    cross_check_view: CrossCheckView,
+    world_picker: WorldPicker,
+    party_view: PartyView,
+    /// All loaded characters, in loading order
+    party: Vec<PartyMember>,
+    /// Index of the character shown in the main view
+    selected: usize,
+    /// Rules of all calculations: the built-in rules or the loaded rules file
+    rules: Rules,
+    rules_file: Option<PathBuf>,
+    /// Compendium the items are checked against, if one was loaded
+    compendium: Option<CompendiumIndex>,
+    /// Directory the compendium was loaded from
+    compendium_dir: Option<PathBuf>,
 }
 
 impl App {
     pub fn new() -> Self {
         Self {
             file_dialog: FileDialog::new(),
-            character_view: CharacterView::new(),
             ap_analysis: ApAnalysis::new(),
-            current_character: None,
-            selected_file: None,
+            diff_view: DiffView::new(),
+            cross_check_view: CrossCheckView::new(),
+            world_picker: WorldPicker::new(),
+            party_view: PartyView::new(),
+            party: Vec::new(),
+            selected: 0,
+            rules: Rules::default(),
+            rules_file: None,
+            compendium: None,
+            compendium_dir: None,
+        }
+    }
+
+    fn load_character(&mut self, path: &Path) -> anyhow::Result<()> {
+        let content = std::fs::read_to_string(path)?;
+        let format = CharacterFormat::detect(&content)?;
+        let loaded_from = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let calculator = ApCalculator::new(&self.rules).with_compendium(self.compendium.as_ref());
+        let member = match format {
+            CharacterFormat::Foundry => {
+                let export = FoundryExport::from_json(&content)?;
+                PartyMember::new(export.original.clone(), Some(export), format, Vec::new(), loaded_from, path.display().to_string(), &calculator)
+            }
+            CharacterFormat::Optolith => {
+                let import = OptolithImport::from_json(&content, &self.rules)?;
+                for warning in &import.warnings {
+                    eprintln!("Optolith import: {}", warning);
+                }
+                PartyMember::new(import.character, None, format, import.warnings, loaded_from, path.display().to_string(), &calculator)
+            }
+        };
+        self.add_to_party(member);
+        Ok(())
+    }
+
+    /// Load an actor picked from a world; it is written back as a Foundry actor export
+    fn load_world_actor(&mut self, world_title: &str, actor: &WorldActor) -> anyhow::Result<()> {
+        let export = FoundryExport::from_json(&actor.to_json()?)?;
+        self.add_to_party(PartyMember::new(
+            export.original.clone(),
+            Some(export),
+            CharacterFormat::Foundry,
+            Vec::new(),
+            format!("{} in world {}", actor.name, world_title),
+            format!("{}/{}", world_title, actor.id),
+            &ApCalculator::new(&self.rules).with_compendium(self.compendium.as_ref()),
+        ));
+        Ok(())
+    }
+
+    /// Add a character to the party and show it. Loading the same file or actor again replaces the earlier version.
+    fn add_to_party(&mut self, member: PartyMember) {
+        match self.party.iter().position(|other| other.key == member.key) {
+            Some(index) => {
+                self.party[index] = member;
+                self.selected = index;
+            }
+            None => {
+                self.party.push(member);
+                self.selected = self.party.len() - 1;
+            }
+        }
+    }
+
+    fn remove_from_party(&mut self, index: usize) {
+        self.party.remove(index);
+        if self.selected > index || self.selected >= self.party.len() {
+            self.selected = self.selected.saturating_sub(1);
         }
     }
 
-    fn load_character(&mut self, path: &PathBuf) -> anyhow::Result<()> {
-        let character = Character::from_file(path)?;
-        self.current_character = Some(character);
-        self.selected_file = Some(path.clone());
+    fn current(&self) -> Option<&PartyMember> {
+        self.party.get(self.selected)
+    }
+
+    /// The AP of every character depend on the loaded rules and compendium
+    fn refresh_summaries(&mut self) {
+        let calculator = ApCalculator::new(&self.rules).with_compendium(self.compendium.as_ref());
+        self.party.iter_mut().for_each(|member| member.refresh_summary(&calculator));
+    }
+
+    /// Sidebar listing the loaded characters with their AP check
+    fn show_party_panel(&mut self, ctx: &egui::Context) {
+        let mut removed = None;
+
+        egui::SidePanel::left("party_panel")
+            .default_width(180.0)
+            .show(ctx, |ui| {
+                ui.heading("👥 Party");
+                ui.separator();
+
+                for (index, member) in self.party.iter().enumerate() {
+                    let hero = &member.summary;
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").on_hover_text("Remove from the party").clicked() {
+                            removed = Some(index);
+                        }
+                        if ui.selectable_label(index == self.selected, &hero.name)
+                            .on_hover_text(format!("{} ({})", member.loaded_from, member.format.name()))
+                            .clicked()
+                        {
+                            self.selected = index;
+                        }
+                        PartyView::show_status(ui, hero);
+                    });
+                }
+
+                ui.add_space(10.0);
+                if ui.button("📊 Party Dashboard").on_hover_text("Compare the AP of all loaded characters").clicked() {
+                    self.party_view.open();
+                }
+            });
+
+        if let Some(index) = removed {
+            self.remove_from_party(index);
+        }
+    }
+
+    /// Compare the loaded character (as old state) with another export (as new state).
+    /// If one of the two files is an Optolith hero and the other a Foundry export, check both sources against each other instead.
+    fn compare_with(&mut self, path: &Path) -> anyhow::Result<()> {
+        let Some(member) = self.current() else {
+            anyhow::bail!("No character loaded");
+        };
+        let current = &member.character;
+        let content = std::fs::read_to_string(path)?;
+        let format = CharacterFormat::detect(&content)?;
+        let calculator = ApCalculator::new(&self.rules).with_compendium(self.compendium.as_ref());
+        match (member.format, format) {
+            (CharacterFormat::Optolith, CharacterFormat::Foundry) => {
+                let optolith = OptolithImport {
+                    character: current.clone(),
+                    warnings: member.import_warnings.clone(),
+                };
+                let foundry = Character::from_json(&content)?;
+                self.cross_check_view.set_comparison(SourceComparison::between(&optolith, &foundry, &calculator));
+            }
+            (CharacterFormat::Foundry, CharacterFormat::Optolith) => {
+                let optolith = OptolithImport::from_json(&content, &self.rules)?;
+                self.cross_check_view.set_comparison(SourceComparison::between(&optolith, current, &calculator));
+            }
+            _ => {
+                let other = Character::from_json(&content)?;
+                self.diff_view.set_diff(CharacterDiff::between(current, &other, &calculator), path.to_path_buf());
+            }
+        }
+        Ok(())
+    }
+
+    fn load_rules(&mut self, path: &Path) -> anyhow::Result<()> {
+        self.rules = Rules::from_file(path)?;
+        self.rules_file = Some(path.to_path_buf());
+        self.refresh_summaries();
+        Ok(())
+    }
+
+    fn load_compendium(&mut self, path: &Path) -> anyhow::Result<()> {
+        let compendium = CompendiumIndex::load(path)?;
+        for warning in &compendium.warnings {
+            eprintln!("Compendium: {}", warning);
+        }
+        println!("Indexed {} compendium entries from {} packs", compendium.entries.len(), compendium.packs.len());
+        self.compendium = Some(compendium);
+        self.compendium_dir = Some(path.to_path_buf());
+        self.refresh_summaries();
         Ok(())
     }
 }
@@ -48,26 +253,117 @@ impl eframe::App for App {
                     }
                 }
 
-                if let Some(ref path) = self.selected_file {
-                    ui.label(format!("Loaded: {}", path.file_name().unwrap_or_default().to_string_lossy()));
+                if ui.button("Open Foundry World").on_hover_text("Pick a character from the database of a local Foundry world").clicked() {
+                    if let Some(path) = self.file_dialog.open_world_dir() {
+                        match FoundryWorld::open(&path) {
+                            Ok(world) => {
+                                println!("Opened world {} ({} actors) from: {:?}", world.title, world.actors.len(), world.path);
+                                self.world_picker.set_world(world, &ApCalculator::new(&self.rules).with_compendium(self.compendium.as_ref()));
+                            }
+                            Err(e) => {
+                                eprintln!("Error opening world: {}", e);
+                            }
+                        }
+                    }
+                }
+
+                if self.world_picker.has_world() && ui.button("🌍 Actors").on_hover_text("Show the actors of the opened world").clicked() {
+                    self.world_picker.open();
+                }
+
+                let compare_button = ui.add_enabled(self.current().is_some(), egui::Button::new("Compare with…"))
+                    .on_hover_text("Show what changed between the loaded character and a newer export, or where an Optolith hero and its Foundry export diverge");
+                if compare_button.clicked() {
+                    if let Some(path) = self.file_dialog.open_file() {
+                        if let Err(e) = self.compare_with(&path) {
+                            eprintln!("Error comparing character: {}", e);
+                        }
+                    }
+                }
+
+                if ui.button("Load Rules File").clicked() {
+                    if let Some(path) = self.file_dialog.open_rules_file() {
+                        match self.load_rules(&path) {
+                            Ok(_) => {
+                                println!("Successfully loaded rules from: {:?}", path);
+                            }
+                            Err(e) => {
+                                eprintln!("Error loading rules: {}", e);
+                            }
+                        }
+                    }
+                }
+
+                if ui.button("Load Compendium").on_hover_text("Check advantages, disadvantages and special abilities against local DSA5 compendium packs").clicked() {
+                    if let Some(path) = self.file_dialog.open_compendium_dir() {
+                        match self.load_compendium(&path) {
+                            Ok(_) => {
+                                println!("Successfully loaded compendium from: {:?}", path);
+                            }
+                            Err(e) => {
+                                eprintln!("Error loading compendium: {}", e);
+                            }
+                        }
+                    }
+                }
+
+                if let Some(member) = self.current() {
+                    ui.label(format!("Loaded: {} ({})", member.loaded_from, member.format.name()));
+
+                    if !member.import_warnings.is_empty() {
+                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {} import warnings", member.import_warnings.len()))
+                            .on_hover_text(member.import_warnings.join("\n"));
+                    }
+                }
+
+                if let Some(ref path) = self.rules_file {
+                    ui.label(format!("Rules: {}", path.file_name().unwrap_or_default().to_string_lossy()));
+                }
+
+                if let Some(ref path) = self.compendium_dir {
+                    ui.label(format!("Compendium: {}", path.file_name().unwrap_or_default().to_string_lossy()));
                 }
             });
         });
 
+        self.diff_view.show(ctx);
+        self.cross_check_view.show(ctx);
+
+        let heroes: Vec<&HeroSummary> = self.party.iter().map(|member| &member.summary).collect();
+        self.party_view.show(ctx, &heroes);
+
+        if let Some((world_title, actor)) = self.world_picker.show(ctx) {
+            match self.load_world_actor(&world_title, &actor) {
+                Ok(_) => {
+                    println!("Successfully loaded {} from world {}", actor.name, world_title);
+                }
+                Err(e) => {
+                    eprintln!("Error loading actor: {}", e);
+                }
+            }
+        }
+
+        if !self.party.is_empty() {
+            self.show_party_panel(ctx);
+        }
+
         // Main content area
         egui::CentralPanel::default().show(ctx, |ui| {
-            match &self.current_character {
-                Some(character) => {
+            let calculator = ApCalculator::new(&self.rules).with_compendium(self.compendium.as_ref());
+            match self.party.get_mut(self.selected) {
+                Some(member) => {
                     // Use SidePanel for left panel with fixed width
                     egui::SidePanel::left("character_panel")
                         .show_inside(ui, |ui| {
-                            self.character_view.show(ui, character);
+                            if member.view.show(ui, &mut member.character, member.export.as_ref(), &calculator) {
+                                member.refresh_summary(&calculator);
+                            }
                         });
 
                     // The remaining space will be used for AP analysis
                     egui::CentralPanel::default()
                         .show_inside(ui, |ui| {
-                            self.ap_analysis.show(ui, character);
+                            self.ap_analysis.show(ui, &member.character, &calculator);
                         });
                 }
                 None => {
@@ -75,7 +371,8 @@ impl eframe::App for App {
                         ui.add_space(200.0);
                         ui.heading("DSA Character Analyzer");
                         ui.add_space(20.0);
-                        ui.label("Click 'Load Character File' to start analyzing a character sheet.");
+                        ui.label("Click 'Load Character File' or 'Open Foundry World' to start analyzing a character sheet.");
+                        ui.label("Load more characters to compare them in the party dashboard.");
                     });
                 }
             }
@@ -1,17 +1,14 @@
-use crate::character::Character;
+use crate::character::{Character, Characteristics, CompendiumIndex, Item, ItemData, ItemKind, Rules};
+use crate::character::data::base_name;
+use crate::character::report::{category, ApReport, ApReportEntry, ApWarning};
 use std::collections::HashMap;
 
-pub struct ApCalculator;
-
-#[derive(Debug, Clone)]
-pub struct ApItem {
-    pub name: String,
-    pub item_type: String,
-    pub raw_value: String,
-    pub step: Option<i32>,
-    pub calculation: String,
-    pub ap_cost: i32,
-    pub was_excluded: bool,
+/// Calculates the AP a character spent, using the rule tables it was created with
+#[derive(Debug, Clone, Copy)]
+pub struct ApCalculator<'a> {
+    rules: &'a Rules,
+    /// Identifies renamed items with special AP rules by their compendium entry
+    compendium: Option<&'a CompendiumIndex>,
 }
 
 #[derive(Debug, Clone)]
@@ -21,108 +18,97 @@ enum ApValueParseResult {
     ParseError,
 }
 
-impl ApCalculator {
-    pub fn calculate_total_spent_ap(character: &Character) -> i32 {
-        let processed_items = Self::apply_special_rules(character);
-        let items_total: i32 = processed_items.iter()
-            .filter(|item| !item.was_excluded)
-            .map(|item| item.ap_cost)
-            .sum();
-        
-        let skills_ap = Self::calculate_skills_ap(character);
-        let combat_skills_ap = Self::calculate_combat_skills_ap(character);
-        let spells_rituals_ap = Self::calculate_spells_and_rituals_ap(character);
-        let magic_tricks_ap = Self::calculate_magic_tricks_ap(character);
-        let liturgies_ceremonies_ap = Self::calculate_liturgies_and_ceremonies_ap(character);
-        let blessings_ap = Self::calculate_blessings_ap(character);
-        let characteristics_ap = Self::calculate_characteristics_ap(character);
-        let energy_values_ap = Self::calculate_energy_values_ap(character);
-
-        items_total + skills_ap + combat_skills_ap + spells_rituals_ap + magic_tricks_ap
-            + liturgies_ceremonies_ap + blessings_ap + characteristics_ap + energy_values_ap
-    }
-
-    pub fn get_ap_by_category(character: &Character) -> HashMap<String, i32> {
-        let mut categories = HashMap::new();
-
-        let processed_items = Self::apply_special_rules(character);
-        for item in processed_items.iter().filter(|item| !item.was_excluded) {
-            if item.ap_cost != 0 {
-                *categories.entry(item.item_type.clone()).or_insert(0) += item.ap_cost;
-            }
-        }
+/// Cost function of a group of talent-like items: (talent_value, stf_multiplier) -> AP
+type TalentCostFn<'a> = fn(&ApCalculator<'a>, i32, i32) -> i32;
+/// Explanation of a talent-like cost: (talent_value, stf_multiplier) -> formula
+type TalentFormulaFn<'a> = fn(&ApCalculator<'a>, i32, i32) -> String;
 
-        // Add skills AP calculation
-        let skills_ap = Self::calculate_skills_ap(character);
-        if skills_ap > 0 {
-            categories.insert("Skills".to_string(), skills_ap);
-        }
+impl Default for ApCalculator<'static> {
+    /// A calculator using the built-in rules
+    fn default() -> Self {
+        ApCalculator::new(Rules::builtin())
+    }
+}
 
-        // Add combat skills AP calculation
-        let combat_skills_ap = Self::calculate_combat_skills_ap(character);
-        if combat_skills_ap > 0 {
-            categories.insert("Combat Skills".to_string(), combat_skills_ap);
-        }
+impl<'a> ApCalculator<'a> {
+    pub fn new(rules: &'a Rules) -> Self {
+        ApCalculator { rules, compendium: None }
+    }
 
-        // Add spells & rituals AP calculation
-        let spells_rituals_ap = Self::calculate_spells_and_rituals_ap(character);
-        if spells_rituals_ap > 0 {
-            categories.insert("Spells/Rituals".to_string(), spells_rituals_ap);
-        }
+    /// Use the compendium, if one was loaded, to recognize items by their source id
+    pub fn with_compendium(self, compendium: Option<&'a CompendiumIndex>) -> Self {
+        ApCalculator { compendium, ..self }
+    }
 
-        // Add magic tricks AP calculation
-        let magic_tricks_ap = Self::calculate_magic_tricks_ap(character);
-        if magic_tricks_ap > 0 {
-            categories.insert("Magic Tricks".to_string(), magic_tricks_ap);
-        }
+    /// The rule tables this calculator uses
+    pub fn rules(&self) -> &'a Rules {
+        self.rules
+    }
 
-        // Add liturgies & ceremonies AP calculation
-        let liturgies_ceremonies_ap = Self::calculate_liturgies_and_ceremonies_ap(character);
-        if liturgies_ceremonies_ap > 0 {
-            categories.insert("Liturgies/Ceremonies".to_string(), liturgies_ceremonies_ap);
-        }
+    /// The compendium this calculator uses, if any
+    pub fn compendium(&self) -> Option<&'a CompendiumIndex> {
+        self.compendium
+    }
 
-        // Add blessings AP calculation
-        let blessings_ap = Self::calculate_blessings_ap(character);
-        if blessings_ap > 0 {
-            categories.insert("Blessings".to_string(), blessings_ap);
-        }
+    pub fn calculate_total_spent_ap(&self, character: &Character) -> i32 {
+        self.build_report(character).total
+    }
 
-        let energy_values_ap = Self::calculate_energy_values_ap(character);
-        if energy_values_ap > 0 {
-            categories.insert("Energies (LeP/AsP/KaP)".to_string(), energy_values_ap);
-        }
+    pub fn get_ap_by_category(&self, character: &Character) -> HashMap<String, i32> {
+        self.build_report(character).ap_by_category()
+    }
 
-        // Add characteristics
-        let characteristics_ap = Self::calculate_characteristics_ap(character);
-        if characteristics_ap > 0 {
-            categories.insert("Characteristics".to_string(), characteristics_ap);
-        }
+    /// Run the complete AP calculation and collect every entry, category total and warning
+    pub fn build_report(&self, character: &Character) -> ApReport {
+        let rules = self.rules;
+        let mut warnings = Vec::new();
+
+        let mut entries = self.ap_items_entries(character, &mut warnings);
+        entries.extend(self.skills_entries(character, &mut warnings));
+        entries.extend(self.combat_skills_entries(character, &mut warnings));
+        entries.extend(self.spells_and_rituals_entries(character, &mut warnings));
+        entries.extend(Self::flat_cost_entries(&character.get_magic_tricks(), category::MAGIC_TRICKS, rules.magic_trick_cost));
+        entries.extend(self.liturgies_and_ceremonies_entries(character, &mut warnings));
+        entries.extend(Self::flat_cost_entries(&character.get_blessings(), category::BLESSINGS, rules.blessing_cost));
+        entries.extend(self.get_energy_values_ap_breakdown(character));
+        entries.extend(self.characteristics_entries(character, &mut warnings));
+        entries.extend(self.species_entries(character, &mut warnings));
 
-        categories
+        let foundry_spent = character.system
+            .as_ref()
+            .and_then(|s| s.details.as_ref())
+            .and_then(|d| d.experience.as_ref())
+            .and_then(|e| e.spent());
+
+        ApReport::new(entries, warnings, foundry_spent)
     }
 
     /// Apply special DSA rules for duplicate advantages/disadvantages
-    fn apply_special_rules(character: &Character) -> Vec<ApItem> {
+    fn apply_special_rules(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
         let mut result = Vec::new();
-        let mut duplicate_groups: HashMap<String, Vec<ApItem>> = HashMap::new();
+        let mut duplicate_groups: HashMap<String, Vec<ApReportEntry>> = HashMap::new();
 
-        // Define which items have the "highest step only" rule
-        let highest_step_only_items = [
-            "Prinzipientreue",
-            "Verpflichtungen",
-            // Add more items that follow this rule as needed
-        ];
+        // Items that have the "highest step only" rule are defined in the rules file
+        let rules = self.rules;
+        let rule_key_of = |item: &Item| Self::highest_step_only_key(rules, self.compendium, item);
+
+        let ap_items = character.get_ap_items();
+
+        // Compendium source ids of items whose rule was recognized, so that items with a
+        // translated or renamed base name created from the same compendium entry join their group
+        let mut rule_keys_by_source_id: HashMap<&str, &str> = HashMap::new();
+        for item in &ap_items {
+            if let (Some(source_id), Some(key)) = (item.source_id(), rule_key_of(item)) {
+                rule_keys_by_source_id.insert(source_id, key);
+            }
+        }
 
         // Process all AP items
-        for item in character.get_ap_items() {
-            let raw_ap_value = item.system.get_ap_value().unwrap_or_default();
-            // TODO unify step or step_option
-            let step_option = item.system.get_step_value()
-                .and_then(|s| s.parse::<i32>().ok());
-            let step = item.system.get_step_value()
-                .and_then(|s| s.parse::<i32>().ok())
-                .unwrap_or(1);
+        for item in ap_items {
+            let purchase = item.purchase();
+            let raw_ap_value = purchase.ap_value.clone().unwrap_or_default();
+            let step_option = purchase.step;
+            let step = step_option.unwrap_or(1);
             let calculated_cost = Self::calculate_item_ap_cost(item);
 
             // Generate explanation based on parsing result
@@ -149,46 +135,60 @@ impl ApCalculator {
                                 step_values.first().unwrap_or(&"0".to_string()).clone()
                             }
                         }
-                        Err(error_msg) => format!("ERROR: {}", error_msg),
+                        Err(error_msg) => {
+                            warnings.push(ApWarning {
+                                name: item.name.clone(),
+                                reason: format!("{}, counted as 0 AP", error_msg),
+                            });
+                            format!("ERROR: {}", error_msg)
+                        }
                     }
                 }
-                ApValueParseResult::ParseError => "Parse error".to_string(),
+                ApValueParseResult::ParseError => {
+                    warnings.push(ApWarning {
+                        name: item.name.clone(),
+                        reason: format!("Unparseable APValue '{}', counted as 0 AP", raw_ap_value),
+                    });
+                    "Parse error".to_string()
+                }
             };
 
-            let ap_item = ApItem {
+            let ap_entry = ApReportEntry {
                 name: item.name.clone(),
-                item_type: item.item_type.clone(),
-                raw_value: raw_ap_value.clone(),
-                step: step_option,
-                calculation: calculation_explanation.clone(),
+                item_type: item.kind.to_string(),
+                category: item.kind.to_string(),
+                value: step_option,
+                stf: None,
+                raw_ap_value: Some(raw_ap_value.clone()),
+                formula: calculation_explanation,
                 ap_cost: calculated_cost,
-                was_excluded: false,
+                excluded: false,
             };
 
-            // Extract the base name (part before the first opening parenthesis)
-            let base_name = Self::extract_base_name(&item.name);
-            
-            if highest_step_only_items.contains(&base_name.as_str()) {
+            let rule_key = rule_key_of(item)
+                .or_else(|| item.source_id().and_then(|id| rule_keys_by_source_id.get(id).copied()));
+
+            if let Some(rule_key) = rule_key {
                 duplicate_groups
-                    .entry(base_name)
-                    .or_insert_with(Vec::new)
-                    .push(ap_item);
+                    .entry(rule_key.to_string())
+                    .or_default()
+                    .push(ap_entry);
             } else {
                 // Regular item, no special rules
-                result.push(ap_item);
+                result.push(ap_entry);
             }
         }
 
         // Process duplicate groups - only keep the one with highest step
-        for (_base_name, mut group) in duplicate_groups {
+        for (_rule_key, mut group) in duplicate_groups {
             if group.len() == 1 {
                 // Only one instance, use it normally
                 result.push(group.into_iter().next().unwrap());
             } else {
                 // Multiple instances, find the one with highest step
                 group.sort_by(|a, b| {
-                    let step_a = a.step.unwrap_or(1);
-                    let step_b = b.step.unwrap_or(1);
+                    let step_a = a.value.unwrap_or(1);
+                    let step_b = b.value.unwrap_or(1);
                     step_b.cmp(&step_a) // Sort by step descending
                 });
 
@@ -196,10 +196,10 @@ impl ApCalculator {
                 result.push(group[0].clone());
 
                 // Add the others as excluded (so they show up in debug but don't count)
-                for item in &group[1..] {
-                    let mut excluded_item = item.clone();
-                    excluded_item.was_excluded = true;
-                    result.push(excluded_item);
+                for entry in &group[1..] {
+                    let mut excluded_entry = entry.clone();
+                    excluded_entry.excluded = true;
+                    result.push(excluded_entry);
                 }
             }
         }
@@ -207,14 +207,15 @@ impl ApCalculator {
         result
     }
 
-    /// Extract the base name from a full name (everything before the first opening parenthesis)
-    /// Example: "Prinzipientreue (Hesindekirche)" -> "Prinzipientreue"
-    fn extract_base_name(full_name: &str) -> String {
-        if let Some(paren_pos) = full_name.find('(') {
-            full_name[..paren_pos].trim().to_string()
-        } else {
-            full_name.to_string()
-        }
+    /// Key of the "highest step only" rule of an item. If a compendium is loaded, the source id of the item identifies
+    /// its official entry, whose name is matched instead of the possibly renamed item; then the rules are
+    /// checked by source id and by the item's own (German or English) base name.
+    fn highest_step_only_key<'r>(rules: &'r Rules, compendium: Option<&CompendiumIndex>, item: &Item) -> Option<&'r str> {
+        let by_compendium = compendium
+            .and_then(|compendium| compendium.find_by_source_id(item))
+            .and_then(|entry| rules.highest_step_only_key(&entry.item, base_name(&entry.item.name)));
+
+        by_compendium.or_else(|| rules.highest_step_only_key(item, base_name(&item.name)))
     }
 
     /// Parse the AP value string into a structured result
@@ -258,345 +259,484 @@ impl ApCalculator {
         Ok(values.iter().take(step as usize).sum())
     }
 
+    /// Calculate the AP cost of an `APValue` (single value or semicolon-separated values per step) at a step
+    pub fn ap_value_cost(ap_value: &str, step: i32) -> Result<i32, String> {
+        match Self::parse_ap_value(ap_value) {
+            ApValueParseResult::SingleValue(value) => Ok(Self::calculate_single_value_cost(value, step)),
+            ApValueParseResult::MultipleValues(values) => Self::calculate_multiple_values_cost(&values, step),
+            ApValueParseResult::ParseError => Err(format!("Unparseable APValue '{}'", ap_value)),
+        }
+    }
+
+    /// Calculate the AP cost of a talent-like item (skill, combat technique, spell, ritual, liturgy, ceremony)
+    /// at the given talent value, using its StF and the progression of its category
+    pub fn talent_ap_cost(&self, item: &Item, talent_value: i32) -> Result<i32, String> {
+        let (_, stf) = Self::talent_data(item)?;
+        let stf = stf.ok_or_else(|| "Missing StF".to_string())?;
+        let stf_multiplier = self.stf_to_multiplier(&stf)
+            .ok_or_else(|| format!("Unknown StF '{}'", stf))?;
+
+        let cost_fn: TalentCostFn<'a> = match item.kind {
+            ItemKind::Skill => Self::talent_value_to_ap_cost,
+            ItemKind::CombatSkill => Self::combat_skill_talent_value_to_ap_cost,
+            ItemKind::Spell | ItemKind::Ritual | ItemKind::Liturgy | ItemKind::Ceremony => Self::learned_ability_talent_value_to_ap_cost,
+            ref other => return Err(format!("Item type '{}' has no talent value", other)),
+        };
+
+        Ok(cost_fn(self, talent_value, stf_multiplier))
+    }
+
+    /// AP cost of raising a talent-like item by one point
+    pub fn next_talent_value_cost(&self, item: &Item) -> Result<i32, String> {
+        let (talent_value, _, _) = self.talent_value_and_stf(item)?;
+        Ok(self.talent_ap_cost(item, talent_value + 1)? - self.talent_ap_cost(item, talent_value)?)
+    }
+
+    /// Highest talent value a talent-like item can be raised to with `available_ap`
+    pub fn max_reachable_talent_value(&self, item: &Item, available_ap: i32) -> Result<i32, String> {
+        // Upper bound for the search, far above any value allowed by the rules
+        const TALENT_VALUE_SEARCH_LIMIT: i32 = 50;
+
+        let (talent_value, _, _) = self.talent_value_and_stf(item)?;
+        let current_cost = self.talent_ap_cost(item, talent_value)?;
+
+        let mut reachable = talent_value;
+        while reachable < TALENT_VALUE_SEARCH_LIMIT
+            && self.talent_ap_cost(item, reachable + 1)? - current_cost <= available_ap
+        {
+            reachable += 1;
+        }
+
+        Ok(reachable)
+    }
+
     /// Calculate the effective AP cost for a single item, considering both APValue and step
-    fn calculate_item_ap_cost(item: &crate::character::Item) -> i32 {
+    fn calculate_item_ap_cost(item: &Item) -> i32 {
+        let purchase = item.purchase();
+
         // Get AP value string
-        let ap_value_str = match item.system.get_ap_value() {
+        let ap_value_str = match purchase.ap_value.as_deref() {
             Some(val) => val,
             None => return 0,
         };
 
         // Get step value (default to 1)
-        let step = item.system.get_step_value()
-            .and_then(|s| s.parse::<i32>().ok())
-            .unwrap_or(1);
+        let step = purchase.step.unwrap_or(1);
 
         // Parse AP value and calculate cost based on type
-        match Self::parse_ap_value(&ap_value_str) {
+        match Self::parse_ap_value(ap_value_str) {
             ApValueParseResult::SingleValue(value) => {
                 Self::calculate_single_value_cost(value, step)
             }
             ApValueParseResult::MultipleValues(values) => {
-                Self::calculate_multiple_values_cost(&values, step).unwrap_or_else(|_| 0)
+                Self::calculate_multiple_values_cost(&values, step).unwrap_or(0)
             }
             ApValueParseResult::ParseError => 0,
         }
     }
 
     /// Get a detailed breakdown that shows which duplicate items were excluded
-    pub fn get_ap_items_breakdown(character: &Character) -> Vec<ApItem> {
-        let mut breakdown = Self::apply_special_rules(character);
-        
+    pub fn get_ap_items_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        self.ap_items_entries(character, &mut Vec::new())
+    }
+
+    fn ap_items_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
+        let mut breakdown = self.apply_special_rules(character, warnings);
+
         // Sort the breakdown to ensure a consistent order and prevent flickering
         breakdown.sort_by(|a, b| {
             // First sort by name
             match a.name.cmp(&b.name) {
                 std::cmp::Ordering::Equal => {
                     // If names are equal, sort by step (higher step first)
-                    let step_a = a.step.unwrap_or(1);
-                    let step_b = b.step.unwrap_or(1);
+                    let step_a = a.value.unwrap_or(1);
+                    let step_b = b.value.unwrap_or(1);
                     step_b.cmp(&step_a)
                 },
                 other => other,
             }
         });
-        
+
         breakdown
     }
 
     /// Calculate AP cost for a single talent value using the progressive cost system
-    fn talent_value_to_ap_cost(talent_value: i32, stf_multiplier: i32) -> i32 {
+    fn talent_value_to_ap_cost(&self, talent_value: i32, stf_multiplier: i32) -> i32 {
         if talent_value < 0 {
             return 0; // Handle invalid values gracefully
         }
-        
-        if talent_value <= 12 {
+
+        let progression = &self.rules.talent_progression;
+
+        if talent_value <= progression.linear_limit {
             talent_value * stf_multiplier
         } else {
-            let cost_until_12 = 12 * stf_multiplier;
-            let above_12 = talent_value - 12;
-            let cost_above_12 = Self::cost_above_12(above_12, stf_multiplier);
-            cost_until_12 + cost_above_12
+            let cost_until_limit = progression.linear_limit * stf_multiplier;
+            let above_limit = talent_value - progression.linear_limit;
+            let cost_above_limit = Self::cost_above_limit(above_limit, stf_multiplier, progression.increment);
+            cost_until_limit + cost_above_limit
         }
     }
-    
-    /// Calculate the progressive cost for talent values above 12
-    fn cost_above_12(above_12: i32, stf_multiplier: i32) -> i32 {
+
+    /// Calculate the progressive cost for values above the linear limit:
+    /// every further point costs `increment` × `base_cost` more than the previous one
+    fn cost_above_limit(above_limit: i32, base_cost: i32, increment: i32) -> i32 {
         let mut total_cost = 0;
-        let mut current_increment_cost = stf_multiplier;
-        
-        for _ in 0..above_12 {
-            current_increment_cost += stf_multiplier;
+        let mut current_increment_cost = base_cost;
+
+        for _ in 0..above_limit {
+            current_increment_cost += increment * base_cost;
             total_cost += current_increment_cost;
         }
-        
+
         total_cost
     }
 
-    /// Calculate AP spent on skills using the progressive cost system
-    pub fn calculate_skills_ap(character: &Character) -> i32 {
-        character.get_skills()
-            .iter()
-            .filter_map(|skill| {
-                let talent_value = skill.system.get_talent_value()
-                    .and_then(|v| v.parse::<i32>().ok())?;
+    /// Costs of the single points above the linear limit, e.g. ["4", "6"] for two points at base cost 2
+    fn increments_above_limit(above_limit: i32, base_cost: i32, increment: i32) -> Vec<String> {
+        (1..=above_limit)
+            .map(|i| (base_cost + i * increment * base_cost).to_string())
+            .collect()
+    }
 
-                let stf_multiplier = skill.system.get_st_f_value()
-                    .and_then(|stf| Self::stf_to_multiplier(&stf))?;
+    /// Explain the progressive talent cost, e.g. "12 × 2 + 4 + 6" for value 14 at StF B
+    fn talent_value_formula(&self, talent_value: i32, stf_multiplier: i32) -> String {
+        let progression = &self.rules.talent_progression;
 
-                Some(Self::talent_value_to_ap_cost(talent_value, stf_multiplier))
-            })
+        if talent_value <= progression.linear_limit {
+            format!("{} × {}", talent_value.max(0), stf_multiplier)
+        } else {
+            let increments = Self::increments_above_limit(
+                talent_value - progression.linear_limit,
+                stf_multiplier,
+                progression.increment,
+            );
+            format!("{} × {} + {}", progression.linear_limit, stf_multiplier, increments.join(" + "))
+        }
+    }
+
+    /// Calculate AP spent on skills using the progressive cost system
+    pub fn calculate_skills_ap(&self, character: &Character) -> i32 {
+        self.get_skills_ap_breakdown(character)
+            .iter()
+            .map(|entry| entry.ap_cost)
             .sum()
     }
 
-    /// Calculate AP cost for combat skills (starting from base value of 6)
-    fn combat_skill_talent_value_to_ap_cost(talent_value: i32, stf_multiplier: i32) -> i32 {
-        const COMBAT_SKILL_BASE: i32 = 6;
+    /// Calculate AP cost for combat skills (starting from the free base value, 6 in the core rules)
+    fn combat_skill_talent_value_to_ap_cost(&self, talent_value: i32, stf_multiplier: i32) -> i32 {
+        let combat_skill_base = self.rules.combat_technique_free_value;
 
-        if talent_value <= COMBAT_SKILL_BASE {
+        if talent_value <= combat_skill_base {
             return 0; // No AP cost for values at or below base
         }
 
         // Calculate the total AP cost as if this were a regular talent
-        let total_ap_cost = Self::talent_value_to_ap_cost(talent_value, stf_multiplier);
+        let total_ap_cost = self.talent_value_to_ap_cost(talent_value, stf_multiplier);
 
-        // Subtract the cost of the first 6 levels (which are free for combat skills)
-        let free_levels_cost = Self::talent_value_to_ap_cost(COMBAT_SKILL_BASE, stf_multiplier);
+        // Subtract the cost of the first levels (which are free for combat skills)
+        let free_levels_cost = self.talent_value_to_ap_cost(combat_skill_base, stf_multiplier);
 
         total_ap_cost - free_levels_cost
     }
 
-    /// Calculate AP spent on combat skills using the progressive cost system
-    pub fn calculate_combat_skills_ap(character: &Character) -> i32 {
-        character.get_combat_skills()
-            .iter()
-            .filter_map(|combat_skill| {
-                let talent_value = combat_skill.system.get_talent_value()
-                    .and_then(|v| v.parse::<i32>().ok())?;
+    fn combat_skill_formula(&self, talent_value: i32, stf_multiplier: i32) -> String {
+        let combat_skill_base = self.rules.combat_technique_free_value;
 
-                let stf_multiplier = combat_skill.system.get_st_f_value()
-                    .and_then(|stf| Self::stf_to_multiplier(&stf))?;
+        if talent_value <= combat_skill_base {
+            format!("free (≤ {})", combat_skill_base)
+        } else {
+            format!("{} − {} × {}",
+                    self.talent_value_formula(talent_value, stf_multiplier),
+                    combat_skill_base,
+                    stf_multiplier)
+        }
+    }
 
-                Some(Self::combat_skill_talent_value_to_ap_cost(talent_value, stf_multiplier))
-            })
+    /// Calculate AP spent on combat skills using the progressive cost system
+    pub fn calculate_combat_skills_ap(&self, character: &Character) -> i32 {
+        self.get_combat_skills_ap_breakdown(character)
+            .iter()
+            .map(|entry| entry.ap_cost)
             .sum()
     }
 
     /// Generic method to calculate AP cost for learned abilities (spells, rituals, liturgies, ceremonies)
     /// These all follow the same pattern: learning cost + progression cost
-    fn learned_ability_talent_value_to_ap_cost(talent_value: i32, stf_multiplier: i32) -> i32 {
+    fn learned_ability_talent_value_to_ap_cost(&self, talent_value: i32, stf_multiplier: i32) -> i32 {
         let learning_ap_cost = stf_multiplier;
-        let progression_ap_cost = Self::talent_value_to_ap_cost(talent_value, stf_multiplier);
+        let progression_ap_cost = self.talent_value_to_ap_cost(talent_value, stf_multiplier);
 
         learning_ap_cost + progression_ap_cost
     }
 
-
-    /// Generic method to calculate AP for a collection of learned abilities (spells, rituals, liturgies, ceremonies)
-    fn calculate_learned_abilities_ap(items: &[&crate::character::Item]) -> i32 {
-        items
-            .iter()
-            .filter_map(|item| {
-                let talent_value = item.system.get_talent_value()
-                    .and_then(|v| v.parse::<i32>().ok())?;
-
-                let stf_multiplier = item.system.get_st_f_value()
-                    .and_then(|stf| Self::stf_to_multiplier(&stf))?;
-
-                Some(Self::learned_ability_talent_value_to_ap_cost(talent_value, stf_multiplier))
-            })
-            .sum()
+    fn learned_ability_formula(&self, talent_value: i32, stf_multiplier: i32) -> String {
+        format!("{} + {}", stf_multiplier, self.talent_value_formula(talent_value, stf_multiplier))
     }
 
     /// Calculate AP spent on spells and rituals using the generic method
-    pub fn calculate_spells_and_rituals_ap(character: &Character) -> i32 {
-        let items = character.get_spells_and_rituals();
-        Self::calculate_learned_abilities_ap(&items)
+    pub fn calculate_spells_and_rituals_ap(&self, character: &Character) -> i32 {
+        self.get_spells_and_rituals_ap_breakdown(character)
+            .iter()
+            .map(|entry| entry.ap_cost)
+            .sum()
     }
 
     /// Calculate AP spent on liturgies and ceremonies using the generic method
-    pub fn calculate_liturgies_and_ceremonies_ap(character: &Character) -> i32 {
-        let items = character.get_liturgies_and_ceremonies();
-        Self::calculate_learned_abilities_ap(&items)
+    pub fn calculate_liturgies_and_ceremonies_ap(&self, character: &Character) -> i32 {
+        self.get_liturgies_and_ceremonies_ap_breakdown(character)
+            .iter()
+            .map(|entry| entry.ap_cost)
+            .sum()
     }
 
     /// Calculate APs spent on magic tricks
-    pub fn calculate_magic_tricks_ap(character: &Character) -> i32 {
-        // Learning a magic trick costs 1 AP
-        character.get_magic_tricks().len() as i32
+    pub fn calculate_magic_tricks_ap(&self, character: &Character) -> i32 {
+        // Learning a magic trick costs a flat amount (1 AP in the core rules)
+        character.get_magic_tricks().len() as i32 * self.rules.magic_trick_cost
     }
 
     /// Calculate APs spent on blessings
-    pub fn calculate_blessings_ap(character: &Character) -> i32 {
-        // Learning a magic trick costs 1 AP
-        character.get_blessings().len() as i32
-    }
-
-    /// Convert StF value to multiplier: A=1, B=2, C=3, D=4
-    fn stf_to_multiplier(stf: &str) -> Option<i32> {
-        match stf.to_uppercase().as_str() {
-            "A" => Some(1),
-            "B" => Some(2),
-            "C" => Some(3),
-            "D" => Some(4),
-            _ => None,
-        }
+    pub fn calculate_blessings_ap(&self, character: &Character) -> i32 {
+        // Learning a blessing costs a flat amount (1 AP in the core rules)
+        character.get_blessings().len() as i32 * self.rules.blessing_cost
     }
 
-    /// Calculate AP spent on Life Points (LeP) advances using D-level talent progression
-    pub fn calculate_lep_ap(character: &Character) -> i32 {
-        if let Some(system) = &character.system {
-            if let Some(status) = &system.status {
-                if let Some(wounds) = &status.wounds {
-                    let advances = wounds.advances();
-                    if advances > 0 {
-                        // LeP advances use D-level talent cost (multiplier 4)
-                        const LEP_MULTIPLIER: i32 = 4;
-                        return Self::talent_value_to_ap_cost(advances, LEP_MULTIPLIER);
-                    }
-                }
-            }
-        }
-        0
+    /// Entries for items that cost a flat amount each (magic tricks, blessings)
+    fn flat_cost_entries(items: &[&Item], category: &str, ap_cost: i32) -> Vec<ApReportEntry> {
+        items
+            .iter()
+            .map(|item| ApReportEntry {
+                name: item.name.clone(),
+                item_type: item.kind.to_string(),
+                category: category.to_string(),
+                value: None,
+                stf: None,
+                raw_ap_value: None,
+                formula: ap_cost.to_string(),
+                ap_cost,
+                excluded: false,
+            })
+            .collect()
     }
 
-    /// Calculate AP spent on Astral Energy (AsP) advances using D-level talent progression
-    pub fn calculate_asp_ap(character: &Character) -> i32 {
-        if let Some(system) = &character.system {
-            if let Some(status) = &system.status {
-                if let Some(astral_energy) = &status.astralenergy {
-                    let advances = astral_energy.advances();
-                    let rebuy_points = astral_energy.rebuy_points();
+    /// Convert StF value to multiplier using the improvement cost table (A=1, B=2, C=3, D=4)
+    fn stf_to_multiplier(&self, stf: &str) -> Option<i32> {
+        self.rules.improvement_cost(stf)
+    }
 
-                    let mut total_cost = 0;
+    /// Energy advances use the talent progression of the energy StF (D in the core rules),
+    /// rebuy points (cost to recover permanently lost AsP/KaP) cost a flat amount each
+    pub fn energy_ap_cost(&self, advances: i32, rebuy_points: i32) -> i32 {
+        let rules = self.rules;
 
-                    // Calculate cost for advances using D-level talent progression
-                    if advances > 0 {
-                        const ASP_MULTIPLIER: i32 = 4;
-                        total_cost += Self::talent_value_to_ap_cost(advances, ASP_MULTIPLIER);
-                    }
+        let mut total_cost = 0;
 
-                    // Rebuy points cost 2 AP each (cost to recover permanently lost AsP)
-                    if rebuy_points > 0 {
-                        total_cost += rebuy_points * 2;
-                    }
+        if advances > 0 {
+            let energy_multiplier = rules.improvement_cost(&rules.energy_improvement_cost).unwrap_or_default();
+            total_cost += self.talent_value_to_ap_cost(advances, energy_multiplier);
+        }
 
-                    return total_cost;
-                }
-            }
+        if rebuy_points > 0 {
+            total_cost += rebuy_points * rules.energy_rebuy_cost;
         }
-        0
+
+        total_cost
+    }
+
+    /// Calculate AP spent on Life Points (LeP) advances using D-level talent progression
+    pub fn calculate_lep_ap(&self, character: &Character) -> i32 {
+        character.system
+            .as_ref()
+            .and_then(|s| s.status.as_ref())
+            .and_then(|s| s.wounds.as_ref())
+            .map(|wounds| self.energy_ap_cost(wounds.advances(), 0))
+            .unwrap_or(0)
+    }
+
+    /// Calculate AP spent on Astral Energy (AsP) advances using D-level talent progression
+    pub fn calculate_asp_ap(&self, character: &Character) -> i32 {
+        character.system
+            .as_ref()
+            .and_then(|s| s.status.as_ref())
+            .and_then(|s| s.astralenergy.as_ref())
+            .map(|asp| self.energy_ap_cost(asp.advances(), asp.rebuy_points()))
+            .unwrap_or(0)
     }
 
     /// Calculate AP spent on Karma Energy (KaP) advances using D-level talent progression
-    pub fn calculate_kap_ap(character: &Character) -> i32 {
-        if let Some(system) = &character.system {
-            if let Some(status) = &system.status {
-                if let Some(karma_energy) = &status.karmaenergy {
-                    let advances = karma_energy.advances();
-                    let rebuy_points = karma_energy.rebuy_points();
+    pub fn calculate_kap_ap(&self, character: &Character) -> i32 {
+        character.system
+            .as_ref()
+            .and_then(|s| s.status.as_ref())
+            .and_then(|s| s.karmaenergy.as_ref())
+            .map(|kap| self.energy_ap_cost(kap.advances(), kap.rebuy_points()))
+            .unwrap_or(0)
+    }
 
-                    let mut total_cost = 0;
+    // Calculate AP spent on energy values (LeP/AsP/KaP)
+    pub fn calculate_energy_values_ap(&self, character: &Character) -> i32 {
+        self.calculate_lep_ap(character) + self.calculate_asp_ap(character) + self.calculate_kap_ap(character)
+    }
 
-                    // Calculate cost for advances using D-level talent progression
-                    if advances > 0 {
-                        const KAP_MULTIPLIER: i32 = 4;
-                        total_cost += Self::talent_value_to_ap_cost(advances, KAP_MULTIPLIER);
-                    }
+    /// Get breakdown of energy AP costs; energies without advances or rebuy points are omitted
+    pub fn get_energy_values_ap_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        let Some(status) = character.system.as_ref().and_then(|s| s.status.as_ref()) else {
+            return Vec::new();
+        };
 
-                    // Rebuy points cost 2 AP each (cost to recover permanently lost KaP)
-                    if rebuy_points > 0 {
-                        total_cost += rebuy_points * 2;
-                    }
+        let energies = [
+            ("Life Points (LeP)", status.wounds.as_ref().map(|w| (w.advances(), 0))),
+            ("Astral Energy (AsP)", status.astralenergy.as_ref().map(|a| (a.advances(), a.rebuy_points()))),
+            ("Karma Energy (KaP)", status.karmaenergy.as_ref().map(|k| (k.advances(), k.rebuy_points()))),
+        ];
+
+        let rules = self.rules;
+        let energy_multiplier = rules.improvement_cost(&rules.energy_improvement_cost).unwrap_or_default();
 
-                    return total_cost;
+        energies
+            .into_iter()
+            .filter_map(|(name, values)| {
+                let (advances, rebuy_points) = values?;
+                if advances <= 0 && rebuy_points <= 0 {
+                    return None;
                 }
-            }
-        }
-        0
+
+                let mut formula = self.talent_value_formula(advances, energy_multiplier);
+                if rebuy_points > 0 {
+                    formula = format!("{} + {} × {} (rebuy)", formula, rebuy_points, rules.energy_rebuy_cost);
+                }
+
+                Some(ApReportEntry {
+                    name: name.to_string(),
+                    item_type: "energy".to_string(),
+                    category: category::ENERGIES.to_string(),
+                    value: Some(advances),
+                    stf: Some(rules.energy_improvement_cost.clone()),
+                    raw_ap_value: None,
+                    formula,
+                    ap_cost: self.energy_ap_cost(advances, rebuy_points),
+                    excluded: false,
+                })
+            })
+            .collect()
     }
 
-    // Calculate AP spent on energy values (LeP/AsP/KaP)
-    pub fn calculate_energy_values_ap(character: &Character) -> i32 {
-        Self::calculate_lep_ap(character) + Self::calculate_asp_ap(character) + Self::calculate_kap_ap(character)
+    /// Calculate AP spent on the species (e.g. 18 AP for elves, 61 AP for dwarves)
+    pub fn calculate_species_ap(&self, character: &Character) -> i32 {
+        self.species_entries(character, &mut Vec::new())
+            .iter()
+            .map(|entry| entry.ap_cost)
+            .sum()
     }
 
-    /// Calculate AP cost for a characteristic value (possibly non-humans need more sophistication?)
-    fn characteristic_to_ap_cost(value: i32) -> Result<i32, &'static str> {
-        if value < 8 {
-            return Err("Value must be greater or equal 8");
-        } else if value <= 14 {
-            return Ok((value - 8) * 15);
-        } else {
-            let cost_until_14 = 6 * 15; // Cost for values 8-14
-            let above_14 = value - 14;
-            let mut cost_above_14 = 0;
+    /// Species AP cost from the species table. Skipped if the export carries a species item
+    /// with its own APValue, which is then already counted as an AP item.
+    fn species_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
+        if character.get_species_items().iter().any(|item| item.purchase().ap_value.is_some()) {
+            return Vec::new();
+        }
 
-            for i in 1..=above_14 {
-                cost_above_14 += 15 + (i * 15);
-            }
+        let Some(species_name) = character.species_name() else {
+            return Vec::new();
+        };
 
-            let cost = cost_until_14 + cost_above_14;
-            Ok(cost)
+        match self.rules.find_species(&species_name) {
+            Some(species) => vec![ApReportEntry {
+                name: species.name.clone(),
+                item_type: "species".to_string(),
+                category: category::SPECIES.to_string(),
+                value: None,
+                stf: None,
+                raw_ap_value: None,
+                formula: species.ap_cost.to_string(),
+                ap_cost: species.ap_cost,
+                excluded: false,
+            }],
+            None => {
+                warnings.push(ApWarning {
+                    name: species_name,
+                    reason: "Unknown species, species AP cost not included".to_string(),
+                });
+                Vec::new()
+            }
         }
     }
 
+    /// Calculate AP cost for a characteristic value (species modifiers are not bought and therefore excluded)
+    pub fn characteristic_to_ap_cost(&self, value: i32) -> Result<i32, String> {
+        let costs = &self.rules.characteristic;
 
-    /// Calculate total AP spent on characteristics
-    pub fn calculate_characteristics_ap(character: &Character) -> i32 {
-        if let Some(system) = &character.system {
-            if let Some(characteristics) = &system.characteristics {
-                let mut total_cost = 0;
-
-                // List of all characteristics to check
-                let char_list = [
-                    &characteristics.mu,
-                    &characteristics.kl,
-                    &characteristics.in_,
-                    &characteristics.ch,
-                    &characteristics.ff,
-                    &characteristics.ge,
-                    &characteristics.ko,
-                    &characteristics.kk,
-                ];
-
-                for char_opt in char_list {
-                    if let Some(char_value) = char_opt {
-                        let nominal_value = char_value.nominal_value();
-                        if let Ok(ap_cost) = Self::characteristic_to_ap_cost(nominal_value) {
-                            total_cost += ap_cost;
-                        }
-                    }
-                }
+        if value < costs.base_value {
+            Err(format!("Value must be greater or equal {}", costs.base_value))
+        } else if value <= costs.linear_limit {
+            Ok((value - costs.base_value) * costs.cost_per_point)
+        } else {
+            // Cost for values from the base value up to the linear limit (8-14 in the core rules)
+            let cost_until_limit = (costs.linear_limit - costs.base_value) * costs.cost_per_point;
+            let above_limit = value - costs.linear_limit;
+            let cost_above_limit = Self::cost_above_limit(above_limit, costs.cost_per_point, costs.increment);
 
-                return total_cost;
-            }
+            Ok(cost_until_limit + cost_above_limit)
         }
-        0
+    }
+
+    /// Explain the characteristic cost, e.g. "6 × 15 + 30 + 45" for value 16
+    fn characteristic_formula(&self, value: i32) -> String {
+        let costs = &self.rules.characteristic;
+
+        if value <= costs.linear_limit {
+            format!("({} − {}) × {}", value, costs.base_value, costs.cost_per_point)
+        } else {
+            let increments = Self::increments_above_limit(
+                value - costs.linear_limit,
+                costs.cost_per_point,
+                costs.increment,
+            );
+            format!("{} × {} + {}",
+                    costs.linear_limit - costs.base_value,
+                    costs.cost_per_point,
+                    increments.join(" + "))
+        }
+    }
+
+    /// Calculate total AP spent on characteristics
+    pub fn calculate_characteristics_ap(&self, character: &Character) -> i32 {
+        self.get_characteristics_ap_breakdown(character)
+            .iter()
+            .map(|entry| entry.ap_cost)
+            .sum()
     }
 
     /// Get breakdown of characteristics AP costs for debugging
-    pub fn get_characteristics_ap_breakdown(character: &Character) -> Vec<(String, i32, i32)> {
+    pub fn get_characteristics_ap_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        self.characteristics_entries(character, &mut Vec::new())
+    }
+
+    fn characteristics_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
         let mut breakdown = Vec::new();
 
         if let Some(system) = &character.system {
             if let Some(characteristics) = &system.characteristics {
-                let char_list = [
-                    ("Mut", &characteristics.mu),
-                    ("Klugheit", &characteristics.kl),
-                    ("Intuition", &characteristics.in_),
-                    ("Charisma", &characteristics.ch),
-                    ("Fingerfertigkeit", &characteristics.ff),
-                    ("Gewandtheit", &characteristics.ge),
-                    ("Konstitution", &characteristics.ko),
-                    ("Körperkraft", &characteristics.kk),
-                ];
-
-                for (name, char_opt) in char_list {
-                    if let Some(char_value) = char_opt {
+                for (key, name) in Characteristics::NAMES {
+                    if let Some(char_value) = characteristics.get(key) {
                         let nominal_value = char_value.nominal_value();
-                        if let Ok(ap_cost) = Self::characteristic_to_ap_cost(nominal_value) {
-                            breakdown.push((name.to_string(), nominal_value, ap_cost));
+                        match self.characteristic_to_ap_cost(nominal_value) {
+                            Ok(ap_cost) => breakdown.push(ApReportEntry {
+                                name: name.to_string(),
+                                item_type: "characteristic".to_string(),
+                                category: category::CHARACTERISTICS.to_string(),
+                                value: Some(nominal_value),
+                                stf: None,
+                                raw_ap_value: None,
+                                formula: self.characteristic_formula(nominal_value),
+                                ap_cost,
+                                excluded: false,
+                            }),
+                            Err(reason) => warnings.push(ApWarning {
+                                name: name.to_string(),
+                                reason: format!("{} (value {})", reason, nominal_value),
+                            }),
                         }
                     }
                 }
@@ -606,75 +746,193 @@ impl ApCalculator {
         breakdown
     }
 
-    /// Get detailed breakdown of skills AP costs
-    pub fn get_skills_ap_breakdown(character: &Character) -> Vec<(String, i32, String, i32)> {
-        character.get_skills()
-            .iter()
-            .filter_map(|skill| {
-                let talent_value = skill.system.get_talent_value()
-                    .and_then(|v| v.parse::<i32>().ok())?;
-
-                let stf_value = skill.system.get_st_f_value()?;
-                let stf_multiplier = Self::stf_to_multiplier(&stf_value)?;
-                let ap_cost = Self::talent_value_to_ap_cost(talent_value, stf_multiplier);
-
-                // Only include skills that actually cost AP
-                if ap_cost > 0 {
-                    Some((skill.name.clone(), talent_value, stf_value, ap_cost))
-                } else {
-                    None
-                }
-            })
-            .collect()
-    }
+    /// Read talent value and StF of a talent-like item, or explain why it cannot be costed
+    fn talent_value_and_stf(&self, item: &Item) -> Result<(i32, String, i32), String> {
+        let (talent_value, stf) = Self::talent_data(item)?;
+        let talent_value = talent_value.ok_or_else(|| match item.system.get_talent_value() {
+            Some(raw_talent_value) => format!("Unparseable talentValue '{}'", raw_talent_value),
+            None => "Missing talentValue".to_string(),
+        })?;
 
-    /// Get detailed breakdown of combat skills AP costs (for debugging)
-    pub fn get_combat_skills_ap_breakdown(character: &Character) -> Vec<(String, i32, String, i32)> {
-        character.get_combat_skills()
-            .iter()
-            .filter_map(|combat_skill| {
-                let talent_value = combat_skill.system.get_talent_value()
-                    .and_then(|v| v.parse::<i32>().ok())?;
+        let stf = stf.ok_or_else(|| "Missing StF".to_string())?;
+        let stf_multiplier = self.stf_to_multiplier(&stf)
+            .ok_or_else(|| format!("Unknown StF '{}'", stf))?;
 
-                let stf = combat_skill.system.get_st_f_value()?;
-                let stf_multiplier = Self::stf_to_multiplier(&stf)?;
-                let ap_cost = Self::combat_skill_talent_value_to_ap_cost(talent_value, stf_multiplier);
+        Ok((talent_value, stf, stf_multiplier))
+    }
 
-                Some((combat_skill.name.clone(), talent_value, stf, ap_cost))
-            })
-            .collect()
+    /// Talent value and StF from the typed view of a talent-like item
+    fn talent_data(item: &Item) -> Result<(Option<i32>, Option<String>), String> {
+        match item.data() {
+            ItemData::Talent(talent) => Ok((talent.talent_value, talent.improvement_cost)),
+            ItemData::CombatSkill(combat_skill) => Ok((combat_skill.talent_value, combat_skill.improvement_cost)),
+            _ => Err(format!("Item type '{}' has no talent value", item.kind)),
+        }
     }
 
-    /// Generic method to get breakdown for learned abilities (spells, rituals, liturgies, ceremonies)
-    /// Returns: (name, talent_value, stf_multiplier, ap_cost)
-    fn get_learned_abilities_ap_breakdown(items: &[&crate::character::Item]) -> Vec<(String, i32, String, i32)> {
+    /// Generic method to get breakdown for talent-like items (skills, combat skills, spells, rituals, liturgies, ceremonies)
+    /// Items that cannot be costed are skipped and reported in `warnings`
+    fn talent_entries(
+        &self,
+        items: &[&Item],
+        category: &str,
+        cost_fn: TalentCostFn<'a>,
+        formula_fn: TalentFormulaFn<'a>,
+        warnings: &mut Vec<ApWarning>,
+    ) -> Vec<ApReportEntry> {
         items
             .iter()
             .filter_map(|item| {
-                let talent_value = item.system.get_talent_value()
-                    .and_then(|v| v.parse::<i32>().ok())?;
-
-                let stf = item.system.get_st_f_value()?;
-                let stf_multiplier = Self::stf_to_multiplier(&stf)?;
-                let ap_cost = Self::learned_ability_talent_value_to_ap_cost(talent_value, stf_multiplier);
-
-                Some((item.name.clone(), talent_value, stf, ap_cost))
+                let (talent_value, stf, stf_multiplier) = match self.talent_value_and_stf(item) {
+                    Ok(values) => values,
+                    Err(reason) => {
+                        warnings.push(ApWarning {
+                            name: item.name.clone(),
+                            reason: format!("{}, skipped in {}", reason, category),
+                        });
+                        return None;
+                    }
+                };
+
+                Some(ApReportEntry {
+                    name: item.name.clone(),
+                    item_type: item.kind.to_string(),
+                    category: category.to_string(),
+                    value: Some(talent_value),
+                    stf: Some(stf),
+                    raw_ap_value: None,
+                    formula: formula_fn(self, talent_value, stf_multiplier),
+                    ap_cost: cost_fn(self, talent_value, stf_multiplier),
+                    excluded: false,
+                })
             })
             .collect()
     }
 
+    /// Get detailed breakdown of skills AP costs
+    pub fn get_skills_ap_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        self.skills_entries(character, &mut Vec::new())
+    }
+
+    fn skills_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
+        let mut breakdown = self.talent_entries(
+            &character.get_skills(),
+            category::SKILLS,
+            Self::talent_value_to_ap_cost,
+            Self::talent_value_formula,
+            warnings,
+        );
+
+        // Only include skills that actually cost AP
+        breakdown.retain(|entry| entry.ap_cost > 0);
+        breakdown
+    }
+
+    /// Get detailed breakdown of combat skills AP costs (for debugging)
+    pub fn get_combat_skills_ap_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        self.combat_skills_entries(character, &mut Vec::new())
+    }
+
+    fn combat_skills_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
+        self.talent_entries(
+            &character.get_combat_skills(),
+            category::COMBAT_SKILLS,
+            Self::combat_skill_talent_value_to_ap_cost,
+            Self::combat_skill_formula,
+            warnings,
+        )
+    }
+
     /// Get detailed breakdown for spells and rituals AP calculation
-    /// Returns: (name, talent_value, stf_multiplier, ap_cost)
-    pub fn get_spells_and_rituals_ap_breakdown(character: &Character) -> Vec<(String, i32, String, i32)> {
-        let items = character.get_spells_and_rituals();
-        Self::get_learned_abilities_ap_breakdown(&items)
+    pub fn get_spells_and_rituals_ap_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        self.spells_and_rituals_entries(character, &mut Vec::new())
+    }
+
+    fn spells_and_rituals_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
+        self.talent_entries(
+            &character.get_spells_and_rituals(),
+            category::SPELLS_RITUALS,
+            Self::learned_ability_talent_value_to_ap_cost,
+            Self::learned_ability_formula,
+            warnings,
+        )
     }
 
     /// Get detailed breakdown for liturgies and ceremonies AP calculation
-    /// Returns: (name, talent_value, stf_multiplier, ap_cost)
-    pub fn get_liturgies_and_ceremonies_ap_breakdown(character: &Character) -> Vec<(String, i32, String, i32)> {
-        let items = character.get_liturgies_and_ceremonies();
-        Self::get_learned_abilities_ap_breakdown(&items)
+    pub fn get_liturgies_and_ceremonies_ap_breakdown(&self, character: &Character) -> Vec<ApReportEntry> {
+        self.liturgies_and_ceremonies_entries(character, &mut Vec::new())
+    }
+
+    fn liturgies_and_ceremonies_entries(&self, character: &Character, warnings: &mut Vec<ApWarning>) -> Vec<ApReportEntry> {
+        self.talent_entries(
+            &character.get_liturgies_and_ceremonies(),
+            category::LITURGIES_CEREMONIES,
+            Self::learned_ability_talent_value_to_ap_cost,
+            Self::learned_ability_formula,
+            warnings,
+        )
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    const PRINCIPLES_SOURCE_ID: &str = "Compendium.dsa5-core.coredisadvantages.Item.principles";
+    const OBLIGATIONS_SOURCE_ID: &str = "Compendium.dsa5-core.coredisadvantages.Item.obligations";
+
+    fn disadvantage(id: &str, name: &str, step: i32, source_id: &str) -> Value {
+        json!({
+            "_id": id, "name": name, "type": "disadvantage", "img": null,
+            "system": { "APValue": { "value": "-10" }, "step": { "value": step } },
+            "_stats": { "compendiumSource": source_id },
+        })
     }
 
-}
\ No newline at end of file
+    fn hero(items: Vec<Value>) -> Character {
+        serde_json::from_value(json!({ "name": "Test", "type": "character", "img": null, "items": items })).unwrap()
+    }
+
+    /// Rules that identify the entries by source id, as given in a rules file
+    fn rules_with_source_ids() -> Rules {
+        let mut rules = Rules::builtin().clone();
+        for (key, source_id) in [("principles", PRINCIPLES_SOURCE_ID), ("obligations", OBLIGATIONS_SOURCE_ID)] {
+            rules.highest_step_only.get_mut(key).unwrap().source_ids.push(source_id.to_string());
+        }
+        rules
+    }
+
+    fn counted_costs(entries: &[ApReportEntry]) -> Vec<(&str, i32)> {
+        let mut costs: Vec<(&str, i32)> = entries.iter().filter(|e| !e.excluded).map(|e| (e.name.as_str(), e.ap_cost)).collect();
+        costs.sort();
+        costs
+    }
+
+    #[test]
+    fn renamed_items_of_an_english_export_are_grouped_by_source_id() {
+        let character = hero(vec![
+            disadvantage("p1", "Vow of the Order", 2, PRINCIPLES_SOURCE_ID),
+            disadvantage("p2", "Tenets of Hesinde", 1, PRINCIPLES_SOURCE_ID),
+            disadvantage("o1", "Debt to the Guild", 1, OBLIGATIONS_SOURCE_ID),
+            disadvantage("o2", "Oath to the Baron", 3, OBLIGATIONS_SOURCE_ID),
+        ]);
+        let rules = rules_with_source_ids();
+        let entries = ApCalculator::new(&rules).get_ap_items_breakdown(&character);
+
+        assert_eq!(counted_costs(&entries), [("Oath to the Baron", -30), ("Vow of the Order", -20)]);
+        assert_eq!(entries.iter().filter(|e| e.excluded).count(), 2);
+    }
+
+    #[test]
+    fn english_base_names_are_matched_without_source_id() {
+        let character = hero(vec![
+            disadvantage("p1", "Principles (Church of Praios)", 1, ""),
+            disadvantage("p2", "Principles (Code of Honor)", 2, ""),
+        ]);
+        let entries = ApCalculator::default().get_ap_items_breakdown(&character);
+
+        assert_eq!(counted_costs(&entries), [("Principles (Code of Honor)", -20)]);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::character::{Character, CombatSkillSystem, Item, ItemData};
+use serde::Serialize;
+
+/// Characteristic value above which every full 3 points give +1 on AT, PA and FK
+const CHARACTERISTIC_BONUS_THRESHOLD: i32 = 8;
+const CHARACTERISTIC_BONUS_STEP: i32 = 3;
+
+/// A derived value with a human-readable explanation of how it was calculated
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivedValue {
+    pub value: i32,
+    pub formula: String,
+}
+
+/// Attack, parry and ranged combat values of one combat technique
+#[derive(Debug, Clone, Serialize)]
+pub struct CombatTechniqueValues {
+    pub name: String,
+    /// Kampftechnikwert (KtW)
+    pub technique_value: i32,
+    /// Leading attributes (Leiteigenschaften) as keys, e.g. ["ge", "kk"]
+    pub leading_attributes: Vec<String>,
+    pub ranged: bool,
+    /// Attacke (melee only)
+    pub attack: Option<DerivedValue>,
+    /// Parade (melee only)
+    pub parry: Option<DerivedValue>,
+    /// Fernkampf (ranged only)
+    pub ranged_attack: Option<DerivedValue>,
+}
+
+/// Combat values derived from the combat techniques and characteristics
+#[derive(Debug, Clone, Serialize)]
+pub struct CombatValues {
+    /// Initiative base value (INI)
+    pub initiative: Option<DerivedValue>,
+    /// Ausweichen (AW)
+    pub dodge: Option<DerivedValue>,
+    pub techniques: Vec<CombatTechniqueValues>,
+}
+
+impl CombatValues {
+    pub fn calculate(character: &Character) -> Self {
+        let characteristics = character.system.as_ref().and_then(|s| s.characteristics.as_ref());
+        let value_of = |key: &str| characteristics.and_then(|c| c.get(key)).map(|c| c.value());
+
+        let initiative = value_of("mu").zip(value_of("ge")).map(|(mu, ge)| DerivedValue {
+            value: half_rounded(mu + ge),
+            formula: format!("INI = (MU + GE) / 2 = ({} + {}) / 2, rounded", mu, ge),
+        });
+
+        let dodge = value_of("ge").map(|ge| DerivedValue {
+            value: half_rounded(ge),
+            formula: format!("AW = GE / 2 = {} / 2, rounded", ge),
+        });
+
+        let techniques = character
+            .get_combat_skills()
+            .into_iter()
+            .filter_map(|item| match &item.data() {
+                ItemData::CombatSkill(combat_skill) => Some(Self::technique_values(item, combat_skill, &value_of)),
+                _ => None,
+            })
+            .collect();
+
+        Self { initiative, dodge, techniques }
+    }
+
+    fn technique_values(
+        item: &Item,
+        combat_skill: &CombatSkillSystem,
+        value_of: &dyn Fn(&str) -> Option<i32>,
+    ) -> CombatTechniqueValues {
+        let technique_value = combat_skill.talent_value.unwrap_or(0);
+        let leading_attributes = combat_skill.leading_attributes.clone();
+
+        let ranged = combat_skill.ranged
+            .unwrap_or_else(|| leading_attributes.iter().any(|key| key == "ff"));
+
+        let mut values = CombatTechniqueValues {
+            name: item.name.clone(),
+            technique_value,
+            leading_attributes: leading_attributes.clone(),
+            ranged,
+            attack: None,
+            parry: None,
+            ranged_attack: None,
+        };
+
+        if ranged {
+            values.ranged_attack = value_of("ff").map(|ff| DerivedValue {
+                value: technique_value + characteristic_bonus(ff),
+                formula: format!("FK = KtW + (FF − 8) / 3 = {} + {}", technique_value, characteristic_bonus(ff)),
+            });
+        } else {
+            values.attack = value_of("mu").map(|mu| DerivedValue {
+                value: technique_value + characteristic_bonus(mu),
+                formula: format!("AT = KtW + (MU − 8) / 3 = {} + {}", technique_value, characteristic_bonus(mu)),
+            });
+
+            // The highest leading attribute counts if a technique has several
+            let leading = leading_attributes
+                .iter()
+                .filter_map(|key| value_of(key).map(|value| (key, value)))
+                .max_by_key(|(_, value)| *value);
+
+            values.parry = leading.map(|(key, value)| {
+                let base = (technique_value + 1) / 2;
+                DerivedValue {
+                    value: base + characteristic_bonus(value),
+                    formula: format!("PA = KtW / 2 (rounded up) + ({} − 8) / 3 = {} + {}",
+                                     key.to_uppercase(), base, characteristic_bonus(value)),
+                }
+            });
+        }
+
+        values
+    }
+}
+
+/// +1 for every full 3 points above 8
+pub fn characteristic_bonus(value: i32) -> i32 {
+    ((value - CHARACTERISTIC_BONUS_THRESHOLD) / CHARACTERISTIC_BONUS_STEP).max(0)
+}
+
+/// Half of a value, rounded half up
+fn half_rounded(value: i32) -> i32 {
+    (value + 1).div_euclid(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn combat_technique(name: &str, value: i32, leading_attributes: &str, weapon_type: &str) -> Value {
+        json!({
+            "_id": name, "name": name, "type": "combatskill", "img": null,
+            "system": {
+                "talentValue": { "value": value }, "StF": { "value": "C" },
+                "guidevalue": { "value": leading_attributes }, "weapontype": { "value": weapon_type },
+            },
+        })
+    }
+
+    /// MU 14, GE 13, KK 16, FF 11
+    fn hero(items: Vec<Value>) -> Character {
+        let characteristics: serde_json::Map<String, Value> = [("mu", 14), ("ge", 13), ("kk", 16), ("ff", 11)]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), json!({ "initial": 8, "advances": value - 8 })))
+            .collect();
+        serde_json::from_value(json!({
+            "name": "Test", "type": "character", "img": null, "items": items,
+            "system": { "characteristics": characteristics },
+        }))
+        .unwrap()
+    }
+
+    fn technique<'a>(values: &'a CombatValues, name: &str) -> &'a CombatTechniqueValues {
+        values.techniques.iter().find(|technique| technique.name == name).unwrap()
+    }
+
+    #[test]
+    fn initiative_and_dodge_are_rounded_half_up() {
+        let values = CombatValues::calculate(&hero(Vec::new()));
+
+        // (14 + 13) / 2 = 13.5 and 13 / 2 = 6.5
+        assert_eq!(values.initiative.map(|v| v.value), Some(14));
+        assert_eq!(values.dodge.map(|v| v.value), Some(7));
+    }
+
+    #[test]
+    fn attack_parry_and_ranged_attack_of_the_techniques() {
+        let values = CombatValues::calculate(&hero(vec![
+            combat_technique("Schwerter", 11, "ge/kk", "melee"),
+            combat_technique("Dolche", 7, "ge", "melee"),
+            combat_technique("Bögen", 10, "ff", "range"),
+        ]));
+
+        let swords = technique(&values, "Schwerter");
+        // AT = 11 + (14 − 8) / 3; PA = 11 / 2 rounded up + (KK 16 − 8) / 3, the higher leading attribute
+        assert_eq!(swords.attack.as_ref().map(|v| v.value), Some(13));
+        assert_eq!(swords.parry.as_ref().map(|v| v.value), Some(8));
+        assert!(swords.ranged_attack.is_none());
+
+        // PA = 7 / 2 rounded up + (13 − 8) / 3
+        assert_eq!(technique(&values, "Dolche").parry.as_ref().map(|v| v.value), Some(5));
+
+        let bows = technique(&values, "Bögen");
+        assert_eq!(bows.ranged_attack.as_ref().map(|v| v.value), Some(11));
+        assert!(bows.attack.is_none() && bows.parry.is_none());
+    }
+
+    #[test]
+    fn characteristic_bonus_per_full_three_points_above_eight() {
+        let bonuses: Vec<i32> = [7, 8, 10, 11, 13, 14, 17].into_iter().map(characteristic_bonus).collect();
+        assert_eq!(bonuses, [0, 0, 0, 1, 1, 2, 3]);
+    }
+}
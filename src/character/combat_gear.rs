@@ -0,0 +1,378 @@
+use crate::character::{Character, CombatTechniqueValues, CombatValues, DerivedValue, Item, ItemData, ItemKind, WeaponSystem};
+use serde::Serialize;
+use std::fmt;
+
+/// Trefferpunkte (TP) as dice plus a fixed modifier, e.g. "1W6+4"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DamageDice {
+    pub dice: i32,
+    pub sides: i32,
+    pub modifier: i32,
+}
+
+impl DamageDice {
+    /// Parse "1W6+4", "2W6", "W3-1" or "1d6 + 4" (German "W" or English "d")
+    pub fn parse(text: &str) -> Option<Self> {
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+        let (dice, rest) = text.split_once(['w', 'd'])?;
+        let dice = if dice.is_empty() { 1 } else { dice.parse().ok()? };
+
+        let sign_position = rest.find(['+', '-']).unwrap_or(rest.len());
+        let (sides, modifier) = rest.split_at(sign_position);
+        let sides = sides.parse().ok()?;
+        let modifier = if modifier.is_empty() { 0 } else { modifier.parse().ok()? };
+
+        (dice > 0 && sides > 0).then_some(Self { dice, sides, modifier })
+    }
+
+    pub fn with_bonus(&self, bonus: i32) -> Self {
+        Self { modifier: self.modifier + bonus, ..*self }
+    }
+
+    pub fn minimum(&self) -> i32 {
+        self.dice + self.modifier
+    }
+
+    pub fn maximum(&self) -> i32 {
+        self.dice * self.sides + self.modifier
+    }
+
+    pub fn average(&self) -> f64 {
+        self.dice as f64 * (self.sides as f64 + 1.0) / 2.0 + self.modifier as f64
+    }
+}
+
+impl fmt::Display for DamageDice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}W{}", self.dice, self.sides)?;
+        if self.modifier != 0 {
+            write!(f, "{:+}", self.modifier)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reichweite of a melee weapon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Reach {
+    Short,
+    Medium,
+    Long,
+}
+
+impl Reach {
+    /// Foundry stores "short", "medium" or "long"; German names are accepted as well
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "short" | "kurz" => Some(Reach::Short),
+            "medium" | "mittel" => Some(Reach::Medium),
+            "long" | "lang" => Some(Reach::Long),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reach::Short => "kurz",
+            Reach::Medium => "mittel",
+            Reach::Long => "lang",
+        }
+    }
+}
+
+/// Range bands of a ranged weapon in Schritt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RangeBands {
+    pub close: i32,
+    pub medium: i32,
+    pub far: i32,
+}
+
+impl RangeBands {
+    /// Parse "10/50/80"
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut bands = text.split('/').map(|band| band.trim().parse::<i32>());
+        let close = bands.next()?.ok()?;
+        let medium = bands.next()?.ok()?;
+        let far = bands.next()?.ok()?;
+        bands.next().is_none().then_some(Self { close, medium, far })
+    }
+}
+
+impl fmt::Display for RangeBands {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.close, self.medium, self.far)
+    }
+}
+
+/// A weapon with its final AT/PA/FK and TP, combining its stats with the combat technique
+#[derive(Debug, Clone, Serialize)]
+pub struct WeaponStats {
+    pub name: String,
+    pub ranged: bool,
+    /// `worn` flag of the weapon (carried in hand), `None` if the export has none
+    pub equipped: Option<bool>,
+    /// Name of the combat technique as stored on the weapon
+    pub technique: Option<String>,
+    /// The combat technique was not found among the character's combat techniques
+    pub technique_missing: bool,
+    pub damage: Option<DamageDice>,
+    pub damage_threshold: Option<i32>,
+    /// Leading attributes of the damage bonus, e.g. ["ge", "kk"]
+    pub damage_attributes: Vec<String>,
+    /// +1 TP for every point of the highest damage attribute above the threshold
+    pub damage_bonus: i32,
+    pub attack_modifier: i32,
+    pub parry_modifier: i32,
+    pub reach: Option<Reach>,
+    pub range: Option<RangeBands>,
+    pub ammunition_group: Option<String>,
+    /// Attacke with the weapon (melee)
+    pub attack: Option<DerivedValue>,
+    /// Parade with the weapon (melee)
+    pub parry: Option<DerivedValue>,
+    /// Fernkampf with the weapon (ranged)
+    pub ranged_attack: Option<DerivedValue>,
+    /// Damage dice including the damage bonus
+    pub hit_points: Option<DamageDice>,
+}
+
+/// Armor with its protection (RS) and encumbrance (BE)
+#[derive(Debug, Clone, Serialize)]
+pub struct ArmorStats {
+    pub name: String,
+    pub protection: Option<i32>,
+    pub encumbrance: Option<i32>,
+    pub worn: Option<bool>,
+}
+
+/// Ammunition, matched to ranged weapons by its ammunition group
+#[derive(Debug, Clone, Serialize)]
+pub struct AmmunitionStock {
+    pub name: String,
+    pub group: Option<String>,
+    pub quantity: i32,
+}
+
+/// Weapons, armor and ammunition of a character
+#[derive(Debug, Clone, Serialize)]
+pub struct CombatGear {
+    pub weapons: Vec<WeaponStats>,
+    pub armor: Vec<ArmorStats>,
+    pub ammunition: Vec<AmmunitionStock>,
+}
+
+impl CombatGear {
+    pub fn calculate(character: &Character) -> Self {
+        let combat_values = CombatValues::calculate(character);
+        let characteristics = character.system.as_ref().and_then(|s| s.characteristics.as_ref());
+        let value_of = |key: &str| characteristics.and_then(|c| c.get(key)).map(|c| c.value());
+
+        let weapons = character
+            .get_weapons()
+            .into_iter()
+            .filter_map(|item| match &item.data() {
+                ItemData::Weapon(weapon) => Some(Self::weapon_stats(item, weapon, &combat_values, &value_of)),
+                _ => None,
+            })
+            .collect();
+
+        let armor = character
+            .get_armor()
+            .into_iter()
+            .filter_map(|item| match &item.data() {
+                ItemData::Armor(armor) => Some(ArmorStats {
+                    name: item.name.clone(),
+                    protection: armor.protection,
+                    encumbrance: armor.encumbrance,
+                    worn: armor.equipment.worn,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let ammunition = character
+            .get_ammunition()
+            .into_iter()
+            .map(|item| AmmunitionStock {
+                name: item.name.clone(),
+                group: item.system.get_ammunition_group(),
+                quantity: item.data().equipment().map(|equipment| equipment.quantity).unwrap_or(1),
+            })
+            .collect();
+
+        Self { weapons, armor, ammunition }
+    }
+
+    /// Ammunition that fits a ranged weapon (same ammunition group)
+    pub fn ammunition_for(&self, weapon: &WeaponStats) -> Vec<&AmmunitionStock> {
+        let Some(group) = weapon.ammunition_group.as_ref() else {
+            return Vec::new();
+        };
+        self.ammunition
+            .iter()
+            .filter(|ammunition| ammunition.group.as_ref().is_some_and(|g| g.eq_ignore_ascii_case(group)))
+            .collect()
+    }
+
+    fn weapon_stats(
+        item: &Item,
+        weapon: &WeaponSystem,
+        combat_values: &CombatValues,
+        value_of: &dyn Fn(&str) -> Option<i32>,
+    ) -> WeaponStats {
+        let ranged = item.kind == ItemKind::RangeWeapon;
+        let technique_name = weapon.combat_technique.clone();
+        let technique: Option<&CombatTechniqueValues> = technique_name.as_ref().and_then(|name| {
+            combat_values.techniques.iter().find(|technique| technique.name.eq_ignore_ascii_case(name.trim()))
+        });
+
+        let attack_modifier = weapon.attack_modifier.unwrap_or(0);
+        let parry_modifier = weapon.parry_modifier.unwrap_or(0);
+        let damage = weapon.damage;
+        let damage_threshold = weapon.damage_threshold;
+
+        // The weapon's own leading attribute wins over the one of the combat technique
+        let damage_attributes: Vec<String> = if weapon.damage_attributes.is_empty() {
+            technique.map(|t| t.leading_attributes.clone()).unwrap_or_default()
+        } else {
+            weapon.damage_attributes.clone()
+        };
+
+        // Ranged weapons have no damage bonus
+        let damage_bonus = match (ranged, damage_threshold) {
+            (false, Some(threshold)) => damage_attributes
+                .iter()
+                .filter_map(|key| value_of(key))
+                .max()
+                .map(|value| (value - threshold).max(0))
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        let with_modifier = |value: Option<&DerivedValue>, label: &str, modifier: i32| {
+            value.map(|value| DerivedValue {
+                value: value.value + modifier,
+                formula: format!("{} = {} of {} + weapon modifier = {} {:+}",
+                                 label, label, technique_name.as_deref().unwrap_or("?"), value.value, modifier),
+            })
+        };
+
+        WeaponStats {
+            name: item.name.clone(),
+            ranged,
+            equipped: weapon.equipment.worn,
+            technique_missing: technique_name.is_some() && technique.is_none(),
+            technique: technique_name.clone(),
+            damage,
+            damage_threshold,
+            damage_bonus,
+            damage_attributes,
+            attack_modifier,
+            parry_modifier,
+            reach: if ranged { None } else { weapon.reach },
+            range: if ranged { weapon.range } else { None },
+            ammunition_group: weapon.ammunition_group.clone(),
+            attack: if ranged { None } else { with_modifier(technique.and_then(|t| t.attack.as_ref()), "AT", attack_modifier) },
+            parry: if ranged { None } else { with_modifier(technique.and_then(|t| t.parry.as_ref()), "PA", parry_modifier) },
+            ranged_attack: if ranged { technique.and_then(|t| t.ranged_attack.clone()) } else { None },
+            hit_points: damage.map(|damage| damage.with_bonus(damage_bonus)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn damage_dice_are_parsed_in_german_and_english_notation() {
+        let cases = [
+            ("1W6+4", Some((1, 6, 4))),
+            ("2W6", Some((2, 6, 0))),
+            ("W3-1", Some((1, 3, -1))),
+            ("1d6 + 4", Some((1, 6, 4))),
+            (" 2D20-2 ", Some((2, 20, -2))),
+            ("0W6", None),
+            ("1W0", None),
+            ("1W6+", None),
+            ("1W", None),
+            ("6", None),
+            ("", None),
+        ];
+        for (text, expected) in cases {
+            let parsed = DamageDice::parse(text).map(|dice| (dice.dice, dice.sides, dice.modifier));
+            assert_eq!(parsed, expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn damage_dice_statistics_and_display() {
+        let dice = DamageDice::parse("2W6+2").unwrap();
+        assert_eq!((dice.minimum(), dice.maximum(), dice.average()), (4, 14, 9.0));
+        assert_eq!(dice.with_bonus(-3).to_string(), "2W6-1");
+        assert_eq!(DamageDice::parse("1W6").unwrap().to_string(), "1W6");
+    }
+
+    #[test]
+    fn range_bands_need_exactly_three_numbers() {
+        let cases = [
+            ("10/50/80", Some((10, 50, 80))),
+            (" 5 / 25 / 40 ", Some((5, 25, 40))),
+            ("10/50", None),
+            ("10/50/80/100", None),
+            ("10/weit/80", None),
+            ("", None),
+        ];
+        for (text, expected) in cases {
+            let parsed = RangeBands::parse(text).map(|bands| (bands.close, bands.medium, bands.far));
+            assert_eq!(parsed, expected, "{:?}", text);
+        }
+    }
+
+    fn weapon(name: &str, item_type: &str, damage_threshold: i32, guidevalue: &str) -> Value {
+        json!({
+            "_id": name, "name": name, "type": item_type, "img": null,
+            "system": {
+                "combatskill": { "value": "Schwerter" }, "damage": { "value": "1W6+4" },
+                "damageThreshold": { "value": damage_threshold }, "guidevalue": { "value": guidevalue },
+                "atmod": { "value": 0 }, "pamod": { "value": -1 },
+            },
+        })
+    }
+
+    #[test]
+    fn damage_bonus_of_the_highest_leading_attribute_above_the_threshold() {
+        let characteristics: serde_json::Map<String, Value> = [("mu", 14), ("ge", 13), ("kk", 16)]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), json!({ "initial": 8, "advances": value - 8 })))
+            .collect();
+        let character: Character = serde_json::from_value(json!({
+            "name": "Test", "type": "character", "img": null,
+            "items": [
+                {
+                    "_id": "ct", "name": "Schwerter", "type": "combatskill", "img": null,
+                    "system": { "talentValue": { "value": 11 }, "guidevalue": { "value": "ge/kk" }, "weapontype": { "value": "melee" } },
+                },
+                weapon("Langschwert", "meleeweapon", 15, ""),
+                weapon("Rapier", "meleeweapon", 12, "ge"),
+                weapon("Wurfmesser", "rangeweapon", 10, ""),
+            ],
+            "system": { "characteristics": characteristics },
+        }))
+        .unwrap();
+        let gear = CombatGear::calculate(&character);
+        let stats = |name: &str| gear.weapons.iter().find(|weapon| weapon.name == name).unwrap();
+
+        // The technique's leading attributes GE/KK: KK 16 is 1 above the threshold
+        let longsword = stats("Langschwert");
+        assert_eq!(longsword.damage_bonus, 1);
+        assert_eq!(longsword.hit_points.map(|dice| dice.to_string()), Some("1W6+5".to_string()));
+        assert_eq!(longsword.parry.as_ref().map(|v| v.value), Some(7));
+        // The weapon's own leading attribute GE 13 wins over the technique's
+        assert_eq!(stats("Rapier").damage_bonus, 1);
+        // No damage bonus for ranged weapons
+        assert_eq!(stats("Wurfmesser").damage_bonus, 0);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::character::data::base_name;
+use crate::character::world::{migrate_v9_document, read_leveldb, read_nedb};
+use crate::character::{Item, ItemKind, PurchaseSystem};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Item types whose APValue, step limit and StF are checked against the compendium
+const CHECKED_ITEM_KINDS: [ItemKind; 3] = [ItemKind::Advantage, ItemKind::Disadvantage, ItemKind::SpecialAbility];
+
+/// An item of a compendium pack
+#[derive(Debug, Clone)]
+pub struct CompendiumEntry {
+    pub item: Item,
+    /// "<package>.<pack>", e.g. "dsa5-core.coreabilities"
+    pub pack: String,
+}
+
+/// Value of an item that deviates from its compendium entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReferenceField {
+    ApValue,
+    /// The highest step of the item differs
+    MaxStep,
+    /// The item has a higher step than the compendium allows
+    Step,
+    ImprovementCost,
+}
+
+/// An item whose values differ from the official compendium entry
+#[derive(Debug, Clone, Serialize)]
+pub struct ReferenceDeviation {
+    pub item: String,
+    pub field: ReferenceField,
+    pub value: String,
+    pub reference: String,
+    pub pack: String,
+}
+
+/// Reference index of local DSA5 compendium packs, by source id and by name
+#[derive(Debug, Clone, Default)]
+pub struct CompendiumIndex {
+    pub entries: Vec<CompendiumEntry>,
+    /// Pack files and directories with at least one indexed item
+    pub packs: Vec<PathBuf>,
+    /// Documents that could not be read
+    pub warnings: Vec<String>,
+    by_id: HashMap<String, usize>,
+    by_name: HashMap<(ItemKind, String), usize>,
+}
+
+impl CompendiumIndex {
+    /// Index all packs in `path`: a pack (`.db` NeDB file, LevelDB directory, `.json` source file) or a directory that
+    /// contains packs at any depth, such as a module, the DSA5 system or Foundry's whole `Data/modules` directory.
+    /// Of several entries with the same name, the first pack in path order wins.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut candidates = Vec::new();
+        find_packs(path, &mut candidates)?;
+
+        let mut index = Self::default();
+        for pack in candidates {
+            let mut warnings = Vec::new();
+            match read_pack(&pack, &mut warnings) {
+                Ok(documents) => {
+                    if index.add_documents(documents, &pack_label(&pack)) > 0 {
+                        index.packs.push(pack.clone());
+                    }
+                }
+                Err(e) => warnings.push(e.to_string()),
+            }
+            index.warnings.extend(warnings.into_iter().map(|warning| format!("{}: {}", pack.display(), warning)));
+        }
+
+        if index.entries.is_empty() {
+            anyhow::bail!("No advantages, disadvantages or special abilities found in the compendium packs in {}", path.display());
+        }
+        Ok(index)
+    }
+
+    /// Add the checked item types among `documents`; returns how many were added
+    pub(crate) fn add_documents(&mut self, documents: Vec<Value>, pack: &str) -> usize {
+        let count = self.entries.len();
+        for mut document in documents {
+            migrate_v9_document(&mut document);
+            // Other document types (actors, journal entries, ...) are no items
+            let Ok(item) = serde_json::from_value::<Item>(document) else {
+                continue;
+            };
+            if !CHECKED_ITEM_KINDS.contains(&item.kind) {
+                continue;
+            }
+
+            let position = self.entries.len();
+            self.by_id.entry(item._id.clone()).or_insert(position);
+            self.by_name.entry((item.kind.clone(), item.name.to_lowercase())).or_insert(position);
+            self.by_name.entry((item.kind.clone(), base_name(&item.name).to_lowercase())).or_insert(position);
+            self.entries.push(CompendiumEntry { item, pack: pack.to_string() });
+        }
+        self.entries.len() - count
+    }
+
+    /// The compendium entry of an item: by the document id of its source id, then by type and name,
+    /// then by the name without its choice in parentheses ("Begabung (Klettern)" → "Begabung")
+    pub fn find(&self, item: &Item) -> Option<&CompendiumEntry> {
+        self.find_by_source_id(item)
+            .or_else(|| self.by_name.get(&(item.kind.clone(), item.name.to_lowercase())).map(|&position| &self.entries[position]))
+            .or_else(|| self.by_name.get(&(item.kind.clone(), base_name(&item.name).to_lowercase())).map(|&position| &self.entries[position]))
+    }
+
+    /// The compendium entry an item was created from, by the document id of its source id
+    pub fn find_by_source_id(&self, item: &Item) -> Option<&CompendiumEntry> {
+        item.source_id()
+            .and_then(|source_id| source_id.rsplit('.').next())
+            .and_then(|id| self.by_id.get(id))
+            .map(|&position| &self.entries[position])
+            .filter(|entry| entry.item.kind == item.kind)
+    }
+
+    /// Advantages, disadvantages and special abilities of the character that are not in the compendium
+    pub fn unmatched<'a>(&self, items: &'a [Item]) -> Vec<&'a Item> {
+        items.iter().filter(|item| CHECKED_ITEM_KINDS.contains(&item.kind) && self.find(item).is_none()).collect()
+    }
+
+    /// Advantages, disadvantages and special abilities whose APValue, step limit or StF deviate from the compendium
+    pub fn deviations(&self, items: &[Item]) -> Vec<ReferenceDeviation> {
+        let mut deviations = Vec::new();
+        for item in items.iter().filter(|item| CHECKED_ITEM_KINDS.contains(&item.kind)) {
+            let Some(entry) = self.find(item) else {
+                continue;
+            };
+            let deviation = |field, value: String, reference: String| ReferenceDeviation {
+                item: item.name.clone(),
+                field,
+                value,
+                reference,
+                pack: entry.pack.clone(),
+            };
+
+            let purchase = item.purchase();
+            let reference = entry.item.purchase();
+            if let (Some(value), Some(reference)) = (&purchase.ap_value, &reference.ap_value) {
+                if !same_ap_value(value, reference) {
+                    deviations.push(deviation(ReferenceField::ApValue, value.clone(), reference.clone()));
+                }
+            }
+            deviations.extend(step_deviations(&purchase, &reference).into_iter().map(|(field, value, reference)| deviation(field, value, reference)));
+
+            let improvement_cost = item.system.get_st_f_value();
+            let reference_cost = entry.item.system.get_st_f_value();
+            if let (Some(value), Some(reference)) = (improvement_cost, reference_cost) {
+                if !value.trim().eq_ignore_ascii_case(reference.trim()) {
+                    deviations.push(deviation(ReferenceField::ImprovementCost, value, reference));
+                }
+            }
+        }
+        deviations
+    }
+}
+
+/// A different highest step, and a step above the highest step of the compendium
+fn step_deviations(purchase: &PurchaseSystem, reference: &PurchaseSystem) -> Vec<(ReferenceField, String, String)> {
+    let Some(reference_max) = reference.max_step else {
+        return Vec::new();
+    };
+
+    let mut deviations = Vec::new();
+    if let Some(max_step) = purchase.max_step.filter(|&max_step| max_step != reference_max) {
+        deviations.push((ReferenceField::MaxStep, max_step.to_string(), reference_max.to_string()));
+    }
+    if let Some(step) = purchase.step.filter(|&step| step > reference_max) {
+        deviations.push((ReferenceField::Step, step.to_string(), reference_max.to_string()));
+    }
+    deviations
+}
+
+/// APValues are equal if they list the same costs ("10;20;30", "10; 20; 30"). The sign is ignored:
+/// disadvantages are exported with negative and positive APValues alike.
+fn same_ap_value(value: &str, reference: &str) -> bool {
+    let costs = |ap_value: &str| -> Option<Vec<i32>> {
+        ap_value.split(';').map(|cost| cost.trim().parse::<i32>().ok().map(i32::abs)).collect()
+    };
+    match (costs(value), costs(reference)) {
+        (Some(value), Some(reference)) => value == reference,
+        _ => value.trim().eq_ignore_ascii_case(reference.trim()),
+    }
+}
+
+/// Documents of a pack: a LevelDB directory, a NeDB file or a JSON file with one document or an array of documents
+fn read_pack(pack: &Path, warnings: &mut Vec<String>) -> anyhow::Result<Vec<Value>> {
+    if pack.is_dir() {
+        return read_leveldb(pack, "items", warnings);
+    }
+
+    let content = std::fs::read_to_string(pack)?;
+    if pack.extension().is_some_and(|extension| extension == "json") {
+        return Ok(match serde_json::from_str(&content)? {
+            Value::Array(documents) => documents,
+            document => vec![document],
+        });
+    }
+    Ok(read_nedb(&content, warnings))
+}
+
+/// Pack files and LevelDB directories in `path`, sorted by path. Files that turn out to hold no items are skipped later.
+fn find_packs(path: &Path, packs: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    if path.is_file() {
+        if matches!(path.extension().and_then(|e| e.to_str()), Some("db" | "json")) {
+            packs.push(path.to_path_buf());
+        }
+        return Ok(());
+    }
+    if path.join("CURRENT").is_file() {
+        packs.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut children: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|child| !child.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')))
+        .collect();
+    children.sort();
+    for child in children {
+        find_packs(&child, packs)?;
+    }
+    Ok(())
+}
+
+/// "<package>.<pack>" from `<package>/packs/<pack>[.db]`, otherwise the name of the pack file or directory
+fn pack_label(path: &Path) -> String {
+    let components: Vec<String> = path.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+    let stem = |name: &str| name.trim_end_matches(".db").trim_end_matches(".json").to_string();
+
+    match components.iter().rposition(|component| component == "packs") {
+        Some(position) if position > 0 && position + 1 < components.len() => {
+            format!("{}.{}", components[position - 1], stem(&components[position + 1]))
+        }
+        _ => stem(components.last().map(String::as_str).unwrap_or_default()),
+    }
+}
@@ -0,0 +1,306 @@
+use crate::character::data::base_name;
+use crate::character::report::category;
+use crate::character::{ApCalculator, ChangeKind, Character, CharacterDiff, DiffEntry, Item, ItemKind, OptolithImport};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// One of the two representations of a hero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Side {
+    Optolith,
+    Foundry,
+}
+
+impl Side {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Side::Optolith => "Optolith",
+            Side::Foundry => "Foundry",
+        }
+    }
+}
+
+/// AP of one category on both sides
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryComparison {
+    pub category: String,
+    pub optolith_ap: i32,
+    pub foundry_ap: i32,
+    /// Sum of the AP deltas of the differences listed for this category
+    pub explained_ap: i32,
+    /// Optolith entries of this category that are only known by their id: they cannot be matched by name and may not be costed
+    pub placeholders: usize,
+    /// The side whose AP in this category is most likely wrong, `None` if both agree
+    pub suspect: Option<Side>,
+}
+
+impl CategoryComparison {
+    /// Foundry AP minus Optolith AP
+    pub fn difference(&self) -> i32 {
+        self.foundry_ap - self.optolith_ap
+    }
+}
+
+/// Comparison of the same hero as Optolith file and as Foundry export.
+/// Entries are matched by item type and name; the Optolith hero is the old and the Foundry export the new state
+/// of the diff, so "added" entries exist only in Foundry and "removed" ones only in Optolith.
+/// Optolith placeholders cannot be matched by name; they and the Foundry-only entries of their item types
+/// are listed in `unmatched` instead of the differences.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceComparison {
+    pub optolith_character: String,
+    pub foundry_character: String,
+    pub optolith_total: i32,
+    pub foundry_total: i32,
+    /// Spent AP as recorded by Foundry
+    pub foundry_spent: Option<i32>,
+    /// Categories sorted by the size of their difference, then by name
+    pub categories: Vec<CategoryComparison>,
+    pub differences: CharacterDiff,
+    /// Optolith placeholders (removed) and Foundry-only entries of the same item types (added)
+    /// that may be their counterparts; sorted like the differences
+    pub unmatched: Vec<DiffEntry>,
+    /// What the Optolith import could not map
+    pub import_warnings: Vec<String>,
+}
+
+impl SourceComparison {
+    pub fn between(optolith: &OptolithImport, foundry: &Character, calculator: &ApCalculator) -> Self {
+        let optolith_character = &optolith.character;
+        let mut differences = CharacterDiff::between(optolith_character, foundry, calculator);
+        let optolith_ap = calculator.get_ap_by_category(optolith_character);
+        let foundry_ap = calculator.get_ap_by_category(foundry);
+        let foundry_spent = foundry.experience().and_then(|experience| experience.spent());
+        let placeholders = optolith.placeholders();
+
+        let placeholder_types: BTreeSet<&str> = placeholders.iter().map(|item| item.kind.as_str()).collect();
+        let (unmatched, matched): (Vec<DiffEntry>, Vec<DiffEntry>) = differences.changes
+            .drain(..)
+            .partition(|change| match change.kind {
+                ChangeKind::Removed => is_optolith_id(base_name(&change.name)),
+                ChangeKind::Added => placeholder_types.contains(change.item_type.as_str()),
+                ChangeKind::Changed => false,
+            });
+        differences.changes = matched;
+
+        let overall_suspect = Self::overall_suspect(differences.old_total, differences.new_total, foundry_spent);
+
+        let names: BTreeSet<&String> = optolith_ap.keys().chain(foundry_ap.keys()).collect();
+        let mut categories: Vec<CategoryComparison> = names
+            .into_iter()
+            .map(|name| {
+                let placeholders = placeholders.iter().filter(|item| category_of(item) == *name).count();
+                let optolith_ap = optolith_ap.get(name).copied().unwrap_or(0);
+                let foundry_ap = foundry_ap.get(name).copied().unwrap_or(0);
+                let explained_ap = differences.changes.iter()
+                    .filter(|change| change.category == *name)
+                    .map(|change| change.ap_delta)
+                    .sum();
+                let suspect = if optolith_ap == foundry_ap {
+                    None
+                } else if explained_ap != foundry_ap - optolith_ap && placeholders > 0 {
+                    // The Optolith side is incomplete: its placeholders are not costed, and the unmatched
+                    // entries are not part of the differences, so the recorded Foundry AP cannot decide
+                    Some(Side::Optolith)
+                } else {
+                    overall_suspect
+                };
+                CategoryComparison {
+                    category: name.clone(),
+                    optolith_ap,
+                    foundry_ap,
+                    explained_ap,
+                    placeholders,
+                    suspect,
+                }
+            })
+            .collect();
+
+        categories.sort_by(|a, b| {
+            b.difference().abs().cmp(&a.difference().abs())
+                .then_with(|| a.category.cmp(&b.category))
+        });
+
+        Self {
+            optolith_character: optolith_character.name.clone(),
+            foundry_character: foundry.name.clone(),
+            optolith_total: differences.old_total,
+            foundry_total: differences.new_total,
+            foundry_spent,
+            categories,
+            differences,
+            unmatched,
+            import_warnings: optolith.warnings.clone(),
+        }
+    }
+
+    /// Both sides have the same values and the same AP in every category
+    pub fn is_consistent(&self) -> bool {
+        self.differences.ap_changes().next().is_none()
+            && self.unmatched.is_empty()
+            && self.categories.iter().all(|c| c.difference() == 0)
+    }
+
+    /// Categories whose AP differs
+    pub fn diverging_categories(&self) -> impl Iterator<Item = &CategoryComparison> {
+        self.categories.iter().filter(|category| category.difference() != 0)
+    }
+
+    /// Differences in value or presence, without equipment that does not cost AP
+    pub fn value_differences(&self) -> impl Iterator<Item = &DiffEntry> {
+        self.differences.changes.iter().filter(|change| change.ap_delta != 0 || change.old_value != change.new_value)
+    }
+
+    /// Foundry tracks spent AP incrementally. If its record still matches its own values, the values were bought
+    /// properly in Foundry and the Optolith file is out of date; if the record matches Optolith instead,
+    /// values were changed in Foundry without paying for them.
+    fn overall_suspect(optolith_total: i32, foundry_total: i32, foundry_spent: Option<i32>) -> Option<Side> {
+        match foundry_spent {
+            _ if optolith_total == foundry_total => None,
+            Some(spent) if spent == foundry_total => Some(Side::Optolith),
+            Some(spent) if spent == optolith_total => Some(Side::Foundry),
+            _ => None,
+        }
+    }
+}
+
+impl OptolithImport {
+    /// Items that are only known by their Optolith id (e.g. "SPELL_12" or "SA_9 (Klettern)")
+    pub fn placeholders(&self) -> Vec<&Item> {
+        self.character.items.iter().filter(|item| is_optolith_id(base_name(&item.name))).collect()
+    }
+}
+
+/// Category of the AP report the item would be counted in
+fn category_of(item: &Item) -> &str {
+    match item.kind {
+        ItemKind::Skill => category::SKILLS,
+        ItemKind::CombatSkill => category::COMBAT_SKILLS,
+        ItemKind::Spell | ItemKind::Ritual => category::SPELLS_RITUALS,
+        ItemKind::Liturgy | ItemKind::Ceremony => category::LITURGIES_CEREMONIES,
+        ItemKind::MagicTrick => category::MAGIC_TRICKS,
+        ItemKind::Blessing => category::BLESSINGS,
+        _ => item.kind.as_str(),
+    }
+}
+
+/// "SPELL_12", "ADV_5", "TAL_3"
+fn is_optolith_id(name: &str) -> bool {
+    name.split_once('_').is_some_and(|(prefix, number)| {
+        !prefix.is_empty()
+            && prefix.chars().all(|c| c.is_ascii_uppercase())
+            && !number.is_empty()
+            && number.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn talent(item_type: &str, name: &str, value: i32) -> Value {
+        json!({
+            "_id": name, "name": name, "type": item_type, "img": null,
+            "system": {
+                "talentValue": { "value": value }, "StF": { "value": "B" },
+                "characteristic1": { "value": "mu" }, "characteristic2": { "value": "kl" }, "characteristic3": { "value": "in" },
+            },
+        })
+    }
+
+    /// An Optolith placeholder: only the id is known, so it has no StF and costs nothing
+    fn placeholder(item_type: &str, id: &str) -> Value {
+        json!({ "_id": id, "name": id, "type": item_type, "img": null, "system": { "talentValue": { "value": 4 } } })
+    }
+
+    fn advantage(name: &str, ap_value: i32) -> Value {
+        json!({ "_id": name, "name": name, "type": "advantage", "img": null, "system": { "APValue": { "value": ap_value.to_string() } } })
+    }
+
+    fn hero(items: &[Value], spent: Option<i32>) -> Character {
+        let mut hero = json!({ "name": "Alrik", "type": "character", "img": null, "items": items, "system": {} });
+        if let Some(spent) = spent {
+            hero["system"]["details"] = json!({ "experience": { "total": 1100, "spent": spent } });
+        }
+        serde_json::from_value(hero).unwrap()
+    }
+
+    /// The Foundry export with its recorded spent AP equal to its calculated AP
+    fn foundry_hero(items: &[Value], calculator: &ApCalculator) -> Character {
+        hero(items, Some(calculator.calculate_total_spent_ap(&hero(items, None))))
+    }
+
+    #[test]
+    fn optolith_ids_are_upper_case_prefixes_with_a_number() {
+        for id in ["SPELL_12", "ADV_5", "TAL_3"] {
+            assert!(is_optolith_id(id), "{}", id);
+        }
+        for name in ["Spell_12", "SPELL_", "_12", "SA_9a", "SA_9 (Klettern)", "Klettern", ""] {
+            assert!(!is_optolith_id(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn the_recorded_spent_ap_decides_which_side_is_suspect() {
+        assert_eq!(SourceComparison::overall_suspect(100, 100, Some(90)), None);
+        assert_eq!(SourceComparison::overall_suspect(100, 120, Some(120)), Some(Side::Optolith));
+        assert_eq!(SourceComparison::overall_suspect(100, 120, Some(100)), Some(Side::Foundry));
+        assert_eq!(SourceComparison::overall_suspect(100, 120, Some(110)), None);
+        assert_eq!(SourceComparison::overall_suspect(100, 120, None), None);
+    }
+
+    #[test]
+    fn placeholders_and_their_possible_counterparts_are_unmatched() {
+        let calculator = ApCalculator::default();
+        let optolith = OptolithImport {
+            character: hero(&[talent("skill", "Klettern", 5), placeholder("spell", "SPELL_12"), advantage("Glück", 30)], None),
+            warnings: vec!["SPELL_12 is not costed".to_string()],
+        };
+        let foundry = foundry_hero(&[talent("skill", "Klettern", 7), talent("spell", "Ignifaxius", 4), advantage("Glück", 30), advantage("Zäher Hund", 10)], &calculator);
+        let comparison = SourceComparison::between(&optolith, &foundry, &calculator);
+
+        let unmatched: Vec<(ChangeKind, &str)> = comparison.unmatched.iter().map(|change| (change.kind, change.name.as_str())).collect();
+        assert_eq!(unmatched.len(), 2);
+        assert!(unmatched.contains(&(ChangeKind::Removed, "SPELL_12")));
+        assert!(unmatched.contains(&(ChangeKind::Added, "Ignifaxius")));
+
+        let differences: Vec<(ChangeKind, &str)> = comparison.differences.changes.iter().map(|change| (change.kind, change.name.as_str())).collect();
+        assert_eq!(differences, [(ChangeKind::Changed, "Klettern"), (ChangeKind::Added, "Zäher Hund")]);
+        assert_eq!(comparison.import_warnings, optolith.warnings);
+        assert!(!comparison.is_consistent());
+    }
+
+    #[test]
+    fn categories_name_the_suspect_side() {
+        let calculator = ApCalculator::default();
+        let optolith = OptolithImport {
+            character: hero(&[talent("skill", "Klettern", 5), placeholder("spell", "SPELL_12")], None),
+            warnings: Vec::new(),
+        };
+        let foundry = foundry_hero(&[talent("skill", "Klettern", 7), talent("spell", "Ignifaxius", 4)], &calculator);
+        let comparison = SourceComparison::between(&optolith, &foundry, &calculator);
+        let category = |name: &str| comparison.categories.iter().find(|category| category.category == name).unwrap();
+
+        // Foundry's record matches its values, so the Optolith file is out of date
+        let skills = category(category::SKILLS);
+        assert_eq!(skills.suspect, Some(Side::Optolith));
+        assert_eq!(skills.explained_ap, skills.difference());
+        // The placeholder is not costed and cannot be matched
+        let spells = category(category::SPELLS_RITUALS);
+        assert_eq!((spells.placeholders, spells.suspect), (1, Some(Side::Optolith)));
+        assert_eq!(spells.explained_ap, 0);
+        assert_eq!(comparison.categories[0].difference().abs(), comparison.diverging_categories().map(|c| c.difference().abs()).max().unwrap());
+    }
+
+    #[test]
+    fn identical_heroes_are_consistent() {
+        let calculator = ApCalculator::default();
+        let items = [talent("skill", "Klettern", 5), advantage("Glück", 30)];
+        let optolith = OptolithImport { character: hero(&items, None), warnings: Vec::new() };
+        let comparison = SourceComparison::between(&optolith, &foundry_hero(&items, &calculator), &calculator);
+
+        assert!(comparison.is_consistent());
+        assert!(comparison.categories.iter().all(|category| category.suspect.is_none()));
+    }
+}
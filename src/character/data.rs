@@ -1,3 +1,9 @@
+use crate::character::combat_gear::{DamageDice, RangeBands, Reach};
+use crate::character::item_kind::{ItemData, ItemKind, PurchaseSystem};
+use crate::character::optolith::{CharacterFormat, OptolithImport};
+use crate::character::rules::Rules;
+use crate::character::species::Species;
+use crate::character::ApCalculator;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -39,6 +45,49 @@ pub struct Characteristics {
     pub kk: Option<CharacteristicValue>,      // Körperkraft
 }
 
+impl Characteristics {
+    /// Keys (as used in the Foundry export) and German names of all characteristics
+    pub const NAMES: [(&'static str, &'static str); 8] = [
+        ("mu", "Mut"),
+        ("kl", "Klugheit"),
+        ("in", "Intuition"),
+        ("ch", "Charisma"),
+        ("ff", "Fingerfertigkeit"),
+        ("ge", "Gewandtheit"),
+        ("ko", "Konstitution"),
+        ("kk", "Körperkraft"),
+    ];
+
+    /// Get a characteristic by its key ("mu", "kl", "in", ...)
+    pub fn get(&self, key: &str) -> Option<&CharacteristicValue> {
+        match key.to_lowercase().as_str() {
+            "mu" => self.mu.as_ref(),
+            "kl" => self.kl.as_ref(),
+            "in" => self.in_.as_ref(),
+            "ch" => self.ch.as_ref(),
+            "ff" => self.ff.as_ref(),
+            "ge" => self.ge.as_ref(),
+            "ko" => self.ko.as_ref(),
+            "kk" => self.kk.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut CharacteristicValue> {
+        match key.to_lowercase().as_str() {
+            "mu" => self.mu.as_mut(),
+            "kl" => self.kl.as_mut(),
+            "in" => self.in_.as_mut(),
+            "ch" => self.ch.as_mut(),
+            "ff" => self.ff.as_mut(),
+            "ge" => self.ge.as_mut(),
+            "ko" => self.ko.as_mut(),
+            "kk" => self.kk.as_mut(),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CharacteristicValue {
     pub initial: i32,
@@ -58,6 +107,12 @@ impl CharacteristicValue {
         self.initial + self.advances
     }
 
+    /// Calculate the characteristic value including the species modifier (initial + species + advances)
+    /// The species modifier is free, so AP costs are always based on the nominal value
+    pub fn value(&self) -> i32 {
+        self.nominal_value() + self.species
+    }
+
     /// Get the base initial value
     pub fn initial_value(&self) -> i32 {
         self.initial
@@ -95,7 +150,9 @@ pub struct WoundValue {
     pub advances: i32,
     pub modifier: i32,
     pub current: i32,
-    pub max: i32,
+    /// Maximum as stored by the source, `None` if it is not recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
     #[serde(flatten)]
     pub other: HashMap<String, serde_json::Value>,
 }
@@ -122,7 +179,9 @@ pub struct AstralEnergyValue {
     pub advances: i32,
     pub modifier: i32,
     pub current: i32,
-    pub max: i32,
+    /// Maximum as stored by the source, `None` if it is not recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
     #[serde(rename = "permanentLoss", default)]
     pub permanent_loss: i32,
     #[serde(default)]
@@ -159,7 +218,9 @@ pub struct KarmaEnergyValue {
     pub advances: i32,
     pub modifier: i32,
     pub current: i32,
-    pub max: i32,
+    /// Maximum as stored by the source, `None` if it is not recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i32>,
     #[serde(rename = "permanentLoss", default)]
     pub permanent_loss: i32,
     #[serde(default)]
@@ -199,10 +260,24 @@ pub struct Details {
     pub other: HashMap<String, serde_json::Value>,
 }
 
+impl Details {
+    /// Get the species name stored in `details.species.value` (empty names are ignored)
+    pub fn species_name(&self) -> Option<String> {
+        self.other.get("species")
+            .and_then(|v| v.get("value"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Experience {
     pub total: i32,
-    pub spent: i32,
+    /// Not recorded by every source (Optolith calculates it instead of storing it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spent: Option<i32>,
     #[serde(flatten)]
     pub other: HashMap<String, serde_json::Value>,
 }
@@ -213,8 +288,8 @@ impl Experience {
         self.total
     }
 
-    /// Get experience points spent
-    pub fn spent(&self) -> i32 {
+    /// Get experience points spent, if recorded
+    pub fn spent(&self) -> Option<i32> {
         self.spent
     }
 }
@@ -230,15 +305,55 @@ impl Experience {
 pub struct Item {
     pub _id: String,
     pub name: String,
+    /// Foundry item type (`type`); replaces the former `item_type: String` field
     #[serde(rename = "type")]
-    pub item_type: String,
+    pub kind: ItemKind,
     pub img: Option<String>,
+    /// Raw `system` data; the typed view of it is `data()`
     pub system: ItemSystem,
     // Store everything else as raw JSON to avoid parsing issues
     #[serde(flatten)]
     pub other: HashMap<String, serde_json::Value>,
 }
 
+impl Item {
+    /// Foundry item type as a string, as the former `item_type` field
+    #[deprecated(note = "use `kind` (an `ItemKind`) or `kind.as_str()`")]
+    pub fn item_type(&self) -> &str {
+        self.kind.as_str()
+    }
+
+    /// Typed view of `system`. It is read from `system` on every call, so it always reflects changes made to it.
+    pub fn data(&self) -> ItemData {
+        ItemData::from_system(&self.kind, &self.system)
+    }
+
+    /// Language-neutral id of the compendium entry this item was created from.
+    /// Foundry V12+ stores it in `_stats.compendiumSource`, older versions in `flags.core.sourceId`.
+    pub fn source_id(&self) -> Option<&str> {
+        self.other.get("_stats")
+            .and_then(|stats| stats.get("compendiumSource"))
+            .and_then(|v| v.as_str())
+            .or_else(|| {
+                self.other.get("flags")
+                    .and_then(|flags| flags.get("core"))
+                    .and_then(|core| core.get("sourceId"))
+                    .and_then(|v| v.as_str())
+            })
+            .filter(|id| !id.is_empty())
+    }
+
+    /// APValue and step, also for item types without a purchase view
+    pub fn purchase(&self) -> PurchaseSystem {
+        PurchaseSystem::from_system(&self.system)
+    }
+}
+
+/// Item name without its specification, e.g. "Prinzipientreue (Hesindekirche)" → "Prinzipientreue"
+pub(crate) fn base_name(name: &str) -> &str {
+    name.split(" (").next().unwrap_or(name).trim()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ItemSystem {
     // Store everything as raw JSON values to avoid parsing issues
@@ -282,6 +397,10 @@ impl ItemSystem {
             })
     }
 
+    pub fn get_value_as_i32(&self, key: &str) -> Option<i32> {
+        self.get_value_as_string(key).and_then(|v| v.trim().parse::<i32>().ok())
+    }
+
     pub fn get_price(&self) -> Option<String> {
         self.get_value_as_string("price")
     }
@@ -294,6 +413,88 @@ impl ItemSystem {
         self.get_value_as_string("weight")
     }
 
+    /// Weight of one unit in Stein
+    pub fn get_weight_stone(&self) -> Option<f64> {
+        self.get_weight().and_then(|w| w.trim().replace(',', ".").parse::<f64>().ok())
+    }
+
+    /// Quantity as a number (1 if missing or unparseable)
+    pub fn get_quantity_count(&self) -> i32 {
+        self.get_quantity()
+            .and_then(|q| q.parse::<f64>().ok())
+            .map(|q| q as i32)
+            .unwrap_or(1)
+    }
+
+    /// `worn` flag of armor, weapons and containers, `None` if the item has none
+    pub fn is_worn(&self) -> Option<bool> {
+        self.data.get("worn")
+            .and_then(|v| v.get("value"))
+            .and_then(|v| v.as_bool())
+    }
+
+    /// Id of the container (bag) the item is stored in
+    pub fn get_container_id(&self) -> Option<&str> {
+        self.data.get("parent_id")
+            .and_then(|v| v.as_str())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Rüstungsschutz (RS) of armor
+    pub fn get_protection(&self) -> Option<i32> {
+        self.get_value_as_i32("protection")
+    }
+
+    /// Belastung (BE) of armor
+    pub fn get_encumbrance(&self) -> Option<i32> {
+        self.get_value_as_i32("encumbrance")
+    }
+
+    /// Trefferpunkte (TP) of a weapon, e.g. "1W6+4"
+    pub fn get_damage(&self) -> Option<DamageDice> {
+        self.get_value_as_string("damage").and_then(|v| DamageDice::parse(&v))
+    }
+
+    /// Schadensschwelle of a melee weapon: every point of the leading attribute above it adds 1 TP
+    pub fn get_damage_threshold(&self) -> Option<i32> {
+        self.get_value_as_i32("damageThreshold")
+    }
+
+    /// AT modifier of a melee weapon
+    pub fn get_attack_modifier(&self) -> Option<i32> {
+        self.get_value_as_i32("atmod")
+    }
+
+    /// PA modifier of a melee weapon or shield
+    pub fn get_parry_modifier(&self) -> Option<i32> {
+        self.get_value_as_i32("pamod")
+    }
+
+    /// Reichweite of a melee weapon ("short", "medium" or "long")
+    pub fn get_reach(&self) -> Option<Reach> {
+        self.get_value_as_string("reach").and_then(|v| Reach::parse(&v))
+    }
+
+    /// Range bands of a ranged weapon ("close/medium/far" in Schritt, stored in `reach`)
+    pub fn get_range_bands(&self) -> Option<RangeBands> {
+        self.get_value_as_string("reach").and_then(|v| RangeBands::parse(&v))
+    }
+
+    /// Name of the combat technique a weapon is used with
+    pub fn get_combat_technique(&self) -> Option<String> {
+        self.get_value_as_string("combatskill").filter(|v| !v.trim().is_empty())
+    }
+
+    /// Ammunition group of a ranged weapon or of ammunition, e.g. "arrow"
+    pub fn get_ammunition_group(&self) -> Option<String> {
+        self.get_value_as_string("ammunitiongroup").filter(|v| !v.trim().is_empty() && v != "-")
+    }
+
+    /// Whether a skill is affected by encumbrance: "yes", "no" or "maybe"
+    pub fn get_burden_value(&self) -> Option<String> {
+        self.get_value_as_string("burden")
+    }
+
     pub fn get_group(&self) -> Option<String> {
         self.get_value_as_string("group")
     }
@@ -315,6 +516,11 @@ impl ItemSystem {
         self.get_value_as_string("guidevalue")
     }
 
+    /// "melee" or "range" for combat techniques
+    pub fn get_weapon_type_value(&self) -> Option<String> {
+        self.get_value_as_string("weapontype")
+    }
+
     pub fn get_st_f_value(&self) -> Option<String> {
         self.get_value_as_string("StF")
     }
@@ -326,23 +532,71 @@ impl ItemSystem {
 
 impl Character {
     pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        Self::from_file_with_rules(path, Rules::builtin())
+    }
+
+    /// Load a character file; Optolith heroes are mapped with the Optolith tables of `rules`
+    pub fn from_file_with_rules(path: &Path, rules: &Rules) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let character: Character = serde_json::from_str(&content)?;
-        Ok(character)
+        Self::from_json_with_rules(&content, rules)
+    }
+
+    /// Parse a character from the content of a Foundry VTT actor export or an Optolith hero (detected from the content)
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        Self::from_json_with_rules(content, Rules::builtin())
+    }
+
+    /// Like `from_json`, mapping Optolith heroes with the Optolith tables of `rules`
+    pub fn from_json_with_rules(content: &str, rules: &Rules) -> anyhow::Result<Self> {
+        match CharacterFormat::detect(content)? {
+            CharacterFormat::Foundry => {
+                let character: Character = serde_json::from_str(content)?;
+                Ok(character)
+            }
+            CharacterFormat::Optolith => Ok(OptolithImport::from_json(content, rules)?.character),
+        }
     }
 
+    pub fn find_item(&self, id: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item._id == id)
+    }
+
+    /// Items bought with an APValue; item types without a typed view are checked in the raw `system`
     pub fn get_ap_items(&self) -> Vec<&Item> {
         self.items
             .iter()
-            .filter(|item| item.system.get_ap_value().is_some())
+            .filter(|item| match &item.data() {
+                ItemData::Purchase(purchase) => purchase.ap_value.is_some(),
+                ItemData::Raw => item.system.get_ap_value().is_some(),
+                _ => false,
+            })
             .collect()
     }
 
     // Private helper method
-    fn get_items_by_types(&self, types: &[&str]) -> Vec<&Item> {
+    fn get_items_by_kinds(&self, kinds: &[ItemKind]) -> Vec<&Item> {
+        let mut items: Vec<&Item> = self.items
+            .iter()
+            .filter(|item| kinds.contains(&item.kind))
+            .collect();
+
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        items
+    }
+
+    /// Physical items (equipment, weapons, armor, money, ...); unknown item types count if they have a quantity, weight or price
+    pub fn get_inventory(&self) -> Vec<&Item> {
         let mut items: Vec<&Item> = self.items
             .iter()
-            .filter(|item| types.contains(&item.item_type.as_str()))
+            .filter(|item| match &item.data() {
+                ItemData::Weapon(_) | ItemData::Armor(_) | ItemData::Equipment(_) => true,
+                ItemData::Raw => {
+                    item.system.get_quantity().is_some()
+                        || item.system.get_weight().is_some()
+                        || item.system.get_price().is_some()
+                }
+                _ => false,
+            })
             .collect();
 
         items.sort_by(|a, b| a.name.cmp(&b.name));
@@ -350,51 +604,100 @@ impl Character {
     }
 
     pub fn get_skills(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["skill"])
+        self.get_items_by_kinds(&[ItemKind::Skill])
     }
 
     pub fn get_combat_skills(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["combatskill"])
+        self.get_items_by_kinds(&[ItemKind::CombatSkill])
     }
 
     pub fn get_spells(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["spell"])
+        self.get_items_by_kinds(&[ItemKind::Spell])
     }
 
     pub fn get_rituals(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["ritual"])
+        self.get_items_by_kinds(&[ItemKind::Ritual])
     }
 
     pub fn get_spells_and_rituals(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["spell", "ritual"])
+        self.get_items_by_kinds(&[ItemKind::Spell, ItemKind::Ritual])
     }
 
     pub fn get_magic_tricks(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["magictrick"])
+        self.get_items_by_kinds(&[ItemKind::MagicTrick])
     }
 
     pub fn get_liturgies(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["liturgy"])
+        self.get_items_by_kinds(&[ItemKind::Liturgy])
     }
 
     pub fn get_ceremonies(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["ceremony"])
+        self.get_items_by_kinds(&[ItemKind::Ceremony])
     }
 
     pub fn get_liturgies_and_ceremonies(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["liturgy", "ceremony"])
+        self.get_items_by_kinds(&[ItemKind::Liturgy, ItemKind::Ceremony])
     }
 
     pub fn get_blessings(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["blessing"])
+        self.get_items_by_kinds(&[ItemKind::Blessing])
     }
 
     pub fn get_advantages(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["advantage"])
+        self.get_items_by_kinds(&[ItemKind::Advantage])
     }
 
     pub fn get_disadvantages(&self) -> Vec<&Item> {
-        self.get_items_by_types(&["disadvantage"])
+        self.get_items_by_kinds(&[ItemKind::Disadvantage])
+    }
+
+    pub fn get_special_abilities(&self) -> Vec<&Item> {
+        self.get_items_by_kinds(&[ItemKind::SpecialAbility])
+    }
+
+    pub fn get_weapons(&self) -> Vec<&Item> {
+        self.get_items_by_kinds(&[ItemKind::MeleeWeapon, ItemKind::RangeWeapon])
+    }
+
+    pub fn get_armor(&self) -> Vec<&Item> {
+        self.get_items_by_kinds(&[ItemKind::Armor])
+    }
+
+    pub fn get_ammunition(&self) -> Vec<&Item> {
+        self.get_items_by_kinds(&[ItemKind::Ammunition])
+    }
+
+    pub fn get_species_items(&self) -> Vec<&Item> {
+        self.get_items_by_kinds(&[ItemKind::Species])
+    }
+
+    /// Get the species name from the details, or from an embedded species item
+    pub fn species_name(&self) -> Option<String> {
+        self.system
+            .as_ref()
+            .and_then(|s| s.details.as_ref())
+            .and_then(|d| d.species_name())
+            .or_else(|| self.get_species_items().first().map(|item| item.name.clone()))
+    }
+
+    /// Get the experience (total and spent AP) recorded in the export
+    pub fn experience(&self) -> Option<&Experience> {
+        self.system
+            .as_ref()
+            .and_then(|s| s.details.as_ref())
+            .and_then(|d| d.experience.as_ref())
+    }
+
+    /// AP not yet spent: total minus the recorded spent AP, or minus the calculated AP if none are recorded
+    pub fn remaining_ap(&self, calculator: &ApCalculator) -> Option<i32> {
+        let experience = self.experience()?;
+        let spent = experience.spent().unwrap_or_else(|| calculator.calculate_total_spent_ap(self));
+        Some(experience.total() - spent)
+    }
+
+    /// Get the species data from the species table of `rules`, if the species is known
+    pub fn species(&self, rules: &Rules) -> Option<Species> {
+        self.species_name().and_then(|name| rules.find_species(&name).cloned())
     }
 
     pub fn has_image(&self) -> bool {
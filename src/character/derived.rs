@@ -0,0 +1,439 @@
+use crate::character::data::StatusValues;
+use crate::character::rules::{Rules, StepModifier};
+use crate::character::{Character, DerivedValue, Item};
+use serde::Serialize;
+
+/// Derived values (Grundwerte) that are calculated from characteristics, species and advantages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum DerivedValueKind {
+    LifePoints,
+    AstralEnergy,
+    KarmaEnergy,
+    SoulPower,
+    Toughness,
+    Speed,
+    FatePoints,
+}
+
+impl DerivedValueKind {
+    pub const ALL: [DerivedValueKind; 7] = [
+        DerivedValueKind::LifePoints,
+        DerivedValueKind::AstralEnergy,
+        DerivedValueKind::KarmaEnergy,
+        DerivedValueKind::SoulPower,
+        DerivedValueKind::Toughness,
+        DerivedValueKind::Speed,
+        DerivedValueKind::FatePoints,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DerivedValueKind::LifePoints => "Life Points (LeP)",
+            DerivedValueKind::AstralEnergy => "Astral Energy (AsP)",
+            DerivedValueKind::KarmaEnergy => "Karma Energy (KaP)",
+            DerivedValueKind::SoulPower => "Soul Power (SK)",
+            DerivedValueKind::Toughness => "Toughness (ZK)",
+            DerivedValueKind::Speed => "Speed (GS)",
+            DerivedValueKind::FatePoints => "Fate Points (SchiP)",
+        }
+    }
+
+    /// Key in the `derived_values` section of the rules file
+    pub fn rules_key(&self) -> &'static str {
+        match self {
+            DerivedValueKind::LifePoints => "life_points",
+            DerivedValueKind::AstralEnergy => "astral_energy",
+            DerivedValueKind::KarmaEnergy => "karma_energy",
+            DerivedValueKind::SoulPower => "soul_power",
+            DerivedValueKind::Toughness => "toughness",
+            DerivedValueKind::Speed => "speed",
+            DerivedValueKind::FatePoints => "fate_points",
+        }
+    }
+
+    /// Key in `system.status` of the Foundry export
+    fn status_key(&self) -> &'static str {
+        match self {
+            DerivedValueKind::LifePoints => "wounds",
+            DerivedValueKind::AstralEnergy => "astralenergy",
+            DerivedValueKind::KarmaEnergy => "karmaenergy",
+            DerivedValueKind::SoulPower => "soulpower",
+            DerivedValueKind::Toughness => "toughness",
+            DerivedValueKind::Speed => "speed",
+            DerivedValueKind::FatePoints => "fatePoints",
+        }
+    }
+}
+
+/// A calculated derived value next to the value Foundry stored in the export
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivedValueCheck {
+    pub kind: DerivedValueKind,
+    pub calculated: Option<DerivedValue>,
+    /// Why the value could not be calculated (unknown species, no tradition, ...)
+    pub missing_reason: Option<String>,
+    /// Maximum stored by Foundry
+    pub foundry: Option<i32>,
+}
+
+impl DerivedValueCheck {
+    /// Both values are known and differ
+    pub fn disagrees(&self) -> bool {
+        match (&self.calculated, self.foundry) {
+            (Some(calculated), Some(foundry)) => calculated.value != foundry,
+            _ => false,
+        }
+    }
+}
+
+/// Life points at or below which a pain level (Schmerz) applies
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PainThreshold {
+    pub level: i32,
+    pub life_points: i32,
+}
+
+impl PainThreshold {
+    pub fn name(&self) -> &'static str {
+        match self.level {
+            1 => "Schmerz I",
+            2 => "Schmerz II",
+            3 => "Schmerz III",
+            _ => "Schmerz IV",
+        }
+    }
+}
+
+/// Derived values of a character with their pain thresholds
+#[derive(Debug, Clone, Serialize)]
+pub struct DerivedValues {
+    pub values: Vec<DerivedValueCheck>,
+    /// Based on the calculated maximum LeP, or Foundry's if it cannot be calculated
+    pub pain_thresholds: Vec<PainThreshold>,
+}
+
+impl DerivedValues {
+    pub fn calculate(character: &Character, rules: &Rules) -> Self {
+        let status = character.system.as_ref().and_then(|s| s.status.as_ref());
+
+        let values: Vec<DerivedValueCheck> = DerivedValueKind::ALL
+            .iter()
+            .map(|&kind| {
+                let (calculated, missing_reason) = match Self::calculate_value(character, kind, rules) {
+                    Ok(value) => (value, None),
+                    Err(reason) => (None, Some(reason)),
+                };
+                DerivedValueCheck {
+                    kind,
+                    calculated,
+                    missing_reason,
+                    foundry: status.and_then(|s| foundry_value(s, kind)),
+                }
+            })
+            .collect();
+
+        let max_life_points = values
+            .iter()
+            .find(|check| check.kind == DerivedValueKind::LifePoints)
+            .and_then(|check| check.calculated.as_ref().map(|c| c.value).or(check.foundry));
+
+        let pain_thresholds = max_life_points.map(pain_thresholds).unwrap_or_default();
+
+        Self { values, pain_thresholds }
+    }
+
+    pub fn get(&self, kind: DerivedValueKind) -> Option<&DerivedValueCheck> {
+        self.values.iter().find(|check| check.kind == kind)
+    }
+
+    pub fn disagreements(&self) -> Vec<&DerivedValueCheck> {
+        self.values.iter().filter(|check| check.disagrees()).collect()
+    }
+
+    /// `Ok(None)` if the character does not have this value (e.g. AsP without a magical tradition)
+    fn calculate_value(character: &Character, kind: DerivedValueKind, rules: &Rules) -> Result<Option<DerivedValue>, String> {
+        let derived_rules = &rules.derived_values;
+        let system = character.system.as_ref().ok_or_else(|| "No system data".to_string())?;
+        let characteristics = system.characteristics.as_ref();
+        let status = system.status.as_ref();
+        let value_of = |key: &str| {
+            characteristics
+                .and_then(|c| c.get(key))
+                .map(|c| c.value())
+                .ok_or_else(|| format!("Missing characteristic {}", key.to_uppercase()))
+        };
+        let species = || match character.species_name() {
+            Some(name) => character.species(rules).ok_or_else(|| format!("Unknown species '{}'", name)),
+            None => Err("No species".to_string()),
+        };
+
+        let (modifier, modifier_formula) = advantage_modifiers(
+            character,
+            derived_rules.modifiers.get(kind.rules_key()).map(Vec::as_slice).unwrap_or_default(),
+        );
+
+        let (value, formula) = match kind {
+            DerivedValueKind::LifePoints => {
+                let species = species()?;
+                let ko = value_of("ko")?;
+                let advances = status.and_then(|s| s.wounds.as_ref()).map(|w| w.advances()).unwrap_or(0);
+                (
+                    species.life_points_base + 2 * ko + advances + modifier,
+                    format!("LeP = {} ({}) + 2 × KO {} + {} advances{}",
+                            species.life_points_base, species.name, ko, advances, modifier_formula),
+                )
+            }
+            DerivedValueKind::AstralEnergy | DerivedValueKind::KarmaEnergy => {
+                let Some((tradition, leading_attribute)) = find_tradition(character, kind, rules) else {
+                    let has_energy = status.and_then(|s| foundry_value(s, kind)).is_some_and(|max| max > 0);
+                    return if has_energy {
+                        Err("Unknown tradition".to_string())
+                    } else {
+                        Ok(None)
+                    };
+                };
+                let attribute_value = value_of(&leading_attribute)?;
+                let (advances, permanent_loss, rebuy) = match kind {
+                    DerivedValueKind::AstralEnergy => status
+                        .and_then(|s| s.astralenergy.as_ref())
+                        .map(|a| (a.advances(), a.permanent_loss(), a.rebuy_points())),
+                    _ => status
+                        .and_then(|s| s.karmaenergy.as_ref())
+                        .map(|k| (k.advances(), k.permanent_loss, k.rebuy)),
+                }
+                .unwrap_or_default();
+                // Permanently lost points count until they are bought back
+                let loss = (rebuy - permanent_loss).min(0);
+                (
+                    derived_rules.energy_base + attribute_value + advances + loss + modifier,
+                    format!("{} = {} + {} {} ({}) + {} advances{}{}",
+                            if kind == DerivedValueKind::AstralEnergy { "AsP" } else { "KaP" },
+                            derived_rules.energy_base, leading_attribute.to_uppercase(), attribute_value,
+                            tradition, advances,
+                            if loss != 0 { format!(" − {} permanent loss", -loss) } else { String::new() },
+                            modifier_formula),
+                )
+            }
+            DerivedValueKind::SoulPower => {
+                let species = species()?;
+                let (mu, kl, int) = (value_of("mu")?, value_of("kl")?, value_of("in")?);
+                let base = sixth_rounded(mu + kl + int);
+                (
+                    species.soul_power_base + base + modifier,
+                    format!("SK = {} ({}) + (MU {} + KL {} + IN {}) / 6, rounded = {} + {}{}",
+                            species.soul_power_base, species.name, mu, kl, int,
+                            species.soul_power_base, base, modifier_formula),
+                )
+            }
+            DerivedValueKind::Toughness => {
+                let species = species()?;
+                let (ko, kk) = (value_of("ko")?, value_of("kk")?);
+                let base = sixth_rounded(2 * ko + kk);
+                (
+                    species.toughness_base + base + modifier,
+                    format!("ZK = {} ({}) + (KO {} + KO {} + KK {}) / 6, rounded = {} + {}{}",
+                            species.toughness_base, species.name, ko, ko, kk,
+                            species.toughness_base, base, modifier_formula),
+                )
+            }
+            DerivedValueKind::Speed => {
+                let species = species()?;
+                (
+                    species.speed + modifier,
+                    format!("GS = {} ({}){}", species.speed, species.name, modifier_formula),
+                )
+            }
+            DerivedValueKind::FatePoints => (
+                derived_rules.fate_points_base + modifier,
+                format!("SchiP = {}{}", derived_rules.fate_points_base, modifier_formula),
+            ),
+        };
+
+        Ok(Some(DerivedValue { value, formula }))
+    }
+}
+
+/// Sum of the advantage/disadvantage modifiers and their part of the formula (" + 2 Hohe Lebenskraft")
+fn advantage_modifiers(character: &Character, modifiers: &[StepModifier]) -> (i32, String) {
+    let mut total = 0;
+    let mut formula = String::new();
+
+    let items = character.get_advantages().into_iter().chain(character.get_disadvantages());
+    for item in items {
+        let Some(modifier) = modifiers.iter().find(|m| m.matcher.matches_item(item)) else {
+            continue;
+        };
+        let step = step_of(item);
+        let value = modifier.per_step * step;
+        total += value;
+        formula.push_str(&format!(" {} {} {}", if value < 0 { "−" } else { "+" }, value.abs(), item.name));
+    }
+
+    (total, formula)
+}
+
+/// Tradition name and leading attribute key for AsP or KaP
+fn find_tradition(character: &Character, kind: DerivedValueKind, rules: &Rules) -> Option<(String, String)> {
+    character
+        .get_special_abilities()
+        .into_iter()
+        .find_map(|item| {
+            rules.derived_values.traditions
+                .iter()
+                .filter(|tradition| tradition.energy == kind.rules_key())
+                .find(|tradition| tradition.matcher.matches_item(item))
+                .map(|tradition| (item.name.clone(), tradition.leading_attribute.to_lowercase()))
+        })
+}
+
+fn step_of(item: &Item) -> i32 {
+    item.data().step().unwrap_or(1)
+}
+
+/// Maximum stored by Foundry: the typed `max` for energies, `max` or `value` for the other status values
+fn foundry_value(status: &StatusValues, kind: DerivedValueKind) -> Option<i32> {
+    match kind {
+        DerivedValueKind::LifePoints => status.wounds.as_ref().and_then(|w| w.max),
+        DerivedValueKind::AstralEnergy => status.astralenergy.as_ref().and_then(|a| a.max),
+        DerivedValueKind::KarmaEnergy => status.karmaenergy.as_ref().and_then(|k| k.max),
+        _ => {
+            let value = status.other.get(kind.status_key())?;
+            value.get("max")
+                .or_else(|| value.get("value"))
+                .and_then(|v| v.as_i64())
+                .map(|v| v as i32)
+        }
+    }
+}
+
+/// Pain levels I–III at ¾, ½ and ¼ of the maximum LeP, level IV at 5 LeP
+pub fn pain_thresholds(max_life_points: i32) -> Vec<PainThreshold> {
+    [
+        (1, max_life_points * 3 / 4),
+        (2, max_life_points / 2),
+        (3, max_life_points / 4),
+        (4, 5),
+    ]
+    .into_iter()
+    .map(|(level, life_points)| PainThreshold { level, life_points })
+    .collect()
+}
+
+/// A sixth of a value, rounded half up
+fn sixth_rounded(value: i32) -> i32 {
+    (value + 3).div_euclid(6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn characteristic(value: i32) -> Value {
+        json!({ "initial": 8, "advances": value - 8 })
+    }
+
+    fn item(name: &str, item_type: &str, step: i32) -> Value {
+        json!({ "_id": name, "name": name, "type": item_type, "img": null, "system": { "step": { "value": step } } })
+    }
+
+    fn energy(advances: i32, max: i32, permanent_loss: i32, rebuy: i32) -> Value {
+        json!({
+            "initial": 0, "value": max, "advances": advances, "modifier": 0, "current": max, "max": max,
+            "permanentLoss": permanent_loss, "rebuy": rebuy,
+        })
+    }
+
+    /// A human mage: MU 14, KL 12, IN 13, KO 13, KK 11, 2 LeP and 3 AsP bought, 4 AsP lost and 1 bought back
+    fn mage(species: &str, items: Vec<Value>) -> Character {
+        serde_json::from_value(json!({
+            "name": "Test",
+            "type": "character",
+            "img": null,
+            "items": items,
+            "system": {
+                "characteristics": {
+                    "mu": characteristic(14), "kl": characteristic(12), "in": characteristic(13), "ch": characteristic(12),
+                    "ff": characteristic(12), "ge": characteristic(12), "ko": characteristic(13), "kk": characteristic(11),
+                },
+                "status": {
+                    "wounds": { "initial": 0, "value": 34, "advances": 2, "modifier": 0, "current": 34, "max": 34 },
+                    "astralenergy": energy(3, 32, 4, 1),
+                    "karmaenergy": energy(0, 0, 0, 0),
+                },
+                "details": { "species": { "value": species } },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn calculated(values: &DerivedValues, kind: DerivedValueKind) -> Option<i32> {
+        values.get(kind).and_then(|check| check.calculated.as_ref()).map(|value| value.value)
+    }
+
+    #[test]
+    fn calculates_the_derived_values_of_a_human() {
+        let values = DerivedValues::calculate(&mage("Mensch", vec![
+            item("Hohe Lebenskraft", "advantage", 2),
+            item("Behäbig", "disadvantage", 1),
+            item("Tradition (Gildenmagier)", "specialability", 1),
+        ]), Rules::builtin());
+
+        // 5 + 2 × 13 + 2 advances + 2 Hohe Lebenskraft
+        assert_eq!(calculated(&values, DerivedValueKind::LifePoints), Some(35));
+        // 20 + KL 12 + 3 advances − 3 lost and not bought back
+        assert_eq!(calculated(&values, DerivedValueKind::AstralEnergy), Some(32));
+        // −5 + (14 + 12 + 13) / 6 = −5 + 6.5, rounded up
+        assert_eq!(calculated(&values, DerivedValueKind::SoulPower), Some(2));
+        // −5 + (13 + 13 + 11) / 6 = −5 + 6.17, rounded down
+        assert_eq!(calculated(&values, DerivedValueKind::Toughness), Some(1));
+        assert_eq!(calculated(&values, DerivedValueKind::Speed), Some(7));
+        assert_eq!(calculated(&values, DerivedValueKind::FatePoints), Some(3));
+    }
+
+    #[test]
+    fn compares_with_the_stored_maximum() {
+        let values = DerivedValues::calculate(&mage("Mensch", vec![
+            item("Hohe Lebenskraft", "advantage", 2),
+            item("Tradition (Gildenmagier)", "specialability", 1),
+        ]), Rules::builtin());
+
+        let disagreements: Vec<DerivedValueKind> = values.disagreements().iter().map(|check| check.kind).collect();
+        assert_eq!(disagreements, [DerivedValueKind::LifePoints]);
+        assert_eq!(values.get(DerivedValueKind::LifePoints).unwrap().foundry, Some(34));
+    }
+
+    #[test]
+    fn energies_without_tradition() {
+        let values = DerivedValues::calculate(&mage("Mensch", Vec::new()), Rules::builtin());
+
+        // AsP are stored but no tradition grants them; KaP are neither stored nor granted
+        let astral_energy = values.get(DerivedValueKind::AstralEnergy).unwrap();
+        assert!(astral_energy.calculated.is_none());
+        assert_eq!(astral_energy.missing_reason.as_deref(), Some("Unknown tradition"));
+        let karma_energy = values.get(DerivedValueKind::KarmaEnergy).unwrap();
+        assert!(karma_energy.calculated.is_none() && karma_energy.missing_reason.is_none());
+    }
+
+    #[test]
+    fn unknown_species_is_reported() {
+        let values = DerivedValues::calculate(&mage("Drache", Vec::new()), Rules::builtin());
+        let life_points = values.get(DerivedValueKind::LifePoints).unwrap();
+        assert!(life_points.calculated.is_none());
+        assert_eq!(life_points.missing_reason.as_deref(), Some("Unknown species 'Drache'"));
+        // Values that do not depend on the species are still calculated
+        assert_eq!(calculated(&values, DerivedValueKind::FatePoints), Some(3));
+    }
+
+    #[test]
+    fn pain_thresholds_from_the_maximum_life_points() {
+        let thresholds: Vec<(i32, i32)> = pain_thresholds(35).iter().map(|t| (t.level, t.life_points)).collect();
+        assert_eq!(thresholds, [(1, 26), (2, 17), (3, 8), (4, 5)]);
+    }
+
+    #[test]
+    fn sixth_rounded_half_up() {
+        let rounded: Vec<i32> = [0, 2, 3, 8, 9, 39, -3, -4].into_iter().map(sixth_rounded).collect();
+        assert_eq!(rounded, [0, 0, 1, 1, 2, 7, 0, -1]);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::character::{ApCalculator, Character};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Differences between two exports of the same character, e.g. before and after a session
+#[derive(Debug, Clone, Serialize)]
+pub struct CharacterDiff {
+    pub old_character: String,
+    pub new_character: String,
+    /// Calculated spent AP of the old export
+    pub old_total: i32,
+    /// Calculated spent AP of the new export
+    pub new_total: i32,
+    /// Changes sorted by category, then by name
+    pub changes: Vec<DiffEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "+",
+            ChangeKind::Removed => "−",
+            ChangeKind::Changed => "~",
+        }
+    }
+}
+
+/// A single change of an item, characteristic or energy
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffEntry {
+    pub name: String,
+    /// Foundry item type, or "characteristic"/"energy"/"species" for values stored on the actor
+    pub item_type: String,
+    pub category: String,
+    pub kind: ChangeKind,
+    /// Talent value, step, characteristic value or energy advances before the change
+    pub old_value: Option<i32>,
+    /// Talent value, step, characteristic value or energy advances after the change
+    pub new_value: Option<i32>,
+    /// Number of items with this name (only for items)
+    pub old_count: usize,
+    pub new_count: usize,
+    /// AP spent after the change minus AP spent before
+    pub ap_delta: i32,
+}
+
+/// State of one item, characteristic or energy in one export
+#[derive(Debug, Default)]
+struct Snapshot {
+    category: Option<String>,
+    value: Option<i32>,
+    item_count: usize,
+    ap_cost: i32,
+}
+
+type SnapshotKey = (String, String);
+
+impl CharacterDiff {
+    /// Compare two exports. AP deltas use the same calculation as the AP report,
+    /// so the sum of all deltas equals `new_total - old_total`.
+    pub fn between(old: &Character, new: &Character, calculator: &ApCalculator) -> Self {
+        let (old_snapshots, old_total) = Self::snapshots(old, calculator);
+        let (new_snapshots, new_total) = Self::snapshots(new, calculator);
+
+        let keys: HashSet<&SnapshotKey> = old_snapshots.keys().chain(new_snapshots.keys()).collect();
+        let empty = Snapshot::default();
+
+        let mut changes: Vec<DiffEntry> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let before = old_snapshots.get(key).unwrap_or(&empty);
+                let after = new_snapshots.get(key).unwrap_or(&empty);
+
+                let kind = if before.item_count == 0 && after.item_count > 0 {
+                    ChangeKind::Added
+                } else if before.item_count > 0 && after.item_count == 0 {
+                    ChangeKind::Removed
+                } else if before.value != after.value
+                    || before.ap_cost != after.ap_cost
+                    || before.item_count != after.item_count
+                {
+                    ChangeKind::Changed
+                } else {
+                    return None;
+                };
+
+                let (item_type, name) = key.clone();
+                let category = after.category.clone()
+                    .or_else(|| before.category.clone())
+                    .unwrap_or_else(|| item_type.clone());
+
+                Some(DiffEntry {
+                    name,
+                    item_type,
+                    category,
+                    kind,
+                    old_value: before.value,
+                    new_value: after.value,
+                    old_count: before.item_count,
+                    new_count: after.item_count,
+                    ap_delta: after.ap_cost - before.ap_cost,
+                })
+            })
+            .collect();
+
+        changes.sort_by(|a, b| {
+            a.category.cmp(&b.category)
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.item_type.cmp(&b.item_type))
+        });
+
+        Self {
+            old_character: old.name.clone(),
+            new_character: new.name.clone(),
+            old_total,
+            new_total,
+            changes,
+        }
+    }
+
+    /// Total AP delta between the two exports
+    pub fn ap_delta(&self) -> i32 {
+        self.new_total - self.old_total
+    }
+
+    /// Changes that cost or refund AP
+    pub fn ap_changes(&self) -> impl Iterator<Item = &DiffEntry> {
+        self.changes.iter().filter(|change| change.ap_delta != 0)
+    }
+
+    fn snapshots(character: &Character, calculator: &ApCalculator) -> (BTreeMap<SnapshotKey, Snapshot>, i32) {
+        let mut snapshots: BTreeMap<SnapshotKey, Snapshot> = BTreeMap::new();
+
+        // All items, including equipment that does not cost AP
+        for item in &character.items {
+            let snapshot = snapshots
+                .entry((item.kind.to_string(), item.name.clone()))
+                .or_default();
+            snapshot.item_count += 1;
+
+            let data = item.data();
+            let value = data.talent_value().or_else(|| data.step());
+            if value.is_some() {
+                snapshot.value = snapshot.value.max(value);
+            }
+        }
+
+        // AP per item, and the values stored on the actor (characteristics, energies, species)
+        let report = calculator.build_report(character);
+        for entry in &report.entries {
+            let snapshot = snapshots
+                .entry((entry.item_type.clone(), entry.name.clone()))
+                .or_default();
+            snapshot.category = Some(entry.category.clone());
+
+            if snapshot.item_count == 0 {
+                snapshot.value = entry.value;
+            }
+            if !entry.excluded {
+                snapshot.ap_cost += entry.ap_cost;
+            }
+        }
+
+        (snapshots, report.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn skill(name: &str, value: i32) -> Value {
+        json!({
+            "_id": name, "name": name, "type": "skill", "img": null,
+            "system": {
+                "talentValue": { "value": value }, "StF": { "value": "B" },
+                "characteristic1": { "value": "mu" }, "characteristic2": { "value": "kl" }, "characteristic3": { "value": "in" },
+            },
+        })
+    }
+
+    fn advantage(id: &str, name: &str, ap_value: i32) -> Value {
+        json!({ "_id": id, "name": name, "type": "advantage", "img": null, "system": { "APValue": { "value": ap_value.to_string() } } })
+    }
+
+    fn hero(mu_advances: i32, items: Vec<Value>) -> Character {
+        serde_json::from_value(json!({
+            "name": "Test", "type": "character", "img": null, "items": items,
+            "system": { "characteristics": { "mu": { "initial": 8, "advances": mu_advances } } },
+        }))
+        .unwrap()
+    }
+
+    fn change<'a>(diff: &'a CharacterDiff, name: &str) -> &'a DiffEntry {
+        diff.changes.iter().find(|change| change.name == name).unwrap()
+    }
+
+    #[test]
+    fn deltas_sum_up_to_the_difference_of_the_totals() {
+        let old = hero(4, vec![skill("Klettern", 4), skill("Schwimmen", 2), advantage("a1", "Glück", 30)]);
+        let new = hero(6, vec![skill("Klettern", 7), advantage("a2", "Begabung (Klettern)", 12), advantage("a1", "Glück", 30)]);
+        let diff = CharacterDiff::between(&old, &new, &ApCalculator::default());
+
+        assert_ne!(diff.ap_delta(), 0);
+        assert_eq!(diff.changes.iter().map(|change| change.ap_delta).sum::<i32>(), diff.ap_delta());
+        assert_eq!(diff.ap_changes().map(|change| change.ap_delta).sum::<i32>(), diff.ap_delta());
+    }
+
+    #[test]
+    fn changes_are_classified_as_added_removed_or_changed() {
+        let old = hero(4, vec![skill("Klettern", 4), skill("Schwimmen", 2), advantage("a1", "Glück", 30)]);
+        let new = hero(4, vec![skill("Klettern", 7), advantage("a2", "Begabung (Klettern)", 12), advantage("a1", "Glück", 30)]);
+        let diff = CharacterDiff::between(&old, &new, &ApCalculator::default());
+
+        let klettern = change(&diff, "Klettern");
+        assert_eq!((klettern.kind, klettern.old_value, klettern.new_value), (ChangeKind::Changed, Some(4), Some(7)));
+        assert_eq!(change(&diff, "Schwimmen").kind, ChangeKind::Removed);
+        let added = change(&diff, "Begabung (Klettern)");
+        assert_eq!((added.kind, added.ap_delta), (ChangeKind::Added, 12));
+        // Unchanged values are no changes
+        assert!(diff.changes.iter().all(|change| change.name != "Glück" && change.name != "Mut"));
+    }
+
+    #[test]
+    fn items_with_the_same_name_are_grouped() {
+        let old = hero(4, vec![advantage("a1", "Glück", 10)]);
+        let new = hero(4, vec![advantage("a1", "Glück", 10), advantage("a2", "Glück", 10)]);
+        let diff = CharacterDiff::between(&old, &new, &ApCalculator::default());
+
+        assert_eq!(diff.changes.len(), 1);
+        let grouped = change(&diff, "Glück");
+        assert_eq!((grouped.kind, grouped.old_count, grouped.new_count), (ChangeKind::Changed, 1, 2));
+        assert_eq!(grouped.ap_delta, 10);
+        assert_eq!(diff.ap_delta(), 10);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::character::{Character, Item, ItemData, Rules};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Containers can be nested; deeper chains are treated as not carried
+const MAX_CONTAINER_DEPTH: usize = 10;
+
+/// An inventory item with weight and whether it counts towards the carried weight
+#[derive(Debug, Clone, Serialize)]
+pub struct CarriedItem {
+    pub name: String,
+    pub item_type: String,
+    pub quantity: i32,
+    /// Weight of all units in Stein
+    pub weight: f64,
+    pub carried: bool,
+    /// Why the item does not count, e.g. "not worn"
+    pub excluded_reason: Option<String>,
+}
+
+/// Worn armor with its protection (RS) and encumbrance (BE)
+#[derive(Debug, Clone, Serialize)]
+pub struct WornArmor {
+    pub name: String,
+    pub protection: i32,
+    pub encumbrance: i32,
+}
+
+/// Encumbrance (Belastung) from carried weight and worn armor
+#[derive(Debug, Clone, Serialize)]
+pub struct Encumbrance {
+    pub items: Vec<CarriedItem>,
+    /// Weight of all carried items in Stein
+    pub carried_weight: f64,
+    /// KK × 2 Stein, `None` without KK
+    pub carrying_capacity: Option<f64>,
+    /// Levels from the weight above the carrying capacity
+    pub weight_level: i32,
+    pub armor: Vec<WornArmor>,
+    /// Sum of the BE of the worn armor
+    pub armor_level: i32,
+    /// Total level, capped at the highest level
+    pub level: i32,
+    /// Highest level, at which the character is incapacitated
+    pub max_level: i32,
+    /// Skills that are affected by encumbrance (burden "yes")
+    pub affected_skills: Vec<String>,
+    /// Skills that may be affected, depending on the situation (burden "maybe")
+    pub possibly_affected_skills: Vec<String>,
+}
+
+impl Encumbrance {
+    pub fn calculate(character: &Character, rules: &Rules) -> Self {
+        let encumbrance_rules = &rules.encumbrance;
+        let items_by_id: HashMap<&str, &Item> = character.items.iter().map(|item| (item._id.as_str(), item)).collect();
+
+        let items: Vec<CarriedItem> = character.items
+            .iter()
+            .filter_map(|item| {
+                let data = item.data();
+                let equipment = data.equipment()?;
+                let unit_weight = equipment.weight?;
+                // The BE of worn armor already stands for its weight
+                let excluded_reason = if matches!(data, ItemData::Armor(_)) && equipment.worn == Some(true) {
+                    Some("worn armor (BE)".to_string())
+                } else {
+                    not_carried_reason(item, &items_by_id, 0)
+                };
+                Some(CarriedItem {
+                    name: item.name.clone(),
+                    item_type: item.kind.to_string(),
+                    quantity: equipment.quantity,
+                    weight: unit_weight * equipment.quantity as f64,
+                    carried: excluded_reason.is_none(),
+                    excluded_reason,
+                })
+            })
+            .collect();
+
+        let carried_weight: f64 = items.iter().filter(|item| item.carried).map(|item| item.weight).sum();
+
+        let carrying_capacity = character.system
+            .as_ref()
+            .and_then(|s| s.characteristics.as_ref())
+            .and_then(|c| c.get("kk"))
+            .map(|kk| kk.value() as f64 * encumbrance_rules.carrying_capacity_per_kk);
+
+        let weight_level = carrying_capacity
+            .map(|capacity| ((carried_weight - capacity).max(0.0) / encumbrance_rules.stone_per_level).floor() as i32)
+            .unwrap_or(0);
+
+        let armor: Vec<WornArmor> = character.items
+            .iter()
+            .filter_map(|item| match &item.data() {
+                ItemData::Armor(armor) if armor.equipment.worn == Some(true) => Some(WornArmor {
+                    name: item.name.clone(),
+                    protection: armor.protection.unwrap_or(0),
+                    encumbrance: armor.encumbrance.unwrap_or(0),
+                }),
+                _ => None,
+            })
+            .collect();
+        let armor_level = armor.iter().map(|armor| armor.encumbrance).sum();
+
+        let skills_with_burden = |burden: &str| -> Vec<String> {
+            character.get_skills()
+                .into_iter()
+                .filter(|skill| match skill.data() {
+                    ItemData::Talent(talent) => talent.burden.is_some_and(|b| b.eq_ignore_ascii_case(burden)),
+                    _ => false,
+                })
+                .map(|skill| skill.name.clone())
+                .collect()
+        };
+
+        Self {
+            items,
+            carried_weight,
+            carrying_capacity,
+            weight_level,
+            armor,
+            armor_level,
+            level: (weight_level + armor_level).clamp(0, encumbrance_rules.max_level),
+            max_level: encumbrance_rules.max_level,
+            affected_skills: skills_with_burden("yes"),
+            possibly_affected_skills: skills_with_burden("maybe"),
+        }
+    }
+
+    /// A value reduced by one per encumbrance level (AT, PA, FK, AW, INI, GS and affected skill checks)
+    pub fn apply(&self, value: i32) -> i32 {
+        value - self.level
+    }
+
+    /// At the highest level the character is incapacitated (handlungsunfähig)
+    pub fn is_incapacitated(&self) -> bool {
+        self.level >= self.max_level
+    }
+}
+
+/// Items marked as not worn do not count, nor do items in a container that is not carried
+fn not_carried_reason(item: &Item, items_by_id: &HashMap<&str, &Item>, depth: usize) -> Option<String> {
+    let data = item.data();
+    let equipment = data.equipment()?;
+    if equipment.worn == Some(false) && depth == 0 {
+        return Some("not worn".to_string());
+    }
+
+    let container = equipment.container_id.as_deref().and_then(|id| items_by_id.get(id))?;
+    if depth >= MAX_CONTAINER_DEPTH {
+        return Some("nested too deeply".to_string());
+    }
+    if container.data().equipment().and_then(|equipment| equipment.worn) == Some(false) {
+        return Some(format!("stored in {}", container.name));
+    }
+    not_carried_reason(container, items_by_id, depth + 1).map(|_| format!("stored in {}", container.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn equipment(id: &str, weight: f64, worn: Option<bool>, container: Option<&str>) -> Value {
+        let mut system = json!({ "weight": { "value": weight }, "quantity": { "value": 1 } });
+        if let Some(worn) = worn {
+            system["worn"] = json!({ "value": worn });
+        }
+        if let Some(container) = container {
+            system["parent_id"] = json!(container);
+        }
+        json!({ "_id": id, "name": id, "type": "equipment", "img": null, "system": system })
+    }
+
+    fn armor(id: &str, weight: f64, worn: bool, encumbrance: i32) -> Value {
+        json!({
+            "_id": id, "name": id, "type": "armor", "img": null,
+            "system": {
+                "weight": { "value": weight }, "quantity": { "value": 1 }, "worn": { "value": worn },
+                "protection": { "value": 3 }, "encumbrance": { "value": encumbrance },
+            },
+        })
+    }
+
+    /// KK 10: a carrying capacity of 20 Stein
+    fn hero(items: Vec<Value>) -> Character {
+        serde_json::from_value(json!({
+            "name": "Test", "type": "character", "img": null, "items": items,
+            "system": { "characteristics": { "kk": { "initial": 8, "advances": 2 } } },
+        }))
+        .unwrap()
+    }
+
+    fn reason<'a>(encumbrance: &'a Encumbrance, name: &str) -> Option<&'a str> {
+        encumbrance.items.iter().find(|item| item.name == name).and_then(|item| item.excluded_reason.as_deref())
+    }
+
+    #[test]
+    fn items_in_containers_that_are_not_carried_do_not_count() {
+        let character = hero(vec![
+            equipment("backpack", 1.0, Some(true), None),
+            equipment("pouch", 0.5, None, Some("backpack")),
+            equipment("rope", 2.0, None, Some("pouch")),
+            equipment("chest", 10.0, Some(false), None),
+            equipment("box", 1.0, None, Some("chest")),
+            equipment("coins", 0.5, None, Some("box")),
+        ]);
+        let encumbrance = Encumbrance::calculate(&character, Rules::builtin());
+
+        assert_eq!(reason(&encumbrance, "rope"), None);
+        assert_eq!(reason(&encumbrance, "chest"), Some("not worn"));
+        assert_eq!(reason(&encumbrance, "box"), Some("stored in chest"));
+        assert_eq!(reason(&encumbrance, "coins"), Some("stored in box"));
+        assert_eq!(encumbrance.carried_weight, 3.5);
+    }
+
+    #[test]
+    fn worn_armor_counts_with_its_be_and_not_with_its_weight() {
+        let character = hero(vec![
+            armor("chain mail", 6.0, true, 2),
+            armor("spare leather armor", 3.0, false, 1),
+            equipment("anvil", 20.0, None, None),
+        ]);
+        let encumbrance = Encumbrance::calculate(&character, Rules::builtin());
+
+        assert_eq!(reason(&encumbrance, "chain mail"), Some("worn armor (BE)"));
+        assert_eq!(reason(&encumbrance, "spare leather armor"), Some("not worn"));
+        assert_eq!(encumbrance.carried_weight, 20.0);
+        assert_eq!(encumbrance.weight_level, 0);
+        assert_eq!(encumbrance.armor_level, 2);
+        assert_eq!(encumbrance.level, 2);
+    }
+
+    #[test]
+    fn level_is_capped_at_the_highest_level() {
+        let character = hero(vec![equipment("boulder", 200.0, None, None), armor("plate", 14.0, true, 3)]);
+        let encumbrance = Encumbrance::calculate(&character, Rules::builtin());
+
+        assert!(encumbrance.weight_level + encumbrance.armor_level > encumbrance.max_level);
+        assert_eq!(encumbrance.level, encumbrance.max_level);
+        assert!(encumbrance.is_incapacitated());
+        assert_eq!(encumbrance.apply(10), 10 - encumbrance.max_level);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::character::{ApCalculator, Character, Characteristics, ExperienceLevel, Item, ItemKind};
+use serde::Serialize;
+
+/// A creation maximum of the experience level next to the character's current value
+#[derive(Debug, Clone, Serialize)]
+pub struct LevelCap {
+    pub name: &'static str,
+    pub limit: i32,
+    pub current: Option<i32>,
+    /// The value that reaches `current`, e.g. the highest skill
+    pub subject: Option<String>,
+}
+
+impl LevelCap {
+    pub fn exceeded(&self) -> bool {
+        self.current.is_some_and(|current| current > self.limit)
+    }
+}
+
+/// Experience level (Erfahrungsgrad) inferred from the starting AP, with the caps it implies
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperienceLevelCheck {
+    pub total_ap: i32,
+    /// AP awarded since creation, if known
+    pub awarded_ap: Option<i32>,
+    /// `total_ap - awarded_ap`, or the total AP if the awards are not known
+    pub start_ap: i32,
+    /// Highest level whose starting AP does not exceed `start_ap`
+    pub level: Option<ExperienceLevel>,
+    /// The total is exactly the starting AP of the level plus the awards (only checked if the awards are known)
+    pub confirmed: Option<bool>,
+    pub caps: Vec<LevelCap>,
+}
+
+impl ExperienceLevelCheck {
+    /// `None` if the export has no experience data
+    pub fn evaluate(character: &Character, awarded_ap: Option<i32>, calculator: &ApCalculator) -> Option<Self> {
+        let total_ap = character.experience()?.total();
+        let start_ap = total_ap - awarded_ap.unwrap_or(0);
+        let level = calculator.rules().experience_level_for(start_ap).cloned();
+
+        let confirmed = awarded_ap.map(|_| level.as_ref().is_some_and(|level| level.ap == start_ap));
+        let caps = level.as_ref().map(|level| Self::caps(character, level, calculator)).unwrap_or_default();
+
+        Some(Self { total_ap, awarded_ap, start_ap, level, confirmed, caps })
+    }
+
+    pub fn exceeded_caps(&self) -> impl Iterator<Item = &LevelCap> {
+        self.caps.iter().filter(|cap| cap.exceeded())
+    }
+
+    fn caps(character: &Character, level: &ExperienceLevel, calculator: &ApCalculator) -> Vec<LevelCap> {
+        let rules = calculator.rules();
+        let characteristics = character.system.as_ref().and_then(|s| s.characteristics.as_ref());
+        let characteristic_values: Vec<(&str, i32)> = Characteristics::NAMES
+            .iter()
+            .filter_map(|(key, name)| characteristics.and_then(|c| c.get(key)).map(|c| (*name, c.value())))
+            .collect();
+
+        let highest_characteristic = characteristic_values.iter().max_by_key(|(_, value)| *value);
+        let characteristic_sum = (!characteristic_values.is_empty())
+            .then(|| characteristic_values.iter().map(|(_, value)| value).sum());
+
+        let report = calculator.build_report(character);
+        let count = |items: Vec<&Item>| Some(items.len() as i32);
+
+        vec![
+            LevelCap {
+                name: "Characteristic",
+                limit: level.max_characteristic,
+                current: highest_characteristic.map(|(_, value)| *value),
+                subject: highest_characteristic.map(|(name, _)| name.to_string()),
+            },
+            LevelCap {
+                name: "Sum of characteristics",
+                limit: level.max_characteristic_sum,
+                current: characteristic_sum,
+                subject: None,
+            },
+            Self::highest_talent_cap("Skill", level.max_skill, character.get_skills()),
+            Self::highest_talent_cap("Combat technique", level.max_combat_technique, character.get_combat_skills()),
+            LevelCap {
+                name: "Spells/Rituals",
+                limit: level.max_spells,
+                current: count(character.get_spells_and_rituals()),
+                subject: None,
+            },
+            LevelCap {
+                name: "Liturgies/Ceremonies",
+                limit: level.max_spells,
+                current: count(character.get_liturgies_and_ceremonies()),
+                subject: None,
+            },
+            LevelCap {
+                name: "Advantages (AP)",
+                limit: rules.creation_limits.advantages_ap,
+                current: Some(report.item_type_total(&ItemKind::Advantage)),
+                subject: None,
+            },
+            LevelCap {
+                name: "Disadvantages (AP)",
+                limit: rules.creation_limits.disadvantages_ap,
+                current: Some(-report.item_type_total(&ItemKind::Disadvantage)),
+                subject: None,
+            },
+        ]
+    }
+
+    fn highest_talent_cap(name: &'static str, limit: i32, items: Vec<&Item>) -> LevelCap {
+        let highest = items
+            .into_iter()
+            .filter_map(|item| item.data().talent_value().map(|value| (item, value)))
+            .max_by_key(|(_, value)| *value);
+
+        LevelCap {
+            name,
+            limit,
+            current: highest.map(|(_, value)| value),
+            subject: highest.map(|(item, _)| item.name.clone()),
+        }
+    }
+}
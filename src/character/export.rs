@@ -0,0 +1,425 @@
+use crate::character::{ApCalculator, Character, CharacterFormat, Characteristics, CharacteristicValue, Item, ItemData};
+use serde_json::Value;
+use std::ops::Range;
+use std::path::Path;
+
+/// `system` keys of the item values that can be edited
+const EDITABLE_ITEM_VALUES: [&str; 2] = ["talentValue", "step"];
+
+/// A Foundry actor export as loaded, used to write edits back into it.
+/// Only edited values and the spent AP are replaced in the original text; everything else,
+/// including the layout, number forms (`1e3`) and escapes (`\u00e4`), is written as it was read.
+#[derive(Debug, Clone)]
+pub struct FoundryExport {
+    /// The export as read
+    content: String,
+    /// The parsed export with its original key order
+    source: Value,
+    /// The export was pretty-printed (Foundry writes it with an indentation of 2)
+    pretty: bool,
+    trailing_newline: bool,
+    /// The character before any edits
+    pub original: Character,
+}
+
+/// One step of the path to a value in the export
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathStep {
+    Key(&'static str),
+    Index(usize),
+}
+
+impl FoundryExport {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        if CharacterFormat::detect(content)? != CharacterFormat::Foundry {
+            anyhow::bail!("Only Foundry actor exports can be written back");
+        }
+        Ok(Self {
+            content: content.to_string(),
+            source: serde_json::from_str(content)?,
+            pretty: content.trim().contains('\n'),
+            trailing_newline: content.ends_with('\n'),
+            original: Character::from_json(content)?,
+        })
+    }
+
+    /// The export with the edited talent values, steps and characteristic advances of `character`
+    /// and its recomputed spent AP (`details.experience.spent`).
+    /// The changed numbers are patched into the original text. Only if one of them is missing
+    /// from the export, the whole export is serialized again, which normalizes number forms and escapes.
+    pub fn to_json(&self, character: &Character, calculator: &ApCalculator) -> anyhow::Result<String> {
+        let edits = self.edits(character, calculator);
+
+        let spans: Option<Vec<_>> = edits
+            .iter()
+            .map(|(path, value)| value_span(&self.content, path).map(|span| (span, *value)))
+            .collect();
+        if let Some(mut spans) = spans {
+            spans.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+            let mut output = self.content.clone();
+            for (span, value) in spans {
+                // Keep the number a string if the export stores it as one
+                let replacement = if output[span.clone()].starts_with('"') {
+                    format!("\"{}\"", value)
+                } else {
+                    value.to_string()
+                };
+                output.replace_range(span, &replacement);
+            }
+            return Ok(output);
+        }
+
+        let mut json = self.source.clone();
+        for (path, value) in &edits {
+            set_number(&mut json, path, *value)?;
+        }
+        let mut output = if self.pretty {
+            serde_json::to_string_pretty(&json)?
+        } else {
+            serde_json::to_string(&json)?
+        };
+        if self.trailing_newline {
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    pub fn save(&self, character: &Character, path: &Path, calculator: &ApCalculator) -> anyhow::Result<()> {
+        std::fs::write(path, self.to_json(character, calculator)?)?;
+        Ok(())
+    }
+
+    /// Paths and new values of everything `character` changed against the export
+    fn edits(&self, character: &Character, calculator: &ApCalculator) -> Vec<(Vec<PathStep>, i32)> {
+        let mut edits = Vec::new();
+
+        let raw_items = self.source.get("items").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+        for (index, raw_item) in raw_items.iter().enumerate() {
+            let Some(id) = raw_item.get("_id").and_then(Value::as_str) else {
+                continue;
+            };
+            let (Some(original), Some(edited)) = (self.original.find_item(id), character.find_item(id)) else {
+                continue;
+            };
+            for key in EDITABLE_ITEM_VALUES {
+                let value = edited.system.get_value_as_i32(key);
+                if let Some(value) = value.filter(|_| value != original.system.get_value_as_i32(key)) {
+                    let path = [PathStep::Key("items"), PathStep::Index(index), PathStep::Key("system"), PathStep::Key(key), PathStep::Key("value")];
+                    edits.push((path.to_vec(), value));
+                }
+            }
+        }
+
+        let original_characteristics = self.original.system.as_ref().and_then(|s| s.characteristics.as_ref());
+        let edited_characteristics = character.system.as_ref().and_then(|s| s.characteristics.as_ref());
+        if let (Some(original), Some(edited)) = (original_characteristics, edited_characteristics) {
+            for (key, _) in Characteristics::NAMES {
+                let advances = edited.get(key).map(CharacteristicValue::advances);
+                if let Some(advances) = advances.filter(|_| advances != original.get(key).map(CharacteristicValue::advances)) {
+                    let path = [PathStep::Key("system"), PathStep::Key("characteristics"), PathStep::Key(key), PathStep::Key("advances")];
+                    edits.push((path.to_vec(), advances));
+                }
+            }
+        }
+
+        if self.source.pointer("/system/details/experience").is_some() {
+            let spent = calculator.calculate_total_spent_ap(character);
+            if Some(spent) != self.original.experience().and_then(|experience| experience.spent()) {
+                let path = [PathStep::Key("system"), PathStep::Key("details"), PathStep::Key("experience"), PathStep::Key("spent")];
+                edits.push((path.to_vec(), spent));
+            }
+        }
+
+        edits
+    }
+}
+
+/// Edits made in the GUI; they change the raw `system` as well as the typed view
+impl Character {
+    /// Set the talent value of a skill, combat technique, spell, ritual, liturgy or ceremony
+    pub fn set_talent_value(&mut self, item_id: &str, value: i32) -> anyhow::Result<()> {
+        let item = self.find_item_mut(item_id)?;
+        if !item.kind.has_talent_value() {
+            anyhow::bail!("{} ({}) has no talent value", item.name, item.kind);
+        }
+        item.set_system_value("talentValue", value);
+        Ok(())
+    }
+
+    /// Set the step (Stufe) of an advantage, disadvantage or special ability, from 1 to its highest step
+    pub fn set_step(&mut self, item_id: &str, step: i32) -> anyhow::Result<()> {
+        let item = self.find_item_mut(item_id)?;
+        let ItemData::Purchase(purchase) = item.data() else {
+            anyhow::bail!("{} ({}) has no step", item.name, item.kind);
+        };
+        let max_step = purchase.max_step.unwrap_or(1);
+        if !(1..=max_step).contains(&step) {
+            anyhow::bail!("{} has no step {} (highest step {})", item.name, step, max_step);
+        }
+        item.set_system_value("step", step);
+        Ok(())
+    }
+
+    /// Set the advances of a characteristic ("mu", "kl", ...)
+    pub fn set_characteristic_advances(&mut self, key: &str, advances: i32) -> anyhow::Result<()> {
+        let characteristic = self.system
+            .as_mut()
+            .and_then(|s| s.characteristics.as_mut())
+            .and_then(|c| c.get_mut(key))
+            .ok_or_else(|| anyhow::anyhow!("Unknown characteristic '{}'", key))?;
+        characteristic.advances = advances;
+        Ok(())
+    }
+
+    fn find_item_mut(&mut self, id: &str) -> anyhow::Result<&mut Item> {
+        self.items
+            .iter_mut()
+            .find(|item| item._id == id)
+            .ok_or_else(|| anyhow::anyhow!("No item with id '{}'", id))
+    }
+}
+
+impl Item {
+    /// Set `system.<key>.value`
+    pub fn set_system_value(&mut self, key: &str, value: i32) {
+        let entry = self.system.data
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+        if let Some(object) = entry.as_object_mut() {
+            object.insert("value".to_string(), Value::from(value));
+        } else {
+            *entry = serde_json::json!({ "value": value });
+        }
+    }
+}
+
+/// Replace the number at `path`, keeping it a string if the export stores it as one
+fn set_number(json: &mut Value, path: &[PathStep], value: i32) -> anyhow::Result<()> {
+    let mut slot = json;
+    for step in path {
+        let missing = || anyhow::anyhow!("Cannot write '{}': the export has no object there", path_name(path));
+        slot = match step {
+            PathStep::Key(key) => {
+                if slot.is_null() {
+                    *slot = Value::Object(Default::default());
+                }
+                slot.as_object_mut().ok_or_else(missing)?.entry(key.to_string()).or_insert(Value::Null)
+            }
+            PathStep::Index(index) => slot.get_mut(*index).ok_or_else(missing)?,
+        };
+    }
+
+    *slot = match slot {
+        Value::String(_) => Value::String(value.to_string()),
+        _ => Value::from(value),
+    };
+    Ok(())
+}
+
+fn path_name(path: &[PathStep]) -> String {
+    path.iter()
+        .map(|step| match step {
+            PathStep::Key(key) => key.to_string(),
+            PathStep::Index(index) => index.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Byte range of the value at `path` in the JSON text `content`.
+/// Like serde_json, the last of duplicate keys counts.
+fn value_span(content: &str, path: &[PathStep]) -> Option<Range<usize>> {
+    let bytes = content.as_bytes();
+    let mut start = skip_whitespace(bytes, 0);
+    for step in path {
+        start = match (step, bytes.get(start)?) {
+            (PathStep::Key(key), b'{') => member_start(content, start, key)?,
+            (PathStep::Index(index), b'[') => element_start(bytes, start, *index)?,
+            _ => return None,
+        };
+    }
+    Some(start..value_end(bytes, start)?)
+}
+
+/// Start of the value of `key` in the object starting at `open`
+fn member_start(content: &str, open: usize, key: &str) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut found = None;
+    let mut position = skip_whitespace(bytes, open + 1);
+    if bytes.get(position) == Some(&b'}') {
+        return None;
+    }
+    loop {
+        let key_end = value_end(bytes, position)?;
+        let member_key: String = serde_json::from_str(&content[position..key_end]).ok()?;
+        position = skip_whitespace(bytes, key_end);
+        if bytes.get(position) != Some(&b':') {
+            return None;
+        }
+        position = skip_whitespace(bytes, position + 1);
+        if member_key == key {
+            found = Some(position);
+        }
+        position = skip_whitespace(bytes, value_end(bytes, position)?);
+        match bytes.get(position)? {
+            b',' => position = skip_whitespace(bytes, position + 1),
+            b'}' => return found,
+            _ => return None,
+        }
+    }
+}
+
+/// Start of element `index` of the array starting at `open`
+fn element_start(bytes: &[u8], open: usize, index: usize) -> Option<usize> {
+    let mut position = skip_whitespace(bytes, open + 1);
+    if bytes.get(position) == Some(&b']') {
+        return None;
+    }
+    for _ in 0..index {
+        position = skip_whitespace(bytes, value_end(bytes, position)?);
+        if bytes.get(position) != Some(&b',') {
+            return None;
+        }
+        position = skip_whitespace(bytes, position + 1);
+    }
+    Some(position)
+}
+
+/// End of the value (or object key) starting at `start`
+fn value_end(bytes: &[u8], start: usize) -> Option<usize> {
+    match bytes.get(start)? {
+        b'"' => {
+            let mut position = start + 1;
+            loop {
+                match bytes.get(position)? {
+                    b'\\' => position += 2,
+                    b'"' => return Some(position + 1),
+                    _ => position += 1,
+                }
+            }
+        }
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut position = start;
+            loop {
+                match bytes.get(position)? {
+                    b'"' => {
+                        position = value_end(bytes, position)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(position + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                position += 1;
+            }
+        }
+        _ => {
+            let length = bytes[start..]
+                .iter()
+                .position(|byte| matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace())
+                .unwrap_or(bytes.len() - start);
+            Some(start + length)
+        }
+    }
+}
+
+fn skip_whitespace(bytes: &[u8], start: usize) -> usize {
+    start + bytes[start.min(bytes.len())..].iter().take_while(|byte| byte.is_ascii_whitespace()).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pretty-printed like Foundry, with a number in exponent form and an escaped umlaut
+    const EXPORT: &str = r#"{
+  "name": "Alrik",
+  "type": "character",
+  "img": null,
+  "items": [
+    {
+      "_id": "sk1",
+      "name": "K\u00f6rperbeherrschung",
+      "type": "skill",
+      "img": null,
+      "system": {
+        "talentValue": { "value": 4 },
+        "StF": { "value": "D" },
+        "characteristic1": { "value": "gew" },
+        "characteristic2": { "value": "gew" },
+        "characteristic3": { "value": "ko" },
+        "weight": { "value": 1.5e0 }
+      }
+    },
+    {
+      "_id": "adv1",
+      "name": "Glück",
+      "type": "advantage",
+      "img": null,
+      "system": { "APValue": { "value": "30" }, "step": { "value": "1" }, "max": { "value": 3 } }
+    }
+  ],
+  "system": {
+    "characteristics": {
+      "mu": { "initial": 8, "advances": 4 },
+      "kl": { "initial": 8, "advances": 4 }
+    },
+    "details": { "experience": { "total": 1100, "spent": 0 } }
+  }
+}
+"#;
+
+    /// The export with its spent AP set to `spent`
+    fn export_with_spent(spent: i32) -> String {
+        EXPORT.replace("\"spent\": 0", &format!("\"spent\": {}", spent))
+    }
+
+    #[test]
+    fn unmodified_export_is_written_unchanged() {
+        let calculator = ApCalculator::default();
+        let spent = calculator.calculate_total_spent_ap(&Character::from_json(EXPORT).unwrap());
+        let content = export_with_spent(spent);
+        let export = FoundryExport::from_json(&content).unwrap();
+
+        assert_eq!(export.to_json(&export.original, &calculator).unwrap(), content);
+    }
+
+    #[test]
+    fn only_edited_values_differ() {
+        let export = FoundryExport::from_json(EXPORT).unwrap();
+        let calculator = ApCalculator::default();
+        let mut character = export.original.clone();
+        character.set_talent_value("sk1", 7).unwrap();
+        character.set_step("adv1", 2).unwrap();
+        character.set_characteristic_advances("kl", 5).unwrap();
+        assert!(character.set_step("adv1", 4).is_err());
+
+        let output = export.to_json(&character, &calculator).unwrap();
+        let spent = calculator.calculate_total_spent_ap(&character);
+        let expected = export_with_spent(spent)
+            .replace("\"talentValue\": { \"value\": 4 }", "\"talentValue\": { \"value\": 7 }")
+            .replace("\"step\": { \"value\": \"1\" }", "\"step\": { \"value\": \"2\" }")
+            .replace("\"kl\": { \"initial\": 8, \"advances\": 4 }", "\"kl\": { \"initial\": 8, \"advances\": 5 }");
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn value_spans_follow_keys_and_indices() {
+        let content = r#"{ "a": [1, { "b": "x,}" , "c": -2.5e1 }], "a": [3] }"#;
+        let span = |path: &[PathStep]| value_span(content, path).map(|span| &content[span]);
+        assert_eq!(span(&[PathStep::Key("a"), PathStep::Index(0)]), Some("3"));
+        assert_eq!(span(&[PathStep::Key("a"), PathStep::Index(1)]), None);
+        assert_eq!(span(&[PathStep::Key("b")]), None);
+    }
+}
@@ -0,0 +1,335 @@
+use crate::character::combat_gear::{DamageDice, RangeBands, Reach};
+use crate::character::data::ItemSystem;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Foundry item type (`type` of an item). Types this analyzer does not know keep their name in `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ItemKind {
+    Skill,
+    CombatSkill,
+    Spell,
+    Ritual,
+    Liturgy,
+    Ceremony,
+    MagicTrick,
+    Blessing,
+    SpellExtension,
+    Advantage,
+    Disadvantage,
+    SpecialAbility,
+    Species,
+    Culture,
+    Career,
+    MeleeWeapon,
+    RangeWeapon,
+    Ammunition,
+    Armor,
+    Equipment,
+    Consumable,
+    Bag,
+    Money,
+    Other(String),
+}
+
+impl ItemKind {
+    const NAMES: [(ItemKind, &'static str); 23] = [
+        (ItemKind::Skill, "skill"),
+        (ItemKind::CombatSkill, "combatskill"),
+        (ItemKind::Spell, "spell"),
+        (ItemKind::Ritual, "ritual"),
+        (ItemKind::Liturgy, "liturgy"),
+        (ItemKind::Ceremony, "ceremony"),
+        (ItemKind::MagicTrick, "magictrick"),
+        (ItemKind::Blessing, "blessing"),
+        (ItemKind::SpellExtension, "spellextension"),
+        (ItemKind::Advantage, "advantage"),
+        (ItemKind::Disadvantage, "disadvantage"),
+        (ItemKind::SpecialAbility, "specialability"),
+        (ItemKind::Species, "species"),
+        (ItemKind::Culture, "culture"),
+        (ItemKind::Career, "career"),
+        (ItemKind::MeleeWeapon, "meleeweapon"),
+        (ItemKind::RangeWeapon, "rangeweapon"),
+        (ItemKind::Ammunition, "ammunition"),
+        (ItemKind::Armor, "armor"),
+        (ItemKind::Equipment, "equipment"),
+        (ItemKind::Consumable, "consumable"),
+        (ItemKind::Bag, "bag"),
+        (ItemKind::Money, "money"),
+    ];
+
+    pub fn from_type(item_type: &str) -> Self {
+        Self::NAMES
+            .iter()
+            .find(|(_, name)| *name == item_type)
+            .map(|(kind, _)| kind.clone())
+            .unwrap_or_else(|| ItemKind::Other(item_type.to_string()))
+    }
+
+    /// The Foundry type name, e.g. "combatskill"
+    pub fn as_str(&self) -> &str {
+        match self {
+            ItemKind::Other(item_type) => item_type,
+            kind => Self::NAMES
+                .iter()
+                .find(|(known, _)| known == kind)
+                .map(|(_, name)| *name)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Skills, combat techniques, spells, rituals, liturgies and ceremonies: raised via their talent value
+    pub fn has_talent_value(&self) -> bool {
+        matches!(
+            self,
+            ItemKind::Skill | ItemKind::CombatSkill | ItemKind::Spell | ItemKind::Ritual | ItemKind::Liturgy | ItemKind::Ceremony
+        )
+    }
+}
+
+impl From<String> for ItemKind {
+    fn from(item_type: String) -> Self {
+        Self::from_type(&item_type)
+    }
+}
+
+impl From<ItemKind> for String {
+    fn from(kind: ItemKind) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
+impl fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Typed view of the `system` of an item, depending on its kind.
+/// The raw `system` stays available on the item (and is what gets written back on export).
+#[derive(Debug, Clone)]
+pub enum ItemData {
+    /// Skills, spells, rituals, liturgies and ceremonies
+    Talent(TalentSystem),
+    CombatSkill(CombatSkillSystem),
+    /// Advantages, disadvantages, special abilities, magic tricks, blessings, spell extensions and species
+    Purchase(PurchaseSystem),
+    /// Melee and ranged weapons
+    Weapon(WeaponSystem),
+    Armor(ArmorSystem),
+    /// Money, ammunition, equipment and other physical items
+    Equipment(EquipmentSystem),
+    /// Item types without a typed view (only the raw `system` is available)
+    Raw,
+}
+
+impl ItemData {
+    pub fn from_system(kind: &ItemKind, system: &ItemSystem) -> Self {
+        match kind {
+            ItemKind::Skill | ItemKind::Spell | ItemKind::Ritual | ItemKind::Liturgy | ItemKind::Ceremony => {
+                ItemData::Talent(TalentSystem::from_system(system))
+            }
+            ItemKind::CombatSkill => ItemData::CombatSkill(CombatSkillSystem::from_system(system)),
+            ItemKind::Advantage
+            | ItemKind::Disadvantage
+            | ItemKind::SpecialAbility
+            | ItemKind::MagicTrick
+            | ItemKind::Blessing
+            | ItemKind::SpellExtension
+            | ItemKind::Species => ItemData::Purchase(PurchaseSystem::from_system(system)),
+            ItemKind::MeleeWeapon | ItemKind::RangeWeapon => ItemData::Weapon(WeaponSystem::from_system(system)),
+            ItemKind::Armor => ItemData::Armor(ArmorSystem::from_system(system)),
+            ItemKind::Ammunition | ItemKind::Equipment | ItemKind::Consumable | ItemKind::Bag | ItemKind::Money => {
+                ItemData::Equipment(EquipmentSystem::from_system(system))
+            }
+            ItemKind::Culture | ItemKind::Career | ItemKind::Other(_) => ItemData::Raw,
+        }
+    }
+
+    /// Talent value of skills, combat techniques, spells, rituals, liturgies and ceremonies
+    pub fn talent_value(&self) -> Option<i32> {
+        match self {
+            ItemData::Talent(talent) => talent.talent_value,
+            ItemData::CombatSkill(combat_skill) => combat_skill.talent_value,
+            _ => None,
+        }
+    }
+
+    /// Step of advantages, disadvantages, special abilities and the other purchases
+    pub fn step(&self) -> Option<i32> {
+        match self {
+            ItemData::Purchase(purchase) => purchase.step,
+            _ => None,
+        }
+    }
+
+    /// The physical part of weapons, armor and equipment
+    pub fn equipment(&self) -> Option<&EquipmentSystem> {
+        match self {
+            ItemData::Weapon(weapon) => Some(&weapon.equipment),
+            ItemData::Armor(armor) => Some(&armor.equipment),
+            ItemData::Equipment(equipment) => Some(equipment),
+            _ => None,
+        }
+    }
+}
+
+/// Skill, spell, ritual, liturgy or ceremony
+#[derive(Debug, Clone)]
+pub struct TalentSystem {
+    /// `None` if missing or unparseable
+    pub talent_value: Option<i32>,
+    /// Improvement cost column (Steigerungsfaktor, StF)
+    pub improvement_cost: Option<String>,
+    /// The three characteristics of the check, as keys
+    pub characteristics: Option<[String; 3]>,
+    pub group: Option<String>,
+    /// Affected by encumbrance: "yes", "no" or "maybe" (skills only)
+    pub burden: Option<String>,
+}
+
+impl TalentSystem {
+    pub fn from_system(system: &ItemSystem) -> Self {
+        Self {
+            talent_value: system.get_value_as_i32("talentValue"),
+            improvement_cost: system.get_st_f_value(),
+            characteristics: system.get_characteristic_values().map(|(c1, c2, c3)| [c1, c2, c3]),
+            group: system.get_group(),
+            burden: system.get_burden_value(),
+        }
+    }
+}
+
+/// Combat technique
+#[derive(Debug, Clone)]
+pub struct CombatSkillSystem {
+    /// `None` if missing or unparseable
+    pub talent_value: Option<i32>,
+    pub improvement_cost: Option<String>,
+    /// Leading attributes (Leiteigenschaften) as keys, e.g. ["ge", "kk"]
+    pub leading_attributes: Vec<String>,
+    /// From `weapontype` ("range" or "melee"), `None` if the export has none
+    pub ranged: Option<bool>,
+}
+
+impl CombatSkillSystem {
+    pub fn from_system(system: &ItemSystem) -> Self {
+        Self {
+            talent_value: system.get_value_as_i32("talentValue"),
+            improvement_cost: system.get_st_f_value(),
+            leading_attributes: system.get_guidevalue_value().map(|guide| split_attributes(&guide)).unwrap_or_default(),
+            ranged: system.get_weapon_type_value().map(|weapon_type| weapon_type == "range"),
+        }
+    }
+}
+
+/// Bought with a fixed AP value per step (Stufe)
+#[derive(Debug, Clone)]
+pub struct PurchaseSystem {
+    /// Raw APValue, e.g. "10" or "5;10;15" for different costs per step
+    pub ap_value: Option<String>,
+    pub step: Option<i32>,
+    /// Highest step: `max` of advantages and disadvantages, `maxRank` of special abilities
+    pub max_step: Option<i32>,
+}
+
+impl PurchaseSystem {
+    pub fn from_system(system: &ItemSystem) -> Self {
+        Self {
+            ap_value: system.get_ap_value(),
+            step: system.get_value_as_i32("step"),
+            max_step: system.get_value_as_i32("max").or_else(|| system.get_value_as_i32("maxRank")),
+        }
+    }
+}
+
+/// A physical item
+#[derive(Debug, Clone)]
+pub struct EquipmentSystem {
+    /// 1 if missing
+    pub quantity: i32,
+    /// Weight of one unit in Stein
+    pub weight: Option<f64>,
+    /// Raw price of one unit
+    pub price: Option<String>,
+    pub worn: Option<bool>,
+    /// Id of the container the item is stored in
+    pub container_id: Option<String>,
+}
+
+impl EquipmentSystem {
+    pub fn from_system(system: &ItemSystem) -> Self {
+        Self {
+            quantity: system.get_quantity_count(),
+            weight: system.get_weight_stone(),
+            price: system.get_price(),
+            worn: system.is_worn(),
+            container_id: system.get_container_id().map(|id| id.to_string()),
+        }
+    }
+}
+
+/// Melee or ranged weapon
+#[derive(Debug, Clone)]
+pub struct WeaponSystem {
+    pub equipment: EquipmentSystem,
+    pub combat_technique: Option<String>,
+    pub damage: Option<DamageDice>,
+    pub damage_threshold: Option<i32>,
+    /// Leading attributes of the damage bonus, empty if the weapon does not name them
+    pub damage_attributes: Vec<String>,
+    pub attack_modifier: Option<i32>,
+    pub parry_modifier: Option<i32>,
+    /// Melee weapons only
+    pub reach: Option<Reach>,
+    /// Ranged weapons only
+    pub range: Option<RangeBands>,
+    pub ammunition_group: Option<String>,
+}
+
+impl WeaponSystem {
+    pub fn from_system(system: &ItemSystem) -> Self {
+        Self {
+            equipment: EquipmentSystem::from_system(system),
+            combat_technique: system.get_combat_technique(),
+            damage: system.get_damage(),
+            damage_threshold: system.get_damage_threshold(),
+            damage_attributes: system.get_guidevalue_value().map(|guide| split_attributes(&guide)).unwrap_or_default(),
+            attack_modifier: system.get_attack_modifier(),
+            parry_modifier: system.get_parry_modifier(),
+            reach: system.get_reach(),
+            range: system.get_range_bands(),
+            ammunition_group: system.get_ammunition_group(),
+        }
+    }
+}
+
+/// Armor
+#[derive(Debug, Clone)]
+pub struct ArmorSystem {
+    pub equipment: EquipmentSystem,
+    /// Rüstungsschutz (RS)
+    pub protection: Option<i32>,
+    /// Belastung (BE)
+    pub encumbrance: Option<i32>,
+}
+
+impl ArmorSystem {
+    pub fn from_system(system: &ItemSystem) -> Self {
+        Self {
+            equipment: EquipmentSystem::from_system(system),
+            protection: system.get_protection(),
+            encumbrance: system.get_encumbrance(),
+        }
+    }
+}
+
+/// "ge/kk" → ["ge", "kk"]
+fn split_attributes(guide: &str) -> Vec<String> {
+    guide.split('/')
+        .map(|key| key.trim().to_lowercase())
+        .filter(|key| !key.is_empty())
+        .collect()
+}
@@ -0,0 +1,379 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Magic number at the end of every table file
+const TABLE_MAGIC: u64 = 0xdb47_7524_8b80_fb57;
+/// Footer: two block handles padded to 40 bytes, followed by the magic number
+const FOOTER_LEN: usize = 48;
+/// Compression type and CRC after every block
+const BLOCK_TRAILER_LEN: usize = 5;
+/// Log files are written in blocks of 32 KiB; records never span a block header
+const LOG_BLOCK_SIZE: usize = 32 * 1024;
+/// CRC (4), length (2) and record type (1)
+const LOG_HEADER_LEN: usize = 7;
+
+/// Value type of an internal key; everything else is a deletion
+const TYPE_VALUE: u8 = 1;
+
+/// The newest version of a key seen so far; `None` if it was deleted
+struct Entry {
+    sequence: u64,
+    value: Option<Vec<u8>>,
+}
+
+/// Read all live keys and values of the LevelDB store in `dir` (e.g. `data/actors` of a Foundry v11+ world), sorted by key.
+/// Every table (`.ldb`/`.sst`) and write-ahead log (`.log`) of the store is read and the newest version of each key is kept.
+/// Checksums are not verified and the manifest is not consulted: files that are no longer live are only left behind by an
+/// interrupted compaction, and their entries are older anyway.
+pub(crate) fn read_store(dir: &Path) -> anyhow::Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+    if !dir.join("CURRENT").is_file() {
+        anyhow::bail!("{} is not a LevelDB store (no CURRENT file)", dir.display());
+    }
+
+    let mut entries = HashMap::new();
+    for file in std::fs::read_dir(dir)? {
+        let path = file?.path();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        let result = match extension {
+            "ldb" | "sst" => read_table(&std::fs::read(&path)?, &mut entries),
+            "log" => read_log(&std::fs::read(&path)?, &mut entries),
+            _ => continue,
+        };
+        result.map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    }
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(key, entry)| entry.value.map(|value| (key, value)))
+        .collect())
+}
+
+fn insert(entries: &mut HashMap<Vec<u8>, Entry>, key: &[u8], sequence: u64, value: Option<&[u8]>) {
+    match entries.get_mut(key) {
+        Some(entry) if entry.sequence > sequence => {}
+        Some(entry) => {
+            entry.sequence = sequence;
+            entry.value = value.map(<[u8]>::to_vec);
+        }
+        None => {
+            entries.insert(key.to_vec(), Entry { sequence, value: value.map(<[u8]>::to_vec) });
+        }
+    }
+}
+
+/**************************************************
+* Tables
+**************************************************/
+
+/// Offset and size of a block in a table file
+struct BlockHandle {
+    offset: usize,
+    size: usize,
+}
+
+impl BlockHandle {
+    fn read(data: &[u8], pos: &mut usize) -> anyhow::Result<Self> {
+        Ok(Self {
+            offset: read_varint(data, pos)? as usize,
+            size: read_varint(data, pos)? as usize,
+        })
+    }
+}
+
+fn read_table(data: &[u8], entries: &mut HashMap<Vec<u8>, Entry>) -> anyhow::Result<()> {
+    let Some(footer) = data.len().checked_sub(FOOTER_LEN).map(|start| &data[start..]) else {
+        anyhow::bail!("table file is too short");
+    };
+    if read_u64(footer, FOOTER_LEN - 8)? != TABLE_MAGIC {
+        anyhow::bail!("not a table file (bad magic number)");
+    }
+
+    let mut pos = 0;
+    let _metaindex = BlockHandle::read(footer, &mut pos)?;
+    let index = BlockHandle::read(footer, &mut pos)?;
+
+    for (_, handle) in block_entries(&read_block(data, &index)?)? {
+        let block = read_block(data, &BlockHandle::read(&handle, &mut 0)?)?;
+        for (internal_key, value) in block_entries(&block)? {
+            // Internal key: user key followed by (sequence << 8 | type)
+            let Some(split) = internal_key.len().checked_sub(8) else {
+                anyhow::bail!("internal key is too short");
+            };
+            let tag = read_u64(&internal_key, split)?;
+            let value = (tag as u8 == TYPE_VALUE).then_some(value.as_slice());
+            insert(entries, &internal_key[..split], tag >> 8, value);
+        }
+    }
+    Ok(())
+}
+
+/// Contents of a block, decompressed
+fn read_block(data: &[u8], handle: &BlockHandle) -> anyhow::Result<Vec<u8>> {
+    let end = handle.offset.saturating_add(handle.size);
+    if end.saturating_add(BLOCK_TRAILER_LEN) > data.len() {
+        anyhow::bail!("block at {} exceeds the file", handle.offset);
+    }
+
+    let contents = &data[handle.offset..end];
+    match data[end] {
+        0 => Ok(contents.to_vec()),
+        1 => Ok(snap::raw::Decoder::new().decompress_vec(contents)?),
+        other => anyhow::bail!("unsupported block compression {}", other),
+    }
+}
+
+/// Key/value pairs of a block. Keys share a prefix with the previous key; the restart points at the end are not needed
+/// when reading the block sequentially.
+fn block_entries(block: &[u8]) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let Some(count_pos) = block.len().checked_sub(4) else {
+        anyhow::bail!("block is too short");
+    };
+    let restarts = read_u32(block, count_pos)? as usize;
+    let Some(end) = restarts.checked_mul(4).and_then(|len| count_pos.checked_sub(len)) else {
+        anyhow::bail!("block has an invalid restart array");
+    };
+
+    let mut result = Vec::new();
+    let mut key = Vec::new();
+    let mut pos = 0;
+    while pos < end {
+        let shared = read_varint(block, &mut pos)? as usize;
+        let unshared = read_varint(block, &mut pos)? as usize;
+        let value_len = read_varint(block, &mut pos)? as usize;
+        if shared > key.len() {
+            anyhow::bail!("block entry shares more than the previous key");
+        }
+        key.truncate(shared);
+        key.extend_from_slice(read_bytes(block, &mut pos, unshared)?);
+        let value = read_bytes(block, &mut pos, value_len)?;
+        result.push((key.clone(), value.to_vec()));
+    }
+    Ok(result)
+}
+
+/**************************************************
+* Write-ahead logs
+**************************************************/
+
+fn read_log(data: &[u8], entries: &mut HashMap<Vec<u8>, Entry>) -> anyhow::Result<()> {
+    let mut record = Vec::new();
+    let mut pos = 0;
+    while pos + LOG_HEADER_LEN <= data.len() {
+        let block_left = LOG_BLOCK_SIZE - pos % LOG_BLOCK_SIZE;
+        if block_left < LOG_HEADER_LEN {
+            // Trailer of the block, too short for a header
+            pos += block_left;
+            continue;
+        }
+
+        let length = u16::from_le_bytes([data[pos + 4], data[pos + 5]]) as usize;
+        let record_type = data[pos + 6];
+        let start = pos + LOG_HEADER_LEN;
+        let Some(fragment) = data.get(start..start + length) else {
+            // Torn write at the end of the log
+            break;
+        };
+        pos = start + length;
+
+        match record_type {
+            // Preallocated, not yet written space
+            0 => {}
+            // Full record
+            1 => apply_write_batch(fragment, entries)?,
+            // First, middle and last fragment
+            2 => record = fragment.to_vec(),
+            3 => record.extend_from_slice(fragment),
+            4 => {
+                record.extend_from_slice(fragment);
+                apply_write_batch(&record, entries)?;
+                record.clear();
+            }
+            other => anyhow::bail!("unknown log record type {}", other),
+        }
+    }
+    Ok(())
+}
+
+/// A write batch: sequence number of the first operation, operation count, then the puts and deletes
+fn apply_write_batch(batch: &[u8], entries: &mut HashMap<Vec<u8>, Entry>) -> anyhow::Result<()> {
+    let sequence = read_u64(batch, 0)?;
+    let count = read_u32(batch, 8)?;
+    let mut pos = 12;
+    for index in 0..count as u64 {
+        let operation = *read_bytes(batch, &mut pos, 1)?.first().unwrap_or(&0);
+        let key = read_length_prefixed(batch, &mut pos)?;
+        let value = if operation == TYPE_VALUE {
+            Some(read_length_prefixed(batch, &mut pos)?)
+        } else {
+            None
+        };
+        let sequence = sequence.checked_add(index)
+            .ok_or_else(|| anyhow::anyhow!("sequence number overflow in write batch"))?;
+        insert(entries, key, sequence, value);
+    }
+    Ok(())
+}
+
+/**************************************************
+* Encoding
+**************************************************/
+
+fn read_varint(data: &[u8], pos: &mut usize) -> anyhow::Result<u64> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos).ok_or_else(|| anyhow::anyhow!("truncated varint"))?;
+        *pos += 1;
+        result |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    anyhow::bail!("varint is too long")
+}
+
+fn read_bytes<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> anyhow::Result<&'a [u8]> {
+    let bytes = pos.checked_add(len)
+        .and_then(|end| data.get(*pos..end))
+        .ok_or_else(|| anyhow::anyhow!("unexpected end of data"))?;
+    *pos += len;
+    Ok(bytes)
+}
+
+fn read_length_prefixed<'a>(data: &'a [u8], pos: &mut usize) -> anyhow::Result<&'a [u8]> {
+    let len = read_varint(data, pos)? as usize;
+    read_bytes(data, pos, len)
+}
+
+fn read_u32(data: &[u8], pos: usize) -> anyhow::Result<u32> {
+    let bytes = read_bytes(data, &mut { pos }, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into()?))
+}
+
+fn read_u64(data: &[u8], pos: usize) -> anyhow::Result<u64> {
+    let bytes = read_bytes(data, &mut { pos }, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A write batch with the given puts (`Some`) and deletes (`None`); keys and values shorter than 128 bytes
+    fn write_batch(sequence: u64, operations: &[(&str, Option<&str>)]) -> Vec<u8> {
+        let mut batch = sequence.to_le_bytes().to_vec();
+        batch.extend_from_slice(&(operations.len() as u32).to_le_bytes());
+        for (key, value) in operations {
+            batch.push(if value.is_some() { TYPE_VALUE } else { 0 });
+            for bytes in std::iter::once(key).chain(value.as_ref()) {
+                batch.push(bytes.len() as u8);
+                batch.extend_from_slice(bytes.as_bytes());
+            }
+        }
+        batch
+    }
+
+    /// A log record with a zero checksum, which is not verified
+    fn log_record(record_type: u8, fragment: &[u8]) -> Vec<u8> {
+        let mut record = vec![0; 4];
+        record.extend_from_slice(&(fragment.len() as u16).to_le_bytes());
+        record.push(record_type);
+        record.extend_from_slice(fragment);
+        record
+    }
+
+    fn value_of<'a>(entries: &'a HashMap<Vec<u8>, Entry>, key: &str) -> Option<&'a [u8]> {
+        entries.get(key.as_bytes()).and_then(|entry| entry.value.as_deref())
+    }
+
+    #[test]
+    fn read_varint_decodes_single_and_multi_byte_values() {
+        let data = [0x05, 0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0x0f];
+        let mut pos = 0;
+        assert_eq!(read_varint(&data, &mut pos).unwrap(), 5);
+        assert_eq!(read_varint(&data, &mut pos).unwrap(), 300);
+        assert_eq!(read_varint(&data, &mut pos).unwrap(), u64::from(u32::MAX));
+        assert_eq!(pos, data.len());
+    }
+
+    #[test]
+    fn read_varint_rejects_truncated_and_overlong_values() {
+        assert!(read_varint(&[0x80, 0x80], &mut 0).is_err());
+        assert!(read_varint(&[0x80; 11], &mut 0).is_err());
+    }
+
+    #[test]
+    fn block_entries_restore_shared_key_prefixes() {
+        let mut block = Vec::new();
+        // "!actors!a1" in full, then "!actors!a2" sharing 9 bytes, then "!actors!b" sharing 8 bytes
+        for (shared, unshared, value) in [(0, "!actors!a1", "1"), (9, "2", "22"), (8, "b", "")] {
+            block.extend_from_slice(&[shared, unshared.len() as u8, value.len() as u8]);
+            block.extend_from_slice(unshared.as_bytes());
+            block.extend_from_slice(value.as_bytes());
+        }
+        block.extend_from_slice(&0u32.to_le_bytes());
+        block.extend_from_slice(&1u32.to_le_bytes());
+
+        let entries = block_entries(&block).unwrap();
+        let keys: Vec<&[u8]> = entries.iter().map(|(key, _)| key.as_slice()).collect();
+        assert_eq!(keys, [b"!actors!a1".as_slice(), b"!actors!a2", b"!actors!b"]);
+        assert_eq!(entries[1].1, b"22");
+        assert!(entries[2].1.is_empty());
+    }
+
+    #[test]
+    fn block_entries_reject_a_prefix_longer_than_the_previous_key() {
+        let mut block = vec![3, 1, 0, b'a'];
+        block.extend_from_slice(&0u32.to_le_bytes());
+        block.extend_from_slice(&1u32.to_le_bytes());
+        assert!(block_entries(&block).is_err());
+    }
+
+    #[test]
+    fn read_log_joins_fragmented_records() {
+        let batch = write_batch(1, &[("!actors!a1", Some("{\"name\":\"Alrik\"}")), ("!actors!a2", Some("{}"))]);
+        let (first, rest) = batch.split_at(10);
+        let (middle, last) = rest.split_at(10);
+
+        let mut log = log_record(2, first);
+        log.extend(log_record(3, middle));
+        log.extend(log_record(4, last));
+        log.extend(log_record(1, &write_batch(3, &[("!actors!a3", Some("[]"))])));
+
+        let mut entries = HashMap::new();
+        read_log(&log, &mut entries).unwrap();
+        assert_eq!(value_of(&entries, "!actors!a1"), Some(b"{\"name\":\"Alrik\"}".as_slice()));
+        assert_eq!(value_of(&entries, "!actors!a2"), Some(b"{}".as_slice()));
+        assert_eq!(value_of(&entries, "!actors!a3"), Some(b"[]".as_slice()));
+    }
+
+    #[test]
+    fn read_log_skips_the_trailer_of_a_block() {
+        let filler = vec![0; LOG_BLOCK_SIZE - LOG_HEADER_LEN - 3];
+        let mut log = log_record(0, &filler);
+        log.extend_from_slice(&[0; 3]);
+        log.extend(log_record(1, &write_batch(1, &[("key", Some("value"))])));
+
+        let mut entries = HashMap::new();
+        read_log(&log, &mut entries).unwrap();
+        assert_eq!(value_of(&entries, "key"), Some(b"value".as_slice()));
+    }
+
+    #[test]
+    fn apply_write_batch_keeps_the_last_operation_of_a_key() {
+        let mut entries = HashMap::new();
+        apply_write_batch(&write_batch(10, &[("deleted", Some("old")), ("deleted", None), ("kept", None), ("kept", Some("new"))]), &mut entries).unwrap();
+        assert_eq!(value_of(&entries, "deleted"), None);
+        assert_eq!(value_of(&entries, "kept"), Some(b"new".as_slice()));
+
+        // An older batch, e.g. from a log read later, does not overwrite newer versions
+        apply_write_batch(&write_batch(5, &[("deleted", Some("older")), ("kept", Some("older"))]), &mut entries).unwrap();
+        assert_eq!(value_of(&entries, "deleted"), None);
+        assert_eq!(value_of(&entries, "kept"), Some(b"new".as_slice()));
+    }
+
+    #[test]
+    fn apply_write_batch_rejects_an_overflowing_sequence() {
+        let batch = write_batch(u64::MAX, &[("a", Some("1")), ("b", Some("2"))]);
+        assert!(apply_write_batch(&batch, &mut HashMap::new()).is_err());
+    }
+}
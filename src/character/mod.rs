@@ -1,5 +1,46 @@
 pub mod analysis;
+pub mod combat;
+pub mod combat_gear;
+pub mod compendium;
+pub mod cross_check;
 pub mod data;
+pub mod derived;
+pub mod diff;
+pub mod encumbrance;
+pub mod experience_level;
+pub mod export;
+pub mod item_kind;
+mod leveldb;
+pub mod optolith;
+pub mod party;
+pub mod planner;
+pub mod report;
+pub mod rules;
+pub mod skill_check;
+pub mod species;
+pub mod validation;
+pub mod wealth;
+pub mod world;
 
 pub use analysis::*;
-pub use data::*;
\ No newline at end of file
+pub use combat::*;
+pub use combat_gear::*;
+pub use compendium::*;
+pub use cross_check::*;
+pub use data::*;
+pub use derived::*;
+pub use diff::*;
+pub use encumbrance::*;
+pub use experience_level::*;
+pub use export::*;
+pub use item_kind::*;
+pub use optolith::*;
+pub use party::*;
+pub use planner::*;
+pub use report::*;
+pub use rules::*;
+pub use skill_check::*;
+pub use species::*;
+pub use validation::*;
+pub use wealth::*;
+pub use world::*;
\ No newline at end of file
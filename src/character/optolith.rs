@@ -0,0 +1,768 @@
+use crate::character::{Character, Characteristics, DerivedValueKind, DerivedValues, OptolithEntry, OptolithRules, Rules};
+use serde_json::{json, Value};
+use std::collections::btree_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Locale of the names read from Optolith's data files, unless the rules set `optolith.data_locale`
+const DEFAULT_DATA_LOCALE: &str = "de-DE";
+
+/// What the import takes from an Optolith data file
+#[derive(Debug, Clone, Copy)]
+enum DataCategory {
+    /// Spells and liturgies: StF and check
+    Talent,
+    /// Advantages, disadvantages and special abilities: AP value per level
+    Activatable { disadvantage: bool },
+    /// Magic tricks and blessings cost a flat amount, only their name is needed
+    NameOnly,
+}
+
+/// Optolith data files read by `OptolithRules::load_data`, with the prefix of the ids heroes store
+const DATA_FILES: [(&str, &str, DataCategory); 7] = [
+    ("Spells.yaml", "SPELL_", DataCategory::Talent),
+    ("LiturgicalChants.yaml", "LITURGY_", DataCategory::Talent),
+    ("Cantrips.yaml", "CANTRIP_", DataCategory::NameOnly),
+    ("Blessings.yaml", "BLESSING_", DataCategory::NameOnly),
+    ("Advantages.yaml", "ADV_", DataCategory::Activatable { disadvantage: false }),
+    ("Disadvantages.yaml", "DISADV_", DataCategory::Activatable { disadvantage: true }),
+    ("SpecialAbilities.yaml", "SA_", DataCategory::Activatable { disadvantage: false }),
+];
+
+/// Format of a character file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharacterFormat {
+    /// Foundry VTT actor export
+    Foundry,
+    /// Hero saved or exported by Optolith
+    Optolith,
+}
+
+impl CharacterFormat {
+    /// Detect the format from the content of a character file
+    pub fn detect(content: &str) -> anyhow::Result<Self> {
+        let json: Value = serde_json::from_str(content)?;
+        Self::detect_json(&json)
+            .ok_or_else(|| anyhow::anyhow!("Neither a Foundry actor export nor an Optolith hero"))
+    }
+
+    pub fn detect_json(json: &Value) -> Option<Self> {
+        if json.get("clientVersion").is_some() || (json.get("attr").is_some() && json.get("activatable").is_some()) {
+            Some(CharacterFormat::Optolith)
+        } else if json.get("items").is_some_and(Value::is_array) {
+            Some(CharacterFormat::Foundry)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharacterFormat::Foundry => "Foundry VTT",
+            CharacterFormat::Optolith => "Optolith",
+        }
+    }
+}
+
+/// An Optolith hero mapped onto the Foundry data model.
+/// Optolith stores ids only; names, StF and checks come from the `optolith` tables of the rules.
+/// The built-in rules map species, skills and combat techniques only: spells, liturgies, advantages,
+/// disadvantages and special abilities need `optolith.entries` or Optolith's data files (`optolith.data_dir`)
+/// from a rules file to be costed.
+/// Everything that cannot be mapped is imported as a placeholder named by its id and reported in `warnings`.
+#[derive(Debug, Clone)]
+pub struct OptolithImport {
+    pub character: Character,
+    pub warnings: Vec<String>,
+}
+
+impl OptolithImport {
+    pub fn from_file(path: &Path, rules: &Rules) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content, rules)
+    }
+
+    pub fn from_json(content: &str, rules: &Rules) -> anyhow::Result<Self> {
+        let hero: Value = serde_json::from_str(content)?;
+        if CharacterFormat::detect_json(&hero) != Some(CharacterFormat::Optolith) {
+            anyhow::bail!("Not an Optolith hero");
+        }
+
+        let mut warnings = Vec::new();
+        let mut items = Vec::new();
+        let species_name = species_name(&hero, &mut warnings, rules);
+        items.extend(skill_items(&hero, &mut warnings, rules));
+        items.extend(combat_technique_items(&hero, &mut warnings, rules));
+        items.extend(spell_items(&hero, &mut warnings, rules));
+        items.extend(activatable_items(&hero, &mut warnings, rules));
+        items.extend(belonging_items(&hero, rules));
+
+        let avatar = hero.get("avatar").and_then(Value::as_str).filter(|url| url.starts_with("http"));
+        let total_ap = hero.pointer("/ap/total").and_then(Value::as_i64).unwrap_or(0);
+
+        let actor = json!({
+            "name": hero.get("name").and_then(Value::as_str).unwrap_or("Unnamed hero"),
+            "type": "character",
+            "img": avatar,
+            "items": items,
+            "system": {
+                "characteristics": characteristics(&hero, rules),
+                "status": status(&hero),
+                "details": {
+                    // Optolith does not store the spent AP
+                    "experience": { "total": total_ap },
+                    "species": { "value": species_name.unwrap_or_default() },
+                },
+            },
+        });
+
+        let mut character: Character = serde_json::from_value(actor)?;
+        Self::fill_current_energy(&mut character, rules);
+        Ok(Self { character, warnings })
+    }
+
+    /// Optolith stores neither the current LeP/AsP/KaP nor their maximum. The hero starts at full energy,
+    /// so the current values are the calculated maxima; the maxima themselves and the spent AP stay
+    /// unset so that the checks report them as not recorded instead of agreeing with themselves.
+    fn fill_current_energy(character: &mut Character, rules: &Rules) {
+        let derived = DerivedValues::calculate(character, rules);
+        let calculated = |kind| {
+            derived.get(kind)
+                .and_then(|check| check.calculated.as_ref())
+                .map(|value| value.value)
+                .unwrap_or(0)
+        };
+        let (life_points, astral_energy, karma_energy) = (
+            calculated(DerivedValueKind::LifePoints),
+            calculated(DerivedValueKind::AstralEnergy),
+            calculated(DerivedValueKind::KarmaEnergy),
+        );
+
+        let Some(status) = character.system.as_mut().and_then(|system| system.status.as_mut()) else {
+            return;
+        };
+        if let Some(wounds) = status.wounds.as_mut() {
+            (wounds.value, wounds.current) = (life_points, life_points);
+        }
+        if let Some(astral) = status.astralenergy.as_mut() {
+            (astral.value, astral.current) = (astral_energy, astral_energy);
+        }
+        if let Some(karma) = status.karmaenergy.as_mut() {
+            (karma.value, karma.current) = (karma_energy, karma_energy);
+        }
+    }
+}
+
+/// "ATTR_1" … "ATTR_8" → "mu" … "kk"
+fn attribute_key(id: &str) -> Option<&'static str> {
+    let index: usize = id.strip_prefix("ATTR_")?.parse().ok()?;
+    Characteristics::NAMES.get(index.checked_sub(1)?).map(|(key, _)| *key)
+}
+
+/// Optolith ids are kept as item ids; entries with several instances get their index appended
+fn item_id(id: &str, index: usize) -> String {
+    if index == 0 { id.to_string() } else { format!("{}-{}", id, index) }
+}
+
+fn as_i32(value: Option<&Value>) -> Option<i32> {
+    value.and_then(|v| match v {
+        Value::Number(n) => n.as_f64().map(|n| n as i32),
+        Value::String(s) => s.trim().parse().ok(),
+        // Two-handed thresholds and similar pairs: the first value applies
+        Value::Array(values) => as_i32(values.first()),
+        _ => None,
+    })
+}
+
+fn species_name(hero: &Value, warnings: &mut Vec<String>, rules: &Rules) -> Option<String> {
+    let id = hero.get("r").and_then(Value::as_str)?;
+    match rules.optolith.species.get(id) {
+        Some(species) => Some(species.name.clone()),
+        None => {
+            warnings.push(format!("Unknown species '{}': species AP cost and modifiers are not included", id));
+            None
+        }
+    }
+}
+
+/// Optolith stores the bought value (starting at 8); the species modifiers are added on top
+fn characteristics(hero: &Value, rules: &Rules) -> Value {
+    let base_value = rules.characteristic.base_value;
+    let species = hero.get("r").and_then(Value::as_str).and_then(|id| rules.optolith.species.get(id));
+    let selected = hero.pointer("/attr/attributeAdjustmentSelected").and_then(Value::as_str).and_then(attribute_key);
+
+    // Current versions store {"id", "value"} objects, older ones ["ATTR_1", value] pairs
+    let values: Vec<(&'static str, i32)> = hero.pointer("/attr/values")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let (id, value) = match entry {
+                Value::Array(pair) => (pair.first()?, pair.get(1)),
+                _ => (entry.get("id")?, entry.get("value")),
+            };
+            Some((attribute_key(id.as_str()?)?, as_i32(value)?))
+        })
+        .collect();
+
+    let characteristics: serde_json::Map<String, Value> = Characteristics::NAMES
+        .iter()
+        .map(|(key, _)| {
+            let value = values.iter().find(|(k, _)| k == key).map(|(_, v)| *v).unwrap_or(base_value);
+            let modifier = species.and_then(|s| s.attribute_adjustments.get(*key)).copied().unwrap_or(0)
+                + if selected == Some(key) { 1 } else { 0 };
+            (key.to_string(), json!({ "initial": base_value, "species": modifier, "modifier": 0, "advances": value - base_value }))
+        })
+        .collect();
+    Value::Object(characteristics)
+}
+
+/// Bought LeP/AsP/KaP and permanently lost and bought back points; the maximums are calculated after the import
+fn status(hero: &Value) -> Value {
+    let attr = hero.get("attr");
+    let bought = |key: &str| as_i32(attr.and_then(|a| a.get(key))).unwrap_or(0);
+    let permanent = |key: &str, field: &str| as_i32(attr.and_then(|a| a.get(key)).and_then(|p| p.get(field))).unwrap_or(0);
+    let energy = |advances: i32, lost: i32, redeemed: i32| json!({
+        "initial": 0, "value": 0, "advances": advances, "modifier": 0, "current": 0,
+        "permanentLoss": lost, "rebuy": redeemed,
+    });
+
+    json!({
+        "wounds": { "initial": 0, "value": 0, "advances": bought("lp"), "modifier": 0, "current": 0 },
+        "astralenergy": energy(bought("ae"), permanent("permanentAE", "lost"), permanent("permanentAE", "redeemed")),
+        "karmaenergy": energy(bought("kp"), permanent("permanentKP", "lost"), permanent("permanentKP", "redeemed")),
+    })
+}
+
+/// Placeholder for an id without table entry: only its value is known
+fn placeholder(id: &str, item_type: &str, system: Value) -> Value {
+    json!({ "_id": id, "name": id, "type": item_type, "img": null, "system": system })
+}
+
+/// An item named by its table entry, or a placeholder named by its Optolith id
+fn entry_item(item_id: &str, id: &str, entry: Option<&OptolithEntry>, item_type: &str, system: Value) -> Value {
+    json!({
+        "_id": item_id,
+        "name": entry.map(|e| e.name.as_str()).unwrap_or(id),
+        "type": entry.and_then(|e| e.item_type.as_deref()).unwrap_or(item_type),
+        "img": null,
+        "system": system,
+    })
+}
+
+/// All skills of the table (Foundry lists unraised skills as well), then unknown ids as placeholders
+fn skill_items(hero: &Value, warnings: &mut Vec<String>, rules: &Rules) -> Vec<Value> {
+    let talents = hero.get("talents").and_then(Value::as_object);
+    let value_of = |id: &str| as_i32(talents.and_then(|t| t.get(id))).unwrap_or(0);
+
+    let mut items: Vec<Value> = rules.optolith.skills
+        .iter()
+        .map(|(id, skill)| json!({
+            "_id": id,
+            "name": skill.name,
+            "type": "skill",
+            "img": null,
+            "system": {
+                "talentValue": { "value": value_of(id) },
+                "StF": { "value": skill.improvement_cost },
+                "characteristic1": { "value": skill.check[0] },
+                "characteristic2": { "value": skill.check[1] },
+                "characteristic3": { "value": skill.check[2] },
+                "group": { "value": skill.group },
+                "burden": { "value": skill.burden },
+            },
+        }))
+        .collect();
+
+    for id in talents.into_iter().flat_map(|t| t.keys()).filter(|id| !rules.optolith.skills.contains_key(*id)) {
+        warnings.push(format!("Unknown skill '{}': imported without StF, not included in the AP calculation", id));
+        items.push(placeholder(id, "skill", json!({ "talentValue": { "value": value_of(id) } })));
+    }
+    items
+}
+
+/// All combat techniques of the table; Optolith only stores those raised above the free starting value
+fn combat_technique_items(hero: &Value, warnings: &mut Vec<String>, rules: &Rules) -> Vec<Value> {
+    let techniques = hero.get("ct").and_then(Value::as_object);
+    let value_of = |id: &str| as_i32(techniques.and_then(|t| t.get(id))).unwrap_or(rules.combat_technique_free_value);
+
+    let mut items: Vec<Value> = rules.optolith.combat_techniques
+        .iter()
+        .map(|(id, technique)| json!({
+            "_id": id,
+            "name": technique.name,
+            "type": "combatskill",
+            "img": null,
+            "system": {
+                "talentValue": { "value": value_of(id) },
+                "StF": { "value": technique.improvement_cost },
+                "guidevalue": { "value": technique.leading_attributes },
+                "weapontype": { "value": if technique.ranged { "range" } else { "melee" } },
+            },
+        }))
+        .collect();
+
+    for id in techniques.into_iter().flat_map(|t| t.keys()).filter(|id| !rules.optolith.combat_techniques.contains_key(*id)) {
+        warnings.push(format!("Unknown combat technique '{}': imported without StF, not included in the AP calculation", id));
+        items.push(placeholder(id, "combatskill", json!({ "talentValue": { "value": value_of(id) } })));
+    }
+    items
+}
+
+/// Spells and rituals, liturgies and ceremonies (with values), magic tricks and blessings (lists of ids)
+fn spell_items(hero: &Value, warnings: &mut Vec<String>, rules: &Rules) -> Vec<Value> {
+    let mut items = Vec::new();
+    let mut without_cost = Vec::new();
+
+    for (key, item_type) in [("spells", "spell"), ("liturgies", "liturgy")] {
+        for (id, value) in hero.get(key).and_then(Value::as_object).into_iter().flatten() {
+            let entry = rules.optolith.entries.get(id);
+            let mut system = json!({ "talentValue": { "value": as_i32(Some(value)).unwrap_or(0) } });
+            match entry.and_then(|e| e.improvement_cost.as_ref()) {
+                Some(stf) => system["StF"] = json!({ "value": stf }),
+                None => without_cost.push(id.as_str()),
+            }
+            if let Some(check) = entry.and_then(|e| e.check.as_ref()) {
+                for (index, key) in check.iter().enumerate() {
+                    system[format!("characteristic{}", index + 1)] = json!({ "value": key });
+                }
+            }
+            items.push(entry_item(id, id, entry, item_type, system));
+        }
+    }
+
+    for (key, item_type) in [("cantrips", "magictrick"), ("blessings", "blessing")] {
+        for id in hero.get(key).and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            items.push(entry_item(id, id, rules.optolith.entries.get(id), item_type, json!({})));
+        }
+    }
+
+    if !without_cost.is_empty() {
+        warnings.push(format!(
+            "No StF for {} in the Optolith entries of the rules: not included in the AP calculation, the calculated spent AP is incomplete",
+            without_cost.join(", ")
+        ));
+    }
+    items
+}
+
+/// Advantages, disadvantages and special abilities; every active instance becomes an item with its step (tier)
+fn activatable_items(hero: &Value, warnings: &mut Vec<String>, rules: &Rules) -> Vec<Value> {
+    let mut items = Vec::new();
+    let mut without_ap_value = Vec::new();
+
+    for (id, instances) in hero.get("activatable").and_then(Value::as_object).into_iter().flatten() {
+        let item_type = if id.starts_with("ADV_") {
+            "advantage"
+        } else if id.starts_with("DISADV_") {
+            "disadvantage"
+        } else if id.starts_with("SA_") {
+            "specialability"
+        } else {
+            warnings.push(format!("Unknown entry '{}' skipped", id));
+            continue;
+        };
+        let entry = rules.optolith.entries.get(id);
+
+        for (index, instance) in instances.as_array().into_iter().flatten().enumerate() {
+            let mut system = json!({ "step": { "value": as_i32(instance.get("tier")).unwrap_or(1) } });
+            // A custom cost set in Optolith wins over the table
+            let custom_cost = as_i32(instance.get("cost"))
+                .map(|cost| if item_type == "disadvantage" { -cost.abs() } else { cost }.to_string());
+            match custom_cost.or_else(|| entry.and_then(|e| e.ap_value.clone())) {
+                Some(ap_value) => system["APValue"] = json!({ "value": ap_value }),
+                None => without_ap_value.push(id.as_str()),
+            }
+
+            let mut item = entry_item(&item_id(id, index), id, entry, item_type, system);
+            // The selected option, e.g. the skill of a Begabung
+            if let Some(option) = instance.get("sid").filter(|sid| !sid.is_null()) {
+                let option = match option.as_str() {
+                    Some(skill_id) if rules.optolith.skills.contains_key(skill_id) => rules.optolith.skills[skill_id].name.clone(),
+                    Some(text) => text.to_string(),
+                    None => option.to_string(),
+                };
+                let name = format!("{} ({})", item["name"].as_str().unwrap_or(id), option);
+                item["name"] = json!(name);
+            }
+            items.push(item);
+        }
+    }
+
+    if !without_ap_value.is_empty() {
+        without_ap_value.dedup();
+        warnings.push(format!(
+            "No AP value for {} in the Optolith entries of the rules: not included in the AP calculation, the calculated spent AP is incomplete",
+            without_ap_value.join(", ")
+        ));
+    }
+    items
+}
+
+/// Equipment with its weapon and armor stats, and the coins of the purse
+fn belonging_items(hero: &Value, rules: &Rules) -> Vec<Value> {
+    let belongings = hero.get("belongings");
+    let mut items = Vec::new();
+
+    for (id, item) in belongings.and_then(|b| b.get("items")).and_then(Value::as_object).into_iter().flatten() {
+        // Item groups of Optolith: 1 melee weapons, 2 ranged weapons, 3 ammunition, 4 armor
+        let group = as_i32(item.get("gr")).unwrap_or(0);
+        let item_type = match group {
+            1 => "meleeweapon",
+            2 => "rangeweapon",
+            3 => "ammunition",
+            4 => "armor",
+            _ => "equipment",
+        };
+        let number = |key: &str| item.get(key).filter(|v| v.is_number()).cloned();
+
+        let mut system = json!({
+            "quantity": { "value": as_i32(item.get("amount")).unwrap_or(1) },
+            "weight": { "value": number("weight") },
+            "price": { "value": number("price") },
+        });
+
+        if group == 1 || group == 2 {
+            let technique = item.get("combatTechnique").and_then(Value::as_str);
+            if let Some(technique) = technique.and_then(|id| rules.optolith.combat_techniques.get(id)) {
+                system["combatskill"] = json!({ "value": technique.name });
+            }
+            if let (Some(dice), Some(sides)) = (as_i32(item.get("damageDiceNumber")), as_i32(item.get("damageDiceSides"))) {
+                let flat = as_i32(item.get("damageFlat")).unwrap_or(0);
+                let damage = if flat == 0 { format!("{}W{}", dice, sides) } else { format!("{}W{}{:+}", dice, sides, flat) };
+                system["damage"] = json!({ "value": damage });
+            }
+            let damage_bonus = item.get("damageBonus");
+            if let Some(threshold) = as_i32(damage_bonus.and_then(|b| b.get("threshold"))) {
+                system["damageThreshold"] = json!({ "value": threshold });
+            }
+            let primary: Vec<&str> = match damage_bonus.and_then(|b| b.get("primary")) {
+                Some(Value::String(id)) => vec![id.as_str()],
+                Some(Value::Array(ids)) => ids.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            let guide: Vec<&str> = primary.into_iter().filter_map(attribute_key).collect();
+            if !guide.is_empty() {
+                system["guidevalue"] = json!({ "value": guide.join("/") });
+            }
+        }
+
+        if group == 1 {
+            system["atmod"] = json!({ "value": as_i32(item.get("at")).unwrap_or(0) });
+            system["pamod"] = json!({ "value": as_i32(item.get("pa")).unwrap_or(0) });
+            let reach = match as_i32(item.get("reach")) {
+                Some(1) => Some("short"),
+                Some(2) => Some("medium"),
+                Some(3) => Some("long"),
+                _ => None,
+            };
+            if let Some(reach) = reach {
+                system["reach"] = json!({ "value": reach });
+            }
+        } else if group == 2 {
+            let bands: Vec<String> = item.get("range").and_then(Value::as_array).into_iter().flatten()
+                .filter_map(|band| as_i32(Some(band)))
+                .map(|band| band.to_string())
+                .collect();
+            if bands.len() == 3 {
+                system["reach"] = json!({ "value": bands.join("/") });
+            }
+        } else if group == 4 {
+            system["protection"] = json!({ "value": as_i32(item.get("pro")).unwrap_or(0) });
+            system["encumbrance"] = json!({ "value": as_i32(item.get("enc")).unwrap_or(0) });
+        }
+
+        let name = item.get("name").and_then(Value::as_str).unwrap_or(id);
+        items.push(json!({ "_id": id, "name": name, "type": item_type, "img": null, "system": system }));
+    }
+
+    // The purse stores the number of coins per currency as strings: {"d": "1", "s": "12", ...}
+    let purse = belongings.and_then(|b| b.get("purse"));
+    for currency in &rules.wealth.currencies {
+        let count = as_i32(purse.and_then(|p| p.get(currency.key.to_lowercase()))).unwrap_or(0);
+        if count > 0 {
+            items.push(json!({
+                "_id": format!("purse-{}", currency.key.to_lowercase()),
+                "name": currency.name,
+                "type": "money",
+                "img": null,
+                "system": { "quantity": { "value": count } },
+            }));
+        }
+    }
+    items
+}
+
+impl OptolithRules {
+    /// Add the spells, liturgies, advantages, disadvantages and special abilities of Optolith's data files in `dir`:
+    /// ids, StF, checks and AP values from `univ/`, names from the locale directory. Ids already in `entries`
+    /// keep their entry; missing files are skipped. Returns the number of added entries.
+    pub fn load_data(&mut self, dir: &Path) -> anyhow::Result<usize> {
+        if !dir.join("univ").is_dir() {
+            anyhow::bail!("{} contains no univ directory of Optolith data files", dir.display());
+        }
+        let locale = self.data_locale.clone().unwrap_or_else(|| DEFAULT_DATA_LOCALE.to_string());
+        let read = |subdir: &str, file: &str| -> anyhow::Result<Option<String>> {
+            let path = dir.join(subdir).join(file);
+            if !path.is_file() {
+                return Ok(None);
+            }
+            std::fs::read_to_string(&path)
+                .map(Some)
+                .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+        };
+
+        let mut added = 0;
+        for (file, prefix, category) in DATA_FILES {
+            let Some(univ) = read("univ", file)? else {
+                continue;
+            };
+            let l10n = read(&locale, file)?;
+            added += self.add_data(&univ, l10n.as_deref(), prefix, category)
+                .map_err(|e| anyhow::anyhow!("{}: {}", file, e))?;
+        }
+        Ok(added)
+    }
+
+    /// Add the entries of one data file; the l10n file contains the names (entries without one are named by their id)
+    fn add_data(&mut self, univ: &str, l10n: Option<&str>, prefix: &str, category: DataCategory) -> anyhow::Result<usize> {
+        let univ: Vec<serde_yaml::Value> = serde_yaml::from_str(univ)?;
+        let names: HashMap<String, String> = match l10n {
+            Some(l10n) => serde_yaml::from_str::<Vec<serde_yaml::Value>>(l10n)?
+                .iter()
+                .filter_map(|entry| Some((data_id(entry, prefix)?, entry.get("name")?.as_str()?.to_string())))
+                .collect(),
+            None => HashMap::new(),
+        };
+
+        let mut added = 0;
+        for data in &univ {
+            let Some(id) = data_id(data, prefix) else {
+                continue;
+            };
+            let Entry::Vacant(slot) = self.entries.entry(id) else {
+                continue;
+            };
+            let mut entry = OptolithEntry {
+                name: names.get(slot.key()).cloned().unwrap_or_else(|| slot.key().clone()),
+                item_type: None,
+                ap_value: None,
+                improvement_cost: None,
+                check: None,
+            };
+            match category {
+                DataCategory::Talent => {
+                    entry.improvement_cost = data.get("ic").and_then(data_improvement_cost);
+                    entry.check = data_check(data);
+                }
+                DataCategory::Activatable { disadvantage } => entry.ap_value = data_ap_value(data, disadvantage),
+                DataCategory::NameOnly => {}
+            }
+            slot.insert(entry);
+            added += 1;
+        }
+        Ok(added)
+    }
+}
+
+/// The id heroes store: data files may contain the full id ("SPELL_12") or only its number (12)
+fn data_id(data: &serde_yaml::Value, prefix: &str) -> Option<String> {
+    match data.get("id")? {
+        serde_yaml::Value::Number(number) => Some(format!("{}{}", prefix, number.as_u64()?)),
+        serde_yaml::Value::String(id) if id.starts_with(prefix) => Some(id.clone()),
+        serde_yaml::Value::String(id) => Some(format!("{}{}", prefix, id)),
+        _ => None,
+    }
+}
+
+/// StF given as column number (1 = A) or letter
+fn data_improvement_cost(ic: &serde_yaml::Value) -> Option<String> {
+    match ic {
+        serde_yaml::Value::Number(number) => match number.as_u64()? {
+            column @ 1..=26 => Some(char::from(b'A' + column as u8 - 1).to_string()),
+            _ => None,
+        },
+        serde_yaml::Value::String(column) => Some(column.trim().to_uppercase()),
+        _ => None,
+    }
+}
+
+/// Characteristic keys of the check, from `check1`–`check3` or a `check` list of attribute numbers or ids
+fn data_check(data: &serde_yaml::Value) -> Option<[String; 3]> {
+    let values: Vec<&serde_yaml::Value> = match data.get("check").and_then(serde_yaml::Value::as_sequence) {
+        Some(check) => check.iter().collect(),
+        None => ["check1", "check2", "check3"].into_iter().filter_map(|key| data.get(key)).collect(),
+    };
+    let keys: Vec<String> = values
+        .into_iter()
+        .filter_map(|value| match value {
+            serde_yaml::Value::Number(number) => attribute_key(&format!("ATTR_{}", number.as_u64()?)),
+            serde_yaml::Value::String(id) => attribute_key(id),
+            _ => None,
+        })
+        .map(str::to_string)
+        .collect();
+    keys.try_into().ok()
+}
+
+/// APValue in the Foundry format: one cost per level, or a list of the costs of the levels ("5;10;15").
+/// A list without levels depends on the selected option and cannot be costed from the table.
+fn data_ap_value(data: &serde_yaml::Value, disadvantage: bool) -> Option<String> {
+    let signed = |cost: i64| if disadvantage { -cost.abs() } else { cost };
+    match data.get("cost")? {
+        serde_yaml::Value::Number(cost) => cost.as_i64().map(|cost| signed(cost).to_string()),
+        serde_yaml::Value::Sequence(costs) if data.get("levels").is_some() => costs
+            .iter()
+            .map(|cost| cost.as_i64().map(|cost| signed(cost).to_string()))
+            .collect::<Option<Vec<String>>>()
+            .map(|costs| costs.join(";")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::{category, ApCalculator};
+
+    const SPELLS: &str = "
+- id: 1
+  check1: 2
+  check2: 3
+  check3: 4
+  ic: 2
+- id: 2
+  check1: 1
+  check2: 2
+  check3: 6
+  ic: 3
+";
+    const SPELL_NAMES: &str = "
+- id: 1
+  name: Adlerauge
+- id: 2
+  name: Armatrutz
+";
+    const LITURGIES: &str = "
+- id: 1
+  check1: 1
+  check2: 2
+  check3: 4
+  ic: 1
+";
+    const ADVANTAGES: &str = "
+- id: 1
+  cost: 6
+  levels: 3
+- id: 2
+  cost: 5
+- id: 3
+  cost: [4, 6, 8, 10]
+";
+    const ADVANTAGE_NAMES: &str = "
+- id: 1
+  name: Hohe Lebenskraft
+- id: 2
+  name: Glück
+- id: 3
+  name: Begabung
+";
+    const DISADVANTAGES: &str = "
+- id: 1
+  cost: 20
+";
+    const SPECIAL_ABILITIES: &str = "
+- id: 1
+  cost: [10, 15]
+  levels: 2
+";
+
+    fn rules_with_data() -> Rules {
+        let mut rules = Rules::default();
+        let optolith = &mut rules.optolith;
+        optolith.add_data(SPELLS, Some(SPELL_NAMES), "SPELL_", DataCategory::Talent).unwrap();
+        optolith.add_data(LITURGIES, None, "LITURGY_", DataCategory::Talent).unwrap();
+        optolith.add_data(ADVANTAGES, Some(ADVANTAGE_NAMES), "ADV_", DataCategory::Activatable { disadvantage: false }).unwrap();
+        optolith.add_data(DISADVANTAGES, None, "DISADV_", DataCategory::Activatable { disadvantage: true }).unwrap();
+        optolith.add_data(SPECIAL_ABILITIES, None, "SA_", DataCategory::Activatable { disadvantage: false }).unwrap();
+        rules
+    }
+
+    fn hero() -> String {
+        json!({
+            "clientVersion": "1.5.1",
+            "name": "Test",
+            "r": "R_1",
+            "attr": { "values": [], "lp": 0, "ae": 0, "kp": 0 },
+            "talents": {},
+            "ct": {},
+            "spells": { "SPELL_1": 8, "SPELL_2": 4 },
+            "liturgies": { "LITURGY_1": 5 },
+            "activatable": {
+                "ADV_1": [{ "tier": 2 }],
+                "ADV_2": [{}],
+                "ADV_3": [{ "sid": "TAL_1" }],
+                "DISADV_1": [{}],
+                "SA_1": [{ "tier": 2 }],
+            },
+            "ap": { "total": 1100 },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn data_files_give_names_stf_checks_and_ap_values() {
+        let rules = rules_with_data();
+        let entries = &rules.optolith.entries;
+
+        let spell = &entries["SPELL_1"];
+        assert_eq!(spell.name, "Adlerauge");
+        assert_eq!(spell.improvement_cost.as_deref(), Some("B"));
+        assert_eq!(spell.check, Some(["kl".to_string(), "in".to_string(), "ch".to_string()]));
+        // Without a l10n file the entry is named by its id
+        assert_eq!(entries["LITURGY_1"].name, "LITURGY_1");
+        assert_eq!(entries["ADV_1"].ap_value.as_deref(), Some("6"));
+        assert_eq!(entries["DISADV_1"].ap_value.as_deref(), Some("-20"));
+        assert_eq!(entries["SA_1"].ap_value.as_deref(), Some("10;15"));
+        // The cost depends on the selected skill
+        assert_eq!(entries["ADV_3"].ap_value, None);
+    }
+
+    #[test]
+    fn entries_of_the_rules_file_win_over_the_data_files() {
+        let mut optolith = Rules::default().optolith;
+        optolith.entries.insert("ADV_2".to_string(), OptolithEntry {
+            name: "Glück (Hausregel)".to_string(),
+            item_type: None,
+            ap_value: Some("3".to_string()),
+            improvement_cost: None,
+            check: None,
+        });
+
+        let added = optolith.add_data(ADVANTAGES, Some(ADVANTAGE_NAMES), "ADV_", DataCategory::Activatable { disadvantage: false }).unwrap();
+        assert_eq!(added, 2);
+        assert_eq!(optolith.entries["ADV_2"].ap_value.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn hero_with_spells_and_advantages_is_costed_per_category() {
+        let rules = rules_with_data();
+        let import = OptolithImport::from_json(&hero(), &rules).unwrap();
+        let report = ApCalculator::new(&rules).build_report(&import.character);
+        let ap_by_category = report.ap_by_category();
+
+        // SPELL_1: 2 + 8 × 2 (B), SPELL_2: 3 + 4 × 3 (C)
+        assert_eq!(ap_by_category[category::SPELLS_RITUALS], 18 + 15);
+        // 1 + 5 × 1 (A)
+        assert_eq!(ap_by_category[category::LITURGIES_CEREMONIES], 6);
+        // Hohe Lebenskraft 6 × 2 + Glück 5; Begabung has no cost in the table
+        assert_eq!(ap_by_category["advantage"], 17);
+        assert_eq!(ap_by_category["disadvantage"], -20);
+        assert_eq!(ap_by_category["specialability"], 10 + 15);
+
+        let mut names: Vec<&str> = import.character.get_advantages().iter().map(|item| item.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["Begabung (Fliegen)", "Glück", "Hohe Lebenskraft"]);
+        assert!(import.warnings.iter().any(|warning| warning.contains("ADV_3")));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::character::{ApCalculator, ApCategoryTotal, Character, VerificationStatus};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// AP overview of one hero of a party
+#[derive(Debug, Clone, Serialize)]
+pub struct HeroSummary {
+    pub name: String,
+    /// Total AP from the export, if it records them
+    pub total_ap: Option<i32>,
+    /// Spent AP as recorded by the export
+    pub recorded_spent: Option<i32>,
+    pub calculated_spent: i32,
+    /// AP per category, sorted by AP descending, then by name
+    pub categories: Vec<ApCategoryTotal>,
+    /// Entries that could not be costed
+    pub warnings: usize,
+}
+
+impl HeroSummary {
+    pub fn new(character: &Character, calculator: &ApCalculator) -> Self {
+        let report = calculator.build_report(character);
+        Self {
+            name: character.name.clone(),
+            total_ap: character.experience().map(|experience| experience.total()),
+            recorded_spent: report.foundry_spent,
+            calculated_spent: report.total,
+            categories: report.categories,
+            warnings: report.warnings.len(),
+        }
+    }
+
+    /// AP not yet spent according to the export (`total - spent`, as in the planner)
+    pub fn remaining_ap(&self) -> Option<i32> {
+        self.total_ap.zip(self.recorded_spent).map(|(total, spent)| total - spent)
+    }
+
+    pub fn status(&self) -> VerificationStatus {
+        VerificationStatus::between(self.calculated_spent, self.recorded_spent)
+    }
+
+    /// AP spent in `category`, 0 if the hero has nothing in it
+    pub fn category_ap(&self, category: &str) -> i32 {
+        self.categories
+            .iter()
+            .find(|total| total.category == category)
+            .map_or(0, |total| total.ap_cost)
+    }
+}
+
+/// Categories of all heroes, sorted by the AP of the whole party descending, then by name
+pub fn party_categories<'a>(heroes: impl IntoIterator<Item = &'a HeroSummary>) -> Vec<String> {
+    let mut totals: HashMap<&str, i32> = HashMap::new();
+    for total in heroes.into_iter().flat_map(|hero| &hero.categories) {
+        *totals.entry(total.category.as_str()).or_insert(0) += total.ap_cost;
+    }
+
+    let mut categories: Vec<(&str, i32)> = totals.into_iter().collect();
+    categories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    categories.into_iter().map(|(category, _)| category.to_string()).collect()
+}
@@ -0,0 +1,366 @@
+use crate::character::{ApCalculator, Character, Characteristics, Item};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A tentative advancement of a character
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlannedChange {
+    /// Raise a skill, combat technique, spell, ritual, liturgy or ceremony the character already has
+    RaiseTalent { item_type: String, name: String, target: i32 },
+    /// Raise a characteristic (identified by its key "mu", "kl", ...) to a value without species modifier
+    RaiseCharacteristic { key: String, target: i32 },
+    /// Buy energy advances, `target` is the total number of advances
+    RaiseEnergy { energy: Energy, target: i32 },
+    /// Add an advantage, disadvantage or special ability that is bought via its `APValue`
+    AddItem { item_type: String, name: String, ap_value: String, step: i32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Energy {
+    LifePoints,
+    AstralEnergy,
+    KarmaEnergy,
+}
+
+impl Energy {
+    pub const ALL: [Energy; 3] = [Energy::LifePoints, Energy::AstralEnergy, Energy::KarmaEnergy];
+
+    /// Display name, the same as in the AP report
+    pub fn name(&self) -> &'static str {
+        match self {
+            Energy::LifePoints => "Life Points (LeP)",
+            Energy::AstralEnergy => "Astral Energy (AsP)",
+            Energy::KarmaEnergy => "Karma Energy (KaP)",
+        }
+    }
+
+    /// Advances already bought, or `None` if the character does not have this energy
+    fn advances(&self, character: &Character) -> Option<i32> {
+        let status = character.system.as_ref()?.status.as_ref()?;
+        match self {
+            Energy::LifePoints => status.wounds.as_ref().map(|w| w.advances()),
+            Energy::AstralEnergy => status.astralenergy.as_ref().map(|a| a.advances()),
+            Energy::KarmaEnergy => status.karmaenergy.as_ref().map(|k| k.advances()),
+        }
+    }
+}
+
+impl PlannedChange {
+    pub fn description(&self) -> String {
+        match self {
+            PlannedChange::RaiseTalent { name, target, .. } => format!("{} → {}", name, target),
+            PlannedChange::RaiseCharacteristic { key, target } => {
+                format!("{} → {}", characteristic_name(key), target)
+            }
+            PlannedChange::RaiseEnergy { energy, target } => format!("{} → {} advances", energy.name(), target),
+            PlannedChange::AddItem { name, step, .. } if *step > 1 => format!("{} (step {})", name, step),
+            PlannedChange::AddItem { name, .. } => name.clone(),
+        }
+    }
+
+    /// Key that identifies what is changed, so that several changes of the same value build on each other
+    fn target_key(&self) -> String {
+        match self {
+            PlannedChange::RaiseTalent { item_type, name, .. } => format!("{}:{}", item_type, name),
+            PlannedChange::RaiseCharacteristic { key, .. } => format!("characteristic:{}", key.to_lowercase()),
+            PlannedChange::RaiseEnergy { energy, .. } => format!("energy:{:?}", energy),
+            PlannedChange::AddItem { item_type, name, .. } => format!("{}:{}", item_type, name),
+        }
+    }
+
+    fn target(&self) -> i32 {
+        match self {
+            PlannedChange::RaiseTalent { target, .. }
+            | PlannedChange::RaiseCharacteristic { target, .. }
+            | PlannedChange::RaiseEnergy { target, .. } => *target,
+            PlannedChange::AddItem { step, .. } => *step,
+        }
+    }
+}
+
+fn characteristic_name(key: &str) -> &str {
+    Characteristics::NAMES
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, name)| *name)
+        .unwrap_or(key)
+}
+
+/// List of planned changes for one character, saved as JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Plan {
+    /// Name of the character the plan was made for
+    pub character: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum PlanStatus {
+    /// Not yet bought
+    Open,
+    /// The character already has the target value (e.g. in a newer export)
+    Reached,
+    /// The change cannot be applied to this character
+    Invalid(String),
+}
+
+/// Incremental cost of one planned change
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedChangeCost {
+    pub change: PlannedChange,
+    /// Value before this change (including earlier planned changes of the same value)
+    pub current: Option<i32>,
+    pub target: i32,
+    pub ap_cost: i32,
+    pub status: PlanStatus,
+}
+
+/// A plan checked against a character export
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanEvaluation {
+    pub costs: Vec<PlannedChangeCost>,
+    /// AP not yet spent according to the export (`Experience::total - spent`)
+    pub available_ap: i32,
+    /// AP needed for all open changes
+    pub planned_ap: i32,
+}
+
+impl PlanEvaluation {
+    /// AP left after buying all open changes (negative if the plan is not affordable)
+    pub fn remaining_ap(&self) -> i32 {
+        self.available_ap - self.planned_ap
+    }
+}
+
+impl Plan {
+    pub fn new(character: &str) -> Self {
+        Self {
+            character: character.to_string(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The plan was made for this character (compared by name)
+    pub fn is_for(&self, character: &Character) -> bool {
+        self.character == character.name
+    }
+
+    /// Calculate the incremental AP cost of every change from the current values of the character.
+    /// Changes of the same value build on each other in the order of the plan.
+    pub fn evaluate(&self, character: &Character, calculator: &ApCalculator) -> PlanEvaluation {
+        let mut planned_values: HashMap<String, i32> = HashMap::new();
+
+        let costs: Vec<PlannedChangeCost> = self.changes
+            .iter()
+            .map(|change| {
+                let key = change.target_key();
+                let target = change.target();
+                let result = Self::current_value(change, character)
+                    .map(|current| planned_values.get(&key).copied().unwrap_or(current))
+                    .and_then(|current| {
+                        if target <= current {
+                            return Ok((current, 0, PlanStatus::Reached));
+                        }
+                        let ap_cost = Self::cost_at(change, character, target, calculator)?
+                            - Self::cost_at(change, character, current, calculator)?;
+                        Ok((current, ap_cost, PlanStatus::Open))
+                    });
+
+                match result {
+                    Ok((current, ap_cost, status)) => {
+                        planned_values.insert(key, current.max(target));
+                        PlannedChangeCost { change: change.clone(), current: Some(current), target, ap_cost, status }
+                    }
+                    Err(reason) => PlannedChangeCost {
+                        change: change.clone(),
+                        current: None,
+                        target,
+                        ap_cost: 0,
+                        status: PlanStatus::Invalid(reason),
+                    },
+                }
+            })
+            .collect();
+
+        let available_ap = character.remaining_ap(calculator).unwrap_or(0);
+        let planned_ap = costs.iter().map(|cost| cost.ap_cost).sum();
+
+        PlanEvaluation { costs, available_ap, planned_ap }
+    }
+
+    /// Remove the changes the character already has, e.g. after loading a newer export
+    pub fn remove_reached(&mut self, character: &Character, calculator: &ApCalculator) {
+        let evaluation = self.evaluate(character, calculator);
+        self.changes = evaluation.costs
+            .into_iter()
+            .filter(|cost| cost.status != PlanStatus::Reached)
+            .map(|cost| cost.change)
+            .collect();
+    }
+
+    /// Value the character has in the export (step 0 for items that are not bought yet)
+    fn current_value(change: &PlannedChange, character: &Character) -> Result<i32, String> {
+        match change {
+            PlannedChange::RaiseTalent { .. } => {
+                let item = Self::find_item(change, character)
+                    .ok_or_else(|| "Not found in this character".to_string())?;
+                item.data()
+                    .talent_value()
+                    .ok_or_else(|| "Missing or unparseable talentValue".to_string())
+            }
+            PlannedChange::RaiseCharacteristic { key, .. } => character.system
+                .as_ref()
+                .and_then(|s| s.characteristics.as_ref())
+                .and_then(|c| c.get(key))
+                .map(|c| c.nominal_value())
+                .ok_or_else(|| format!("Unknown characteristic '{}'", key)),
+            PlannedChange::RaiseEnergy { energy, .. } => energy.advances(character)
+                .ok_or_else(|| format!("Character has no {}", energy.name())),
+            PlannedChange::AddItem { .. } => Ok(Self::find_item(change, character)
+                .map(|item| item.purchase().step.unwrap_or(1))
+                .unwrap_or(0)),
+        }
+    }
+
+    /// Total AP cost of the changed value at `value`, using the same functions as the AP report
+    fn cost_at(change: &PlannedChange, character: &Character, value: i32, calculator: &ApCalculator) -> Result<i32, String> {
+        match change {
+            PlannedChange::RaiseTalent { .. } => {
+                let item = Self::find_item(change, character)
+                    .ok_or_else(|| "Not found in this character".to_string())?;
+                calculator.talent_ap_cost(item, value)
+            }
+            PlannedChange::RaiseCharacteristic { .. } => calculator.characteristic_to_ap_cost(value),
+            PlannedChange::RaiseEnergy { .. } => Ok(calculator.energy_ap_cost(value, 0)),
+            PlannedChange::AddItem { .. } if value == 0 => Ok(0),
+            PlannedChange::AddItem { ap_value, .. } => ApCalculator::ap_value_cost(ap_value, value),
+        }
+    }
+
+    fn find_item<'a>(change: &PlannedChange, character: &'a Character) -> Option<&'a Item> {
+        let (item_type, name) = match change {
+            PlannedChange::RaiseTalent { item_type, name, .. }
+            | PlannedChange::AddItem { item_type, name, .. } => (item_type, name),
+            _ => return None,
+        };
+
+        character.items
+            .iter()
+            .find(|item| item.kind.as_str() == item_type && &item.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Klettern 4 (StF B), MU 12, an advantage Glück and 100 AP left
+    fn hero() -> Character {
+        serde_json::from_value(json!({
+            "name": "Test", "type": "character", "img": null,
+            "items": [
+                {
+                    "_id": "s1", "name": "Klettern", "type": "skill", "img": null,
+                    "system": { "talentValue": { "value": 4 }, "StF": { "value": "B" } },
+                },
+                { "_id": "a1", "name": "Glück", "type": "advantage", "img": null, "system": { "APValue": { "value": "30" }, "step": { "value": 1 } } },
+            ],
+            "system": {
+                "characteristics": { "mu": { "initial": 8, "advances": 4 } },
+                "details": { "experience": { "total": 1100, "spent": 1000 } },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn raise_klettern(target: i32) -> PlannedChange {
+        PlannedChange::RaiseTalent { item_type: "skill".to_string(), name: "Klettern".to_string(), target }
+    }
+
+    fn add_item(name: &str, ap_value: &str, step: i32) -> PlannedChange {
+        PlannedChange::AddItem { item_type: "advantage".to_string(), name: name.to_string(), ap_value: ap_value.to_string(), step }
+    }
+
+    fn plan(changes: Vec<PlannedChange>) -> Plan {
+        Plan { character: "Test".to_string(), changes }
+    }
+
+    #[test]
+    fn changes_of_the_same_value_build_on_each_other() {
+        let calculator = ApCalculator::default();
+        let character = hero();
+        let chained = plan(vec![raise_klettern(6), raise_klettern(8)]).evaluate(&character, &calculator);
+        let direct = plan(vec![raise_klettern(8)]).evaluate(&character, &calculator);
+
+        assert_eq!(chained.costs[1].current, Some(6));
+        assert!(chained.costs.iter().all(|cost| cost.status == PlanStatus::Open && cost.ap_cost > 0));
+        assert_eq!(chained.planned_ap, direct.planned_ap);
+        assert_eq!(chained.available_ap, 100);
+        assert_eq!(chained.remaining_ap(), 100 - chained.planned_ap);
+    }
+
+    #[test]
+    fn reached_and_invalid_changes_cost_nothing() {
+        let changes = vec![
+            raise_klettern(4),
+            PlannedChange::RaiseTalent { item_type: "skill".to_string(), name: "Schwimmen".to_string(), target: 5 },
+            PlannedChange::RaiseCharacteristic { key: "xx".to_string(), target: 13 },
+            PlannedChange::RaiseEnergy { energy: Energy::AstralEnergy, target: 2 },
+        ];
+        let evaluation = plan(changes).evaluate(&hero(), &ApCalculator::default());
+
+        let statuses: Vec<&PlanStatus> = evaluation.costs.iter().map(|cost| &cost.status).collect();
+        assert_eq!(statuses[0], &PlanStatus::Reached);
+        assert_eq!(statuses[1], &PlanStatus::Invalid("Not found in this character".to_string()));
+        assert_eq!(statuses[2], &PlanStatus::Invalid("Unknown characteristic 'xx'".to_string()));
+        assert!(matches!(statuses[3], PlanStatus::Invalid(_)));
+        assert_eq!(evaluation.planned_ap, 0);
+    }
+
+    #[test]
+    fn new_items_start_at_step_zero() {
+        let evaluation = plan(vec![add_item("Zäher Hund", "10", 2), add_item("Glück", "30", 1)]).evaluate(&hero(), &ApCalculator::default());
+
+        assert_eq!(evaluation.costs[0].current, Some(0));
+        assert_eq!(evaluation.costs[0].ap_cost, 20);
+        assert_eq!(evaluation.costs[1].status, PlanStatus::Reached);
+    }
+
+    #[test]
+    fn remove_reached_keeps_open_and_invalid_changes() {
+        let mut plan = plan(vec![raise_klettern(3), raise_klettern(7), add_item("Glück", "30", 1), add_item("Zäher Hund", "10", 1)]);
+        plan.changes.push(PlannedChange::RaiseCharacteristic { key: "xx".to_string(), target: 13 });
+        plan.remove_reached(&hero(), &ApCalculator::default());
+
+        let descriptions: Vec<String> = plan.changes.iter().map(PlannedChange::description).collect();
+        assert_eq!(descriptions, ["Klettern → 7", "Zäher Hund", "xx → 13"]);
+    }
+
+    #[test]
+    fn plan_file_format() {
+        let plan = plan(vec![raise_klettern(7), PlannedChange::RaiseEnergy { energy: Energy::LifePoints, target: 3 }]);
+        let json = serde_json::to_value(&plan).unwrap();
+
+        assert_eq!(json, json!({
+            "character": "Test",
+            "changes": [
+                { "kind": "raise_talent", "item_type": "skill", "name": "Klettern", "target": 7 },
+                { "kind": "raise_energy", "energy": "LifePoints", "target": 3 },
+            ],
+        }));
+        assert_eq!(serde_json::from_value::<Plan>(json).unwrap().changes, plan.changes);
+    }
+}
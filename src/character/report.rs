@@ -0,0 +1,146 @@
+use crate::character::ItemKind;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Category names used for AP that is not bought as an item with an `APValue`.
+/// AP items (advantages, special abilities, ...) are grouped by their item type instead.
+pub mod category {
+    pub const SKILLS: &str = "Skills";
+    pub const COMBAT_SKILLS: &str = "Combat Skills";
+    pub const SPELLS_RITUALS: &str = "Spells/Rituals";
+    pub const MAGIC_TRICKS: &str = "Magic Tricks";
+    pub const LITURGIES_CEREMONIES: &str = "Liturgies/Ceremonies";
+    pub const BLESSINGS: &str = "Blessings";
+    pub const ENERGIES: &str = "Energies (LeP/AsP/KaP)";
+    pub const CHARACTERISTICS: &str = "Characteristics";
+    pub const SPECIES: &str = "Species";
+}
+
+/// Complete result of an AP calculation for one character
+#[derive(Debug, Clone, Serialize)]
+pub struct ApReport {
+    /// Calculated total of spent AP (sum of all non-excluded entries)
+    pub total: i32,
+    /// Spent AP as recorded by Foundry VTT, if the export contains it
+    pub foundry_spent: Option<i32>,
+    /// AP per category, sorted by AP descending, then by name
+    pub categories: Vec<ApCategoryTotal>,
+    /// Every line that went into the calculation
+    pub entries: Vec<ApReportEntry>,
+    /// Problems found while calculating
+    pub warnings: Vec<ApWarning>,
+}
+
+/// Result of comparing the calculated spent AP with the spent AP recorded in the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum VerificationStatus {
+    Match,
+    /// Calculated minus recorded spent AP (positive: the export shows less)
+    Mismatch(i32),
+    /// The export records no spent AP, e.g. an Optolith hero
+    Unknown,
+}
+
+impl VerificationStatus {
+    pub fn between(calculated: i32, recorded: Option<i32>) -> Self {
+        match recorded {
+            Some(spent) if spent == calculated => VerificationStatus::Match,
+            Some(spent) => VerificationStatus::Mismatch(calculated - spent),
+            None => VerificationStatus::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApCategoryTotal {
+    pub category: String,
+    pub ap_cost: i32,
+}
+
+/// A single line of the AP calculation (one item, characteristic or energy)
+#[derive(Debug, Clone, Serialize)]
+pub struct ApReportEntry {
+    pub name: String,
+    /// Foundry item type, or "characteristic"/"energy" for values stored on the actor
+    pub item_type: String,
+    pub category: String,
+    /// Talent value, step, characteristic value or energy advances
+    pub value: Option<i32>,
+    pub stf: Option<String>,
+    /// Raw `APValue` of items that carry one
+    pub raw_ap_value: Option<String>,
+    /// Human-readable explanation of how the cost was calculated
+    pub formula: String,
+    pub ap_cost: i32,
+    /// Entry is shown for transparency but does not count towards the total
+    pub excluded: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApWarning {
+    pub name: String,
+    pub reason: String,
+}
+
+impl ApReport {
+    pub fn new(entries: Vec<ApReportEntry>, warnings: Vec<ApWarning>, foundry_spent: Option<i32>) -> Self {
+        let mut totals: HashMap<&str, i32> = HashMap::new();
+        for entry in entries.iter().filter(|entry| !entry.excluded) {
+            *totals.entry(entry.category.as_str()).or_insert(0) += entry.ap_cost;
+        }
+
+        let mut categories: Vec<ApCategoryTotal> = totals
+            .into_iter()
+            .filter(|(_, ap_cost)| *ap_cost != 0)
+            .map(|(category, ap_cost)| ApCategoryTotal { category: category.to_string(), ap_cost })
+            .collect();
+        // Sort by AP value descending, then by category name ascending for stable ordering
+        categories.sort_by(|a, b| b.ap_cost.cmp(&a.ap_cost).then_with(|| a.category.cmp(&b.category)));
+
+        let total = categories.iter().map(|c| c.ap_cost).sum();
+
+        Self { total, foundry_spent, categories, entries, warnings }
+    }
+
+    /// Difference between our calculation and Foundry's spent AP (positive: Foundry shows less)
+    pub fn difference(&self) -> Option<i32> {
+        self.foundry_spent.map(|spent| self.total - spent)
+    }
+
+    pub fn status(&self) -> VerificationStatus {
+        VerificationStatus::between(self.total, self.foundry_spent)
+    }
+
+    pub fn ap_by_category(&self) -> HashMap<String, i32> {
+        self.categories
+            .iter()
+            .map(|c| (c.category.clone(), c.ap_cost))
+            .collect()
+    }
+
+    pub fn category_total(&self, category: &str) -> i32 {
+        self.categories
+            .iter()
+            .find(|c| c.category == category)
+            .map(|c| c.ap_cost)
+            .unwrap_or(0)
+    }
+
+    pub fn entries_in<'a>(&'a self, category: &'a str) -> impl Iterator<Item = &'a ApReportEntry> + 'a {
+        self.entries.iter().filter(move |entry| entry.category == category)
+    }
+
+    /// Entries of items bought via their `APValue` (advantages, disadvantages, special abilities, ...)
+    pub fn ap_item_entries(&self) -> impl Iterator<Item = &ApReportEntry> {
+        self.entries.iter().filter(|entry| entry.raw_ap_value.is_some())
+    }
+
+    /// AP of all counted items of one kind, e.g. advantages (negative for disadvantages)
+    pub fn item_type_total(&self, kind: &ItemKind) -> i32 {
+        self.ap_item_entries()
+            .filter(|entry| entry.item_type == kind.as_str() && !entry.excluded)
+            .map(|entry| entry.ap_cost)
+            .sum()
+    }
+}
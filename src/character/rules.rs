@@ -0,0 +1,333 @@
+use crate::character::data::Item;
+use crate::character::species::Species;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Built-in rule tables (DSA5 core rules). A user rules file is merged on top of these.
+pub const DEFAULT_RULES_JSON: &str = include_str!("default_rules.json");
+
+/// Data-driven rule tables for the AP calculation. They are passed to every calculation that
+/// depends on them; `Rules::builtin()` gives the built-in tables without a rules file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rules {
+    /// AP per talent point for each improvement cost column (Steigerungsfaktor, StF)
+    pub improvement_costs: BTreeMap<String, i32>,
+    pub talent_progression: Progression,
+    /// Combat techniques start at this value for free
+    pub combat_technique_free_value: i32,
+    pub characteristic: CharacteristicCosts,
+    /// Improvement cost column used for LeP/AsP/KaP advances
+    pub energy_improvement_cost: String,
+    /// AP per point to buy back permanently lost AsP/KaP
+    pub energy_rebuy_cost: i32,
+    pub magic_trick_cost: i32,
+    pub blessing_cost: i32,
+    /// Items of which only the instance with the highest step is paid, keyed by a language-neutral id
+    pub highest_step_only: BTreeMap<String, ItemMatcher>,
+    pub derived_values: DerivedValueRules,
+    /// Experience levels (Erfahrungsgrade), ordered by their starting AP
+    pub experience_levels: Vec<ExperienceLevel>,
+    pub creation_limits: CreationLimits,
+    pub encumbrance: EncumbranceRules,
+    pub wealth: WealthRules,
+    pub species: BTreeMap<String, Species>,
+    pub optolith: OptolithRules,
+}
+
+/// Cost progression: linear up to `linear_limit`, then every further point costs
+/// `increment` × the base cost more than the previous one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Progression {
+    pub linear_limit: i32,
+    pub increment: i32,
+}
+
+/// Identifies an item independent of the language of the Foundry export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemMatcher {
+    /// Compendium source ids (`_stats.compendiumSource` / `flags.core.sourceId`), checked first
+    #[serde(default)]
+    pub source_ids: Vec<String>,
+    /// German and English names (base name before any parenthesis), used as fallback
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+impl ItemMatcher {
+    /// Match an item by source id or by its full name (not only the base name)
+    pub fn matches_item(&self, item: &Item) -> bool {
+        item.source_id().is_some_and(|source_id| self.matches_source_id(source_id))
+            || self.matches_name(&item.name)
+    }
+
+    pub fn matches_source_id(&self, source_id: &str) -> bool {
+        self.source_ids.iter().any(|id| id == source_id)
+    }
+
+    pub fn matches_name(&self, base_name: &str) -> bool {
+        self.names.iter().any(|name| name.eq_ignore_ascii_case(base_name))
+    }
+}
+
+/// Experience level (Erfahrungsgrad) with its starting AP and the maximums at character creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperienceLevel {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Starting AP
+    pub ap: i32,
+    pub max_characteristic: i32,
+    pub max_characteristic_sum: i32,
+    pub max_skill: i32,
+    pub max_combat_technique: i32,
+    /// Number of spells and rituals, or liturgies and ceremonies
+    pub max_spells: i32,
+}
+
+/// AP limits for advantages and disadvantages at character creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreationLimits {
+    pub advantages_ap: i32,
+    /// Limit for the AP gained from disadvantages (as a positive number)
+    pub disadvantages_ap: i32,
+}
+
+/// Carrying capacity and encumbrance (Belastung) levels from carried weight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncumbranceRules {
+    /// Carrying capacity in Stein per point of KK
+    pub carrying_capacity_per_kk: f64,
+    /// Every full this many Stein above the carrying capacity add one level
+    pub stone_per_level: f64,
+    /// Highest encumbrance level (incapacitated)
+    pub max_level: i32,
+}
+
+/// Currencies and the unit of item prices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WealthRules {
+    /// Item prices are given in this many Kreuzer (Silbertaler = 100)
+    pub price_unit_kreuzer: i64,
+    /// From the most to the least valuable coin
+    pub currencies: Vec<Currency>,
+}
+
+/// A coin, recognized as `money` item by its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Currency {
+    /// Abbreviation, e.g. "D"
+    pub key: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub matcher: ItemMatcher,
+    /// Value in Kreuzer, the smallest coin
+    pub kreuzer: i64,
+}
+
+/// Base values and modifiers for derived values (LeP, AsP, KaP, SK, ZK, GS, fate points)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedValueRules {
+    pub fate_points_base: i32,
+    /// Base of AsP and KaP, added to the leading attribute of the tradition
+    pub energy_base: i32,
+    /// Advantages and disadvantages that change a derived value, keyed by the derived value
+    pub modifiers: BTreeMap<String, Vec<StepModifier>>,
+    pub traditions: Vec<Tradition>,
+}
+
+/// Advantage or disadvantage that changes a derived value by `per_step` for each step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepModifier {
+    #[serde(flatten)]
+    pub matcher: ItemMatcher,
+    pub per_step: i32,
+}
+
+/// Magical or clerical tradition (special ability) and its leading attribute (Leiteigenschaft)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tradition {
+    /// Full names of the special ability, e.g. "Tradition (Gildenmagier)"
+    #[serde(flatten)]
+    pub matcher: ItemMatcher,
+    /// "astral_energy" or "karma_energy"
+    pub energy: String,
+    pub leading_attribute: String,
+}
+
+/// Optolith ids and the data Foundry items carry but Optolith heroes do not, used to import Optolith heroes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptolithRules {
+    /// Keyed by the Optolith species id, e.g. "R_1"
+    pub species: BTreeMap<String, OptolithSpecies>,
+    /// Keyed by the Optolith skill id, e.g. "TAL_1"
+    pub skills: BTreeMap<String, OptolithSkill>,
+    /// Keyed by the Optolith combat technique id, e.g. "CT_1"
+    pub combat_techniques: BTreeMap<String, OptolithCombatTechnique>,
+    /// Spells, liturgies, advantages, disadvantages and special abilities, keyed by their Optolith id.
+    /// Entries not listed here are imported as placeholders named by their id and not costed.
+    /// The built-in rules ship no entries: they come from `data_dir` or are listed in a rules file.
+    #[serde(default)]
+    pub entries: BTreeMap<String, OptolithEntry>,
+    /// Directory with Optolith's YAML data files (`univ/` and a locale directory such as `de-DE/`),
+    /// relative to the rules file. Its entries are added to `entries`; ids listed there keep their entry.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// Locale directory the names are read from, "de-DE" if not set
+    #[serde(default)]
+    pub data_locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptolithSpecies {
+    /// Name of the species in `species`
+    pub name: String,
+    /// Fixed characteristic modifiers, e.g. {"kk": -2}. The +1 chosen in Optolith is added on import.
+    #[serde(default)]
+    pub attribute_adjustments: BTreeMap<String, i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptolithSkill {
+    pub name: String,
+    /// Characteristic keys of the check
+    pub check: [String; 3],
+    pub improvement_cost: String,
+    pub group: String,
+    /// Affected by encumbrance: "yes", "no" or "maybe"
+    pub burden: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptolithCombatTechnique {
+    pub name: String,
+    /// e.g. "ge/kk"
+    pub leading_attributes: String,
+    pub improvement_cost: String,
+    pub ranged: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptolithEntry {
+    pub name: String,
+    /// Foundry item type, if it differs from the one of the Optolith category (e.g. "ritual" for a spell)
+    #[serde(default)]
+    pub item_type: Option<String>,
+    /// APValue of advantages, disadvantages and special abilities
+    #[serde(default)]
+    pub ap_value: Option<String>,
+    /// StF of spells and liturgies
+    #[serde(default)]
+    pub improvement_cost: Option<String>,
+    #[serde(default)]
+    pub check: Option<[String; 3]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacteristicCosts {
+    /// Value every characteristic starts with for free
+    pub base_value: i32,
+    pub cost_per_point: i32,
+    pub linear_limit: i32,
+    pub increment: i32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::from_json(DEFAULT_RULES_JSON).expect("built-in rules must be valid")
+    }
+}
+
+impl Rules {
+    /// Load a rules file and merge it on top of the built-in rules.
+    /// Objects are merged key by key, any other value (including lists) replaces the default.
+    /// The Optolith data files of `optolith.data_dir` are read as well.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let overrides: serde_json::Value = serde_json::from_str(&content)?;
+
+        let mut merged: serde_json::Value = serde_json::from_str(DEFAULT_RULES_JSON)?;
+        merge_json(&mut merged, overrides);
+
+        let mut rules = Self::from_value(merged)?;
+        if let Some(data_dir) = rules.optolith.data_dir.clone() {
+            let data_dir = path.parent().unwrap_or(Path::new("")).join(data_dir);
+            rules.optolith.load_data(&data_dir)?;
+        }
+        Ok(rules)
+    }
+
+    fn from_json(content: &str) -> anyhow::Result<Self> {
+        Self::from_value(serde_json::from_str(content)?)
+    }
+
+    fn from_value(value: serde_json::Value) -> anyhow::Result<Self> {
+        let mut rules: Rules = serde_json::from_value(value)?;
+
+        // The species name is the map key
+        for (name, species) in rules.species.iter_mut() {
+            species.name = name.clone();
+        }
+
+        Ok(rules)
+    }
+
+    /// The built-in rules, parsed once; a loaded rules file never changes them
+    pub fn builtin() -> &'static Rules {
+        static BUILTIN: OnceLock<Rules> = OnceLock::new();
+        BUILTIN.get_or_init(Rules::default)
+    }
+
+    /// Convert StF value to multiplier (A=1, B=2, C=3, D=4 in the core rules)
+    pub fn improvement_cost(&self, stf: &str) -> Option<i32> {
+        self.improvement_costs.get(&stf.trim().to_uppercase()).copied()
+    }
+
+    /// Key of the "highest step only" rule that applies to an item, matched by
+    /// compendium source id first and by its (German or English) base name otherwise
+    pub fn highest_step_only_key(&self, item: &Item, base_name: &str) -> Option<&str> {
+        let by_source_id = item.source_id().and_then(|source_id| {
+            self.highest_step_only
+                .iter()
+                .find(|(_, matcher)| matcher.matches_source_id(source_id))
+        });
+
+        by_source_id
+            .or_else(|| {
+                self.highest_step_only
+                    .iter()
+                    .find(|(_, matcher)| matcher.matches_name(base_name))
+            })
+            .map(|(key, _)| key.as_str())
+    }
+
+    /// Look up a species by its name or one of its aliases (case-insensitive)
+    pub fn find_species(&self, name: &str) -> Option<&Species> {
+        let name = name.trim();
+        self.species.values().find(|species| {
+            species.name.eq_ignore_ascii_case(name)
+                || species.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Highest experience level whose starting AP does not exceed `ap`
+    pub fn experience_level_for(&self, ap: i32) -> Option<&ExperienceLevel> {
+        self.experience_levels
+            .iter()
+            .filter(|level| level.ap <= ap)
+            .max_by_key(|level| level.ap)
+    }
+}
+
+fn merge_json(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_json(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
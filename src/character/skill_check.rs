@@ -0,0 +1,202 @@
+use crate::character::{Character, Item, ItemData};
+use serde::Serialize;
+
+/// Highest quality level (Qualitätsstufe, QS) of a check
+pub const MAX_QUALITY_LEVEL: usize = 6;
+
+/// A DSA5 skill check (Probe): three d20 rolls against three characteristics,
+/// where the skill points (FP) compensate rolls above the characteristic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkillCheck {
+    pub characteristics: [i32; 3],
+    pub skill_value: i32,
+    /// Check modifier (Erleichterung positive, Erschwernis negative), applied to every characteristic
+    pub modifier: i32,
+}
+
+/// Exact outcome probabilities of a skill check (all 8000 rolls enumerated)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CheckProbability {
+    /// Probability of success, including critical successes
+    pub success: f64,
+    /// Probability of at least two 1s (always a success)
+    pub critical_success: f64,
+    /// Probability of at least two 20s (always a failure, Patzer)
+    pub botch: f64,
+    /// Probability of each quality level, index 0 is QS 1. The values sum up to `success`.
+    pub quality_levels: [f64; MAX_QUALITY_LEVEL],
+}
+
+impl CheckProbability {
+    /// Expected quality level of a successful check
+    pub fn expected_quality_level(&self) -> f64 {
+        if self.success == 0.0 {
+            return 0.0;
+        }
+
+        self.quality_levels
+            .iter()
+            .enumerate()
+            .map(|(index, probability)| (index + 1) as f64 * probability)
+            .sum::<f64>()
+            / self.success
+    }
+}
+
+impl SkillCheck {
+    pub fn new(characteristics: [i32; 3], skill_value: i32, modifier: i32) -> Self {
+        Self { characteristics, skill_value, modifier }
+    }
+
+    /// Build the check for a skill, spell or liturgy from its characteristics and talent value
+    pub fn for_item(item: &Item, character: &Character, modifier: i32) -> Option<Self> {
+        let ItemData::Talent(talent) = item.data() else {
+            return None;
+        };
+        let [c1, c2, c3] = talent.characteristics?;
+        let characteristics = character.system.as_ref()?.characteristics.as_ref()?;
+
+        let value_of = |key: &str| characteristics.get(key).map(|c| c.value());
+        let skill_value = talent.talent_value.unwrap_or(0);
+
+        Some(Self::new([value_of(&c1)?, value_of(&c2)?, value_of(&c3)?], skill_value, modifier))
+    }
+
+    /// Quality level and criticals of a single roll, `None` if the check fails
+    pub fn evaluate_roll(&self, rolls: [i32; 3]) -> Option<usize> {
+        let ones = rolls.iter().filter(|&&roll| roll == 1).count();
+        let twenties = rolls.iter().filter(|&&roll| roll == 20).count();
+
+        if twenties >= 2 {
+            return None;
+        }
+
+        let excess: i32 = rolls
+            .iter()
+            .zip(self.characteristics)
+            .map(|(roll, characteristic)| (roll - (characteristic + self.modifier)).max(0))
+            .sum();
+        let remaining = self.skill_value.max(0) - excess;
+
+        if remaining >= 0 {
+            Some(quality_level(remaining))
+        } else if ones >= 2 {
+            // Critical success: succeeds regardless of the remaining skill points
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    /// Enumerate all 20 × 20 × 20 rolls
+    pub fn probability(&self) -> CheckProbability {
+        const ALL_ROLLS: f64 = 8000.0;
+
+        let mut successes = 0;
+        let mut critical_successes = 0;
+        let mut botches = 0;
+        let mut quality_levels = [0u32; MAX_QUALITY_LEVEL];
+
+        for r1 in 1..=20 {
+            for r2 in 1..=20 {
+                for r3 in 1..=20 {
+                    let rolls = [r1, r2, r3];
+                    let ones = rolls.iter().filter(|&&roll| roll == 1).count();
+                    let twenties = rolls.iter().filter(|&&roll| roll == 20).count();
+
+                    if ones >= 2 {
+                        critical_successes += 1;
+                    }
+                    if twenties >= 2 {
+                        botches += 1;
+                    }
+
+                    if let Some(quality_level) = self.evaluate_roll(rolls) {
+                        successes += 1;
+                        quality_levels[quality_level - 1] += 1;
+                    }
+                }
+            }
+        }
+
+        CheckProbability {
+            success: successes as f64 / ALL_ROLLS,
+            critical_success: critical_successes as f64 / ALL_ROLLS,
+            botch: botches as f64 / ALL_ROLLS,
+            quality_levels: quality_levels.map(|count| count as f64 / ALL_ROLLS),
+        }
+    }
+}
+
+/// Quality level for the remaining skill points: 0–3 → QS 1, 4–6 → QS 2, …, 16+ → QS 6
+pub fn quality_level(remaining_points: i32) -> usize {
+    let quality_level = (remaining_points.max(1) + 2) / 3;
+    (quality_level as usize).clamp(1, MAX_QUALITY_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// At least two 1s or at least two 20s: 3 × 19 rolls with exactly two, 1 with three
+    const DOUBLE_ROLLS: f64 = 58.0 / 8000.0;
+
+    #[test]
+    fn quality_level_per_three_remaining_points() {
+        let levels: Vec<usize> = [0, 1, 3, 4, 6, 7, 13, 15, 16, 30].into_iter().map(quality_level).collect();
+        assert_eq!(levels, [1, 1, 1, 2, 2, 3, 5, 5, 6, 6]);
+    }
+
+    #[test]
+    fn evaluate_roll_compensates_excess_with_skill_points() {
+        let check = SkillCheck::new([12, 13, 14], 7, 0);
+        assert_eq!(check.evaluate_roll([10, 10, 10]), Some(3));
+        // 2 + 1 + 0 points above the characteristics leave 4
+        assert_eq!(check.evaluate_roll([14, 14, 14]), Some(2));
+        assert_eq!(check.evaluate_roll([16, 16, 16]), None);
+    }
+
+    #[test]
+    fn evaluate_roll_applies_the_modifier_to_every_characteristic() {
+        assert_eq!(SkillCheck::new([12, 12, 12], 5, 0).evaluate_roll([13, 13, 13]), Some(1));
+        assert_eq!(SkillCheck::new([12, 12, 12], 5, -1).evaluate_roll([13, 13, 13]), None);
+        assert_eq!(SkillCheck::new([12, 12, 12], 0, 1).evaluate_roll([13, 13, 13]), Some(1));
+    }
+
+    #[test]
+    fn evaluate_roll_criticals_override_the_skill_points() {
+        let check = SkillCheck::new([8, 8, 8], 0, 0);
+        assert_eq!(check.evaluate_roll([1, 1, 20]), Some(1));
+        assert_eq!(SkillCheck::new([20, 20, 20], 18, 0).evaluate_roll([20, 20, 1]), None);
+    }
+
+    #[test]
+    fn probability_counts_criticals_and_botches_independently_of_the_values() {
+        let probability = SkillCheck::new([11, 12, 13], 6, 0).probability();
+        assert!((probability.critical_success - DOUBLE_ROLLS).abs() < 1e-12);
+        assert!((probability.botch - DOUBLE_ROLLS).abs() < 1e-12);
+    }
+
+    #[test]
+    fn probability_distributes_successes_over_quality_levels() {
+        let probability = SkillCheck::new([11, 12, 13], 6, 0).probability();
+        let sum: f64 = probability.quality_levels.iter().sum();
+        assert!((sum - probability.success).abs() < 1e-12);
+        assert!(probability.success > 0.0 && probability.success < 1.0);
+
+        // Characteristics of 20 fail only on a botch; all remaining skill points count
+        let certain = SkillCheck::new([20, 20, 20], 18, 0).probability();
+        assert!((certain.success - (1.0 - DOUBLE_ROLLS)).abs() < 1e-12);
+        assert!((certain.quality_levels[MAX_QUALITY_LEVEL - 1] - certain.success).abs() < 1e-12);
+        assert!((certain.expected_quality_level() - 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn expected_quality_level_of_a_hopeless_check() {
+        let probability = SkillCheck::new([1, 1, 1], 0, -20).probability();
+        // Only the critical successes remain
+        assert!((probability.success - DOUBLE_ROLLS).abs() < 1e-12);
+        assert!((probability.expected_quality_level() - 1.0).abs() < 1e-12);
+        assert_eq!(CheckProbability { success: 0.0, critical_success: 0.0, botch: 0.0, quality_levels: [0.0; MAX_QUALITY_LEVEL] }.expected_quality_level(), 0.0);
+    }
+}
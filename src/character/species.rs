@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Species (Spezies) data that affects AP and derived values, defined in the rules file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Species {
+    /// Canonical (German) name, taken from the key in the rules file
+    #[serde(skip_deserializing)]
+    pub name: String,
+    /// Alternative names as found in German and English exports
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// AP cost of the species, paid at character creation
+    pub ap_cost: i32,
+    /// Base value of life points (LeP), added to 2 × KO
+    pub life_points_base: i32,
+    /// Base value of Seelenkraft (SK), added to (MU + KL + IN) / 6
+    pub soul_power_base: i32,
+    /// Base value of Zähigkeit (ZK), added to (KO + KO + KK) / 6
+    pub toughness_base: i32,
+    /// Geschwindigkeit (GS)
+    pub speed: i32,
+}
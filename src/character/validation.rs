@@ -0,0 +1,540 @@
+use crate::character::{ApCalculator, Character, Characteristics, CombatValues, CompendiumIndex, ExperienceLevel, ExperienceLevelCheck, ItemData, ItemKind, ReferenceField};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Item types that are picked once; a second item with the same name is a duplicate
+const UNIQUE_ITEM_KINDS: [ItemKind; 11] = [
+    ItemKind::Advantage, ItemKind::Disadvantage, ItemKind::SpecialAbility, ItemKind::Skill, ItemKind::CombatSkill,
+    ItemKind::Spell, ItemKind::Ritual, ItemKind::Liturgy, ItemKind::Ceremony, ItemKind::MagicTrick, ItemKind::Blessing,
+];
+
+/// Added to the highest characteristic of a check or the leading attribute to get the maximum talent value
+const TALENT_MAXIMUM_BONUS: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Severity {
+    /// The character breaks a rule that always applies
+    Error,
+    /// The character breaks a rule that only applies at character creation, or the data is suspicious
+    Warning,
+}
+
+impl Severity {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Severity::Error => "✖",
+            Severity::Warning => "⚠",
+        }
+    }
+}
+
+/// Rules checked by the validation
+/// Ordered as listed, which is the order of the findings within a severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValidationRule {
+    SkillMaximum,
+    CombatTechniqueMaximum,
+    CreationCharacteristicMaximum,
+    CreationCharacteristicSum,
+    CreationSkillMaximum,
+    CreationCombatTechniqueMaximum,
+    CreationSpellCount,
+    CreationAdvantageAp,
+    CreationDisadvantageAp,
+    StartingAp,
+    DuplicatePick,
+    CompendiumApValue,
+    CompendiumStepLimit,
+    CompendiumImprovementCost,
+}
+
+impl ValidationRule {
+    /// Short identifier, e.g. for filtering the CLI output
+    pub fn id(&self) -> &'static str {
+        match self {
+            ValidationRule::SkillMaximum => "skill-maximum",
+            ValidationRule::CombatTechniqueMaximum => "combat-technique-maximum",
+            ValidationRule::CreationCharacteristicMaximum => "creation-characteristic-maximum",
+            ValidationRule::CreationCharacteristicSum => "creation-characteristic-sum",
+            ValidationRule::CreationSkillMaximum => "creation-skill-maximum",
+            ValidationRule::CreationCombatTechniqueMaximum => "creation-combat-technique-maximum",
+            ValidationRule::CreationSpellCount => "creation-spell-count",
+            ValidationRule::CreationAdvantageAp => "creation-advantage-ap",
+            ValidationRule::CreationDisadvantageAp => "creation-disadvantage-ap",
+            ValidationRule::StartingAp => "starting-ap",
+            ValidationRule::DuplicatePick => "duplicate-pick",
+            ValidationRule::CompendiumApValue => "compendium-ap-value",
+            ValidationRule::CompendiumStepLimit => "compendium-step-limit",
+            ValidationRule::CompendiumImprovementCost => "compendium-improvement-cost",
+        }
+    }
+
+    /// The rule in the core rules (Regelwerk) the finding refers to
+    pub fn reference(&self) -> &'static str {
+        match self {
+            ValidationRule::SkillMaximum => "Fertigkeiten: maximum skill value = highest check characteristic + 2",
+            ValidationRule::CombatTechniqueMaximum => "Kampftechniken: maximum technique value = highest leading attribute + 2",
+            ValidationRule::CreationCharacteristicMaximum => "Heldenerschaffung, Erfahrungsgrad: maximum characteristic value",
+            ValidationRule::CreationCharacteristicSum => "Heldenerschaffung, Erfahrungsgrad: maximum sum of characteristics",
+            ValidationRule::CreationSkillMaximum => "Heldenerschaffung, Erfahrungsgrad: maximum skill value",
+            ValidationRule::CreationCombatTechniqueMaximum => "Heldenerschaffung, Erfahrungsgrad: maximum combat technique value",
+            ValidationRule::CreationSpellCount => "Heldenerschaffung, Erfahrungsgrad: maximum number of spells/liturgies",
+            ValidationRule::CreationAdvantageAp => "Heldenerschaffung: at most 80 AP for advantages",
+            ValidationRule::CreationDisadvantageAp => "Heldenerschaffung: at most 80 AP from disadvantages",
+            ValidationRule::StartingAp => "Heldenerschaffung, Erfahrungsgrad: total AP = starting AP of the level + awarded AP",
+            ValidationRule::DuplicatePick => "Vor- und Nachteile, Sonderfertigkeiten: each can only be picked once (use steps instead)",
+            ValidationRule::CompendiumApValue => "Compendium: the APValue of the official entry",
+            ValidationRule::CompendiumStepLimit => "Compendium: the highest step of the official entry",
+            ValidationRule::CompendiumImprovementCost => "Compendium: the StF of the official entry",
+        }
+    }
+}
+
+/// A rule the character breaks
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub rule: ValidationRule,
+    pub reference: &'static str,
+    /// Item, characteristic or category the finding is about
+    pub subject: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, rule: ValidationRule, subject: impl Into<String>, message: String) -> Self {
+        Self {
+            severity,
+            rule,
+            reference: rule.reference(),
+            subject: subject.into(),
+            message,
+        }
+    }
+}
+
+/// Result of checking a character against the rules
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub character: String,
+    /// Experience level whose creation limits were checked, derived from the starting AP
+    pub experience: Option<ExperienceLevelCheck>,
+    /// Sorted by severity, then by rule
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    /// `awarded_ap` are the AP awarded since creation, if known; the experience level is inferred from
+    /// the starting AP (total AP minus awards). Items are checked against the compendium of `calculator`, if it has one.
+    pub fn validate(character: &Character, awarded_ap: Option<i32>, calculator: &ApCalculator) -> Self {
+        let experience = ExperienceLevelCheck::evaluate(character, awarded_ap, calculator);
+        let experience_level = experience.as_ref().and_then(|check| check.level.as_ref());
+
+        let mut findings = Vec::new();
+        findings.extend(Self::check_skill_maximums(character));
+        findings.extend(Self::check_combat_technique_maximums(character));
+        if let Some(level) = experience_level {
+            findings.extend(Self::check_creation_maximums(character, level));
+        }
+        if let Some(check) = experience.as_ref().filter(|check| check.confirmed == Some(false)) {
+            findings.push(Finding::new(
+                Severity::Warning,
+                ValidationRule::StartingAp,
+                "Experience",
+                format!("Total AP {} − awarded AP {} = {} is not the starting AP of an experience level",
+                        check.total_ap, check.awarded_ap.unwrap_or(0), check.start_ap),
+            ));
+        }
+        findings.extend(Self::check_creation_ap_limits(character, calculator));
+        findings.extend(Self::check_duplicates(character));
+        if let Some(compendium) = calculator.compendium() {
+            findings.extend(Self::check_compendium(character, compendium));
+        }
+
+        findings.sort_by_key(|finding| (finding.severity, finding.rule));
+
+        Self {
+            character: character.name.clone(),
+            experience,
+            findings,
+        }
+    }
+
+    pub fn experience_level(&self) -> Option<&str> {
+        self.experience.as_ref()?.level.as_ref().map(|level| level.name.as_str())
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings.iter().filter(|finding| finding.severity == severity).count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    /// Skills, spells and liturgies: at most the highest of their three characteristics + 2
+    fn check_skill_maximums(character: &Character) -> Vec<Finding> {
+        let Some(characteristics) = character.system.as_ref().and_then(|s| s.characteristics.as_ref()) else {
+            return Vec::new();
+        };
+
+        character.items
+            .iter()
+            .filter_map(|item| {
+                let ItemData::Talent(talent) = &item.data() else {
+                    return None;
+                };
+                let (key, highest) = talent.characteristics
+                    .as_ref()?
+                    .iter()
+                    .filter_map(|key| characteristics.get(key).map(|c| (key, c.value())))
+                    .max_by_key(|(_, value)| *value)?;
+                let value = talent.talent_value?;
+                let maximum = highest + TALENT_MAXIMUM_BONUS;
+
+                (value > maximum).then(|| Finding::new(
+                    Severity::Error,
+                    ValidationRule::SkillMaximum,
+                    &item.name,
+                    format!("Value {} exceeds the maximum of {} ({} {} + {})",
+                            value, maximum, key.to_uppercase(), highest, TALENT_MAXIMUM_BONUS),
+                ))
+            })
+            .collect()
+    }
+
+    /// Combat techniques: at most the highest leading attribute + 2
+    fn check_combat_technique_maximums(character: &Character) -> Vec<Finding> {
+        let Some(characteristics) = character.system.as_ref().and_then(|s| s.characteristics.as_ref()) else {
+            return Vec::new();
+        };
+
+        CombatValues::calculate(character)
+            .techniques
+            .into_iter()
+            .filter_map(|technique| {
+                let (key, highest) = technique.leading_attributes
+                    .iter()
+                    .filter_map(|key| characteristics.get(key).map(|c| (key, c.value())))
+                    .max_by_key(|(_, value)| *value)?;
+                let maximum = highest + TALENT_MAXIMUM_BONUS;
+
+                (technique.technique_value > maximum).then(|| Finding::new(
+                    Severity::Error,
+                    ValidationRule::CombatTechniqueMaximum,
+                    &technique.name,
+                    format!("Value {} exceeds the maximum of {} ({} {} + {})",
+                            technique.technique_value, maximum, key.to_uppercase(), highest, TALENT_MAXIMUM_BONUS),
+                ))
+            })
+            .collect()
+    }
+
+    /// Maximums of the experience level; they only apply at character creation, so they are warnings
+    fn check_creation_maximums(character: &Character, level: &ExperienceLevel) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        if let Some(characteristics) = character.system.as_ref().and_then(|s| s.characteristics.as_ref()) {
+            let mut sum = 0;
+            for (key, name) in Characteristics::NAMES {
+                let Some(value) = characteristics.get(key).map(|c| c.value()) else {
+                    continue;
+                };
+                sum += value;
+                if value > level.max_characteristic {
+                    findings.push(Finding::new(
+                        Severity::Warning,
+                        ValidationRule::CreationCharacteristicMaximum,
+                        name,
+                        format!("Value {} exceeds the creation maximum of {} for {}", value, level.max_characteristic, level.name),
+                    ));
+                }
+            }
+
+            if sum > level.max_characteristic_sum {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    ValidationRule::CreationCharacteristicSum,
+                    "Characteristics",
+                    format!("Sum {} exceeds the creation maximum of {} for {}", sum, level.max_characteristic_sum, level.name),
+                ));
+            }
+        }
+
+        let talent_checks = [
+            (character.get_skills(), level.max_skill, ValidationRule::CreationSkillMaximum),
+            (character.get_combat_skills(), level.max_combat_technique, ValidationRule::CreationCombatTechniqueMaximum),
+        ];
+        for (items, maximum, rule) in talent_checks {
+            for item in items {
+                if let Some(value) = item.data().talent_value().filter(|value| *value > maximum) {
+                    findings.push(Finding::new(
+                        Severity::Warning,
+                        rule,
+                        &item.name,
+                        format!("Value {} exceeds the creation maximum of {} for {}", value, maximum, level.name),
+                    ));
+                }
+            }
+        }
+
+        let spell_counts = [
+            ("Spells/Rituals", character.get_spells_and_rituals().len()),
+            ("Liturgies/Ceremonies", character.get_liturgies_and_ceremonies().len()),
+        ];
+        for (subject, count) in spell_counts {
+            if count as i32 > level.max_spells {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    ValidationRule::CreationSpellCount,
+                    subject,
+                    format!("{} known, the creation maximum for {} is {}", count, level.name, level.max_spells),
+                ));
+            }
+        }
+
+        findings
+    }
+
+    /// At most 80 AP for advantages and 80 AP from disadvantages (only at character creation)
+    fn check_creation_ap_limits(character: &Character, calculator: &ApCalculator) -> Vec<Finding> {
+        let limits = &calculator.rules().creation_limits;
+        let report = calculator.build_report(character);
+        let advantages_ap = report.item_type_total(&ItemKind::Advantage);
+        let disadvantages_ap = -report.item_type_total(&ItemKind::Disadvantage);
+        let mut findings = Vec::new();
+
+        if advantages_ap > limits.advantages_ap {
+            findings.push(Finding::new(
+                Severity::Warning,
+                ValidationRule::CreationAdvantageAp,
+                "Advantages",
+                format!("{} AP spent on advantages, the creation limit is {} AP", advantages_ap, limits.advantages_ap),
+            ));
+        }
+        if disadvantages_ap > limits.disadvantages_ap {
+            findings.push(Finding::new(
+                Severity::Warning,
+                ValidationRule::CreationDisadvantageAp,
+                "Disadvantages",
+                format!("{} AP gained from disadvantages, the creation limit is {} AP", disadvantages_ap, limits.disadvantages_ap),
+            ));
+        }
+
+        findings
+    }
+
+    /// The same advantage, disadvantage, special ability or talent more than once (same type and full name)
+    fn check_duplicates(character: &Character) -> Vec<Finding> {
+        let mut counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for item in character.items.iter().filter(|item| UNIQUE_ITEM_KINDS.contains(&item.kind)) {
+            *counts.entry((item.kind.as_str(), item.name.as_str())).or_insert(0) += 1;
+        }
+
+        counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|((item_type, name), count)| Finding::new(
+                Severity::Error,
+                ValidationRule::DuplicatePick,
+                name,
+                format!("Picked {} times as {}", count, item_type),
+            ))
+            .collect()
+    }
+
+    /// Advantages, disadvantages and special abilities that deviate from the loaded compendium (homebrew or outdated copies)
+    fn check_compendium(character: &Character, compendium: &CompendiumIndex) -> Vec<Finding> {
+        compendium.deviations(&character.items)
+            .into_iter()
+            .map(|deviation| {
+                let (rule, message) = match deviation.field {
+                    ReferenceField::ApValue => (ValidationRule::CompendiumApValue,
+                        format!("APValue {}, {} has {}", deviation.value, deviation.pack, deviation.reference)),
+                    ReferenceField::MaxStep => (ValidationRule::CompendiumStepLimit,
+                        format!("Highest step {}, {} has {}", deviation.value, deviation.pack, deviation.reference)),
+                    ReferenceField::Step => (ValidationRule::CompendiumStepLimit,
+                        format!("Step {} is above the highest step {} in {}", deviation.value, deviation.reference, deviation.pack)),
+                    ReferenceField::ImprovementCost => (ValidationRule::CompendiumImprovementCost,
+                        format!("StF {}, {} has {}", deviation.value, deviation.pack, deviation.reference)),
+                };
+                Finding::new(Severity::Warning, rule, deviation.item, message)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn skill(name: &str, value: i32, check: [&str; 3]) -> Value {
+        json!({
+            "_id": name, "name": name, "type": "skill", "img": null,
+            "system": {
+                "talentValue": { "value": value }, "StF": { "value": "B" },
+                "characteristic1": { "value": check[0] }, "characteristic2": { "value": check[1] }, "characteristic3": { "value": check[2] },
+            },
+        })
+    }
+
+    fn combat_technique(name: &str, value: i32, leading_attributes: &str) -> Value {
+        json!({
+            "_id": name, "name": name, "type": "combatskill", "img": null,
+            "system": {
+                "talentValue": { "value": value }, "StF": { "value": "C" },
+                "guidevalue": { "value": leading_attributes }, "weapontype": { "value": "melee" },
+            },
+        })
+    }
+
+    fn advantage(name: &str, item_type: &str, ap_value: i32) -> Value {
+        json!({ "_id": name, "name": name, "type": item_type, "img": null, "system": { "APValue": { "value": ap_value.to_string() } } })
+    }
+
+    /// All characteristics 12 except those in `raised`
+    fn hero(total_ap: i32, raised: &[(&str, i32)], items: Vec<Value>) -> Character {
+        let characteristics: serde_json::Map<String, Value> = Characteristics::NAMES
+            .iter()
+            .map(|(key, _)| {
+                let value = raised.iter().find(|(raised, _)| raised == key).map_or(12, |(_, value)| *value);
+                (key.to_string(), json!({ "initial": 8, "advances": value - 8 }))
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "name": "Test",
+            "type": "character",
+            "img": null,
+            "items": items,
+            "system": {
+                "characteristics": characteristics,
+                "details": { "experience": { "total": total_ap, "spent": 0 } },
+            },
+        }))
+        .unwrap()
+    }
+
+    fn rules_of(report: &ValidationReport) -> Vec<(ValidationRule, &str)> {
+        report.findings.iter().map(|finding| (finding.rule, finding.subject.as_str())).collect()
+    }
+
+    #[test]
+    fn skill_maximum_is_the_highest_check_characteristic_plus_two() {
+        let character = hero(1100, &[("mu", 14)], vec![
+            skill("Klettern", 16, ["mu", "ge", "kk"]),
+            skill("Kraftakt", 15, ["ko", "kk", "kk"]),
+        ]);
+        let report = ValidationReport::validate(&character, None, &ApCalculator::default());
+
+        let errors: Vec<&Finding> = report.findings.iter().filter(|f| f.rule == ValidationRule::SkillMaximum).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].subject, "Kraftakt");
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!(errors[0].message, "Value 15 exceeds the maximum of 14 (KK 12 + 2)");
+    }
+
+    #[test]
+    fn combat_technique_maximum_uses_the_highest_leading_attribute() {
+        let character = hero(1100, &[("kk", 13)], vec![
+            combat_technique("Hiebwaffen", 15, "kk"),
+            combat_technique("Schwerter", 15, "ge/kk"),
+            combat_technique("Dolche", 15, "ge"),
+        ]);
+        let report = ValidationReport::validate(&character, None, &ApCalculator::default());
+
+        let subjects: Vec<&str> = report.findings.iter()
+            .filter(|f| f.rule == ValidationRule::CombatTechniqueMaximum)
+            .map(|f| f.subject.as_str())
+            .collect();
+        assert_eq!(subjects, ["Dolche"]);
+    }
+
+    #[test]
+    fn creation_maximums_of_the_inferred_experience_level() {
+        // 1100 AP: Erfahren, characteristics at most 14 and summing up to at most 100, skills at most 10
+        let character = hero(1100, &[("mu", 15), ("kl", 14)], vec![skill("Klettern", 11, ["mu", "ge", "kk"])]);
+        let report = ValidationReport::validate(&character, None, &ApCalculator::default());
+
+        assert_eq!(report.experience_level(), Some("Erfahren"));
+        let findings = rules_of(&report);
+        assert!(findings.contains(&(ValidationRule::CreationCharacteristicMaximum, "Mut")));
+        assert!(!findings.contains(&(ValidationRule::CreationCharacteristicMaximum, "Klugheit")));
+        // 15 + 14 + 6 × 12 = 101
+        assert!(findings.contains(&(ValidationRule::CreationCharacteristicSum, "Characteristics")));
+        assert!(findings.contains(&(ValidationRule::CreationSkillMaximum, "Klettern")));
+        assert!(report.findings.iter().all(|f| f.rule != ValidationRule::CreationSkillMaximum || f.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn starting_ap_must_match_an_experience_level() {
+        let character = hero(1150, &[], Vec::new());
+
+        let confirmed = ValidationReport::validate(&character, Some(50), &ApCalculator::default());
+        assert!(!rules_of(&confirmed).iter().any(|(rule, _)| *rule == ValidationRule::StartingAp));
+
+        let unconfirmed = ValidationReport::validate(&character, Some(40), &ApCalculator::default());
+        assert!(rules_of(&unconfirmed).contains(&(ValidationRule::StartingAp, "Experience")));
+    }
+
+    #[test]
+    fn creation_ap_limits_of_advantages_and_disadvantages() {
+        let within = hero(1100, &[], vec![advantage("Glück", "advantage", 30), advantage("Pech", "disadvantage", -80)]);
+        assert!(ValidationReport::validate(&within, None, &ApCalculator::default()).findings.is_empty());
+
+        let beyond = hero(1100, &[], vec![
+            advantage("Glück", "advantage", 60),
+            advantage("Hohe Lebenskraft", "advantage", 21),
+            advantage("Pech", "disadvantage", -81),
+        ]);
+        let report = ValidationReport::validate(&beyond, None, &ApCalculator::default());
+        let findings = rules_of(&report);
+        assert!(findings.contains(&(ValidationRule::CreationAdvantageAp, "Advantages")));
+        assert!(findings.contains(&(ValidationRule::CreationDisadvantageAp, "Disadvantages")));
+    }
+
+    #[test]
+    fn duplicate_picks_of_the_same_type_and_name() {
+        let character = hero(1100, &[], vec![
+            advantage("Glück", "advantage", 30),
+            advantage("Glück", "advantage", 30),
+            // Same name, different type: not a duplicate
+            advantage("Glück", "specialability", 10),
+        ]);
+        let report = ValidationReport::validate(&character, None, &ApCalculator::default());
+
+        let duplicates: Vec<&Finding> = report.findings.iter().filter(|f| f.rule == ValidationRule::DuplicatePick).collect();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].message, "Picked 2 times as advantage");
+        assert!(report.has_errors());
+        // Errors come before warnings
+        assert_eq!(report.findings.first().map(|f| f.severity), Some(Severity::Error));
+    }
+
+    #[test]
+    fn findings_are_sorted_by_severity_then_rule() {
+        let mut special_ability = advantage("Waffenbegabung", "specialability", 10);
+        special_ability["system"]["StF"] = json!({ "value": "A" });
+        let character = hero(1100, &[], vec![
+            special_ability,
+            advantage("Glück", "advantage", 30),
+            skill("Kraftakt", 15, ["ko", "kk", "kk"]),
+        ]);
+        let mut compendium = CompendiumIndex::default();
+        let mut reference = advantage("Waffenbegabung", "specialability", 10);
+        reference["system"]["StF"] = json!({ "value": "B" });
+        compendium.add_documents(vec![reference, advantage("Glück", "advantage", 20)], "dsa5-core.coreabilities");
+        let calculator = ApCalculator::default().with_compendium(Some(&compendium));
+
+        // The compendium check finds the StF of the first item before the APValue of the second
+        let report = ValidationReport::validate(&character, None, &calculator);
+        let order: Vec<(Severity, ValidationRule)> = report.findings.iter().map(|f| (f.severity, f.rule)).collect();
+        assert_eq!(order, [
+            (Severity::Error, ValidationRule::SkillMaximum),
+            (Severity::Warning, ValidationRule::CreationSkillMaximum),
+            (Severity::Warning, ValidationRule::CompendiumApValue),
+            (Severity::Warning, ValidationRule::CompendiumImprovementCost),
+        ]);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::character::{Character, Item, ItemKind, Rules};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// An amount of money in Kreuzer, the smallest coin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Money {
+    pub kreuzer: i64,
+}
+
+impl Money {
+    pub fn from_kreuzer(kreuzer: i64) -> Self {
+        Self { kreuzer }
+    }
+
+    /// Convert an item price (in the price unit of the rules, Silbertaler by default)
+    pub fn from_price(price: f64, rules: &Rules) -> Self {
+        let unit = rules.wealth.price_unit_kreuzer;
+        Self::from_kreuzer((price * unit as f64).round() as i64)
+    }
+
+    /// The amount in the price unit (Silbertaler by default), e.g. for sorting and totals
+    pub fn in_price_unit(&self, rules: &Rules) -> f64 {
+        self.kreuzer as f64 / rules.wealth.price_unit_kreuzer as f64
+    }
+
+    /// Split into the coins of the rules, e.g. "3 D 4 S 2 H" (coins with 0 are left out)
+    pub fn format(&self, rules: &Rules) -> String {
+        let mut remaining = self.kreuzer.abs();
+        let mut parts = Vec::new();
+
+        for currency in rules.wealth.currencies.iter().filter(|currency| currency.kreuzer > 0) {
+            let count = remaining / currency.kreuzer;
+            remaining %= currency.kreuzer;
+            if count > 0 {
+                parts.push(format!("{} {}", count, currency.key));
+            }
+        }
+
+        let sign = if self.kreuzer < 0 { "−" } else { "" };
+        if parts.is_empty() {
+            "0".to_string()
+        } else {
+            format!("{}{}", sign, parts.join(" "))
+        }
+    }
+}
+
+impl std::ops::Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money::from_kreuzer(self.kreuzer + other.kreuzer)
+    }
+}
+
+impl std::iter::Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::default(), |total, money| total + money)
+    }
+}
+
+/// Coins of one currency the character owns
+#[derive(Debug, Clone, Serialize)]
+pub struct CoinCount {
+    pub key: String,
+    pub name: String,
+    pub quantity: i64,
+    pub value: Money,
+}
+
+/// Value of the equipment of one item type
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemTypeValue {
+    pub item_type: String,
+    pub count: usize,
+    pub value: Money,
+}
+
+/// Money and the value of the equipment
+#[derive(Debug, Clone, Serialize)]
+pub struct Wealth {
+    pub coins: Vec<CoinCount>,
+    pub money: Money,
+    /// `price × quantity`, grouped by item type (without money)
+    pub equipment: Vec<ItemTypeValue>,
+    pub equipment_value: Money,
+    /// `money` items whose currency is not known
+    pub unknown_coins: Vec<String>,
+}
+
+impl Wealth {
+    pub fn calculate(character: &Character, rules: &Rules) -> Self {
+        let mut coins: Vec<CoinCount> = rules.wealth.currencies
+            .iter()
+            .map(|currency| CoinCount {
+                key: currency.key.clone(),
+                name: currency.name.clone(),
+                quantity: 0,
+                value: Money::default(),
+            })
+            .collect();
+        let mut unknown_coins = Vec::new();
+
+        for item in character.items.iter().filter(|item| item.kind == ItemKind::Money) {
+            let quantity = quantity_of(item) as i64;
+            match rules.wealth.currencies.iter().position(|currency| currency.matcher.matches_item(item)) {
+                Some(index) => {
+                    coins[index].quantity += quantity;
+                    coins[index].value = Money::from_kreuzer(coins[index].quantity * rules.wealth.currencies[index].kreuzer);
+                }
+                None => unknown_coins.push(item.name.clone()),
+            }
+        }
+
+        let mut by_type: BTreeMap<&str, (usize, Money)> = BTreeMap::new();
+        for item in character.get_inventory().into_iter().filter(|item| item.kind != ItemKind::Money) {
+            let Some(value) = item_value(item, rules) else {
+                continue;
+            };
+            let entry = by_type.entry(item.kind.as_str()).or_default();
+            entry.0 += 1;
+            entry.1 = entry.1 + value;
+        }
+
+        let equipment: Vec<ItemTypeValue> = by_type
+            .into_iter()
+            .map(|(item_type, (count, value))| ItemTypeValue { item_type: item_type.to_string(), count, value })
+            .collect();
+
+        Self {
+            money: coins.iter().map(|coin| coin.value).sum(),
+            equipment_value: equipment.iter().map(|group| group.value).sum(),
+            coins,
+            equipment,
+            unknown_coins,
+        }
+    }
+
+    pub fn total(&self) -> Money {
+        self.money + self.equipment_value
+    }
+}
+
+/// Price of one unit, `None` if the item has no parseable price
+pub fn item_price(item: &Item, rules: &Rules) -> Option<Money> {
+    item.data()
+        .equipment()
+        .and_then(|equipment| equipment.price.as_ref()?.trim().replace(',', ".").parse::<f64>().ok())
+        .map(|price| Money::from_price(price, rules))
+}
+
+/// `price × quantity`
+pub fn item_value(item: &Item, rules: &Rules) -> Option<Money> {
+    item_price(item, rules).map(|price| Money::from_kreuzer(price.kreuzer * quantity_of(item) as i64))
+}
+
+/// Quantity of a physical item, 1 if it has none
+fn quantity_of(item: &Item) -> i32 {
+    item.data().equipment().map_or(1, |equipment| equipment.quantity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn money_is_split_into_the_coins_of_the_rules() {
+        let rules = Rules::builtin();
+        assert_eq!(Money::from_kreuzer(1234).format(rules), "1 D 2 S 3 H 4 K");
+        assert_eq!(Money::from_kreuzer(2050).format(rules), "2 D 5 H");
+        assert_eq!(Money::from_kreuzer(-340).format(rules), "−3 S 4 H");
+        assert_eq!(Money::from_kreuzer(0).format(rules), "0");
+        assert_eq!(Money::from_price(12.5, rules), Money::from_kreuzer(1250));
+    }
+}
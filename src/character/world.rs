@@ -0,0 +1,350 @@
+use crate::character::leveldb;
+use crate::character::Character;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// DSA5 actor type of player characters; NPCs and creatures are "npc" and "creature"
+const PLAYER_CHARACTER_TYPE: &str = "character";
+
+/// How a world stores its documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldStore {
+    /// `data/actors.db`, one JSON document per line (Foundry VTT up to v10)
+    NeDb,
+    /// `data/actors/`, a LevelDB store with embedded documents stored separately (Foundry VTT v11+)
+    LevelDb,
+}
+
+impl WorldStore {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorldStore::NeDb => "NeDB",
+            WorldStore::LevelDb => "LevelDB",
+        }
+    }
+}
+
+/// An actor of a world, with its embedded items as in an actor export
+#[derive(Debug, Clone)]
+pub struct WorldActor {
+    pub id: String,
+    pub name: String,
+    /// Actor type ("character", "npc", "creature")
+    pub actor_type: String,
+    pub source: Value,
+}
+
+impl WorldActor {
+    pub fn is_player_character(&self) -> bool {
+        self.actor_type == PLAYER_CHARACTER_TYPE
+    }
+
+    /// The actor as a pretty-printed Foundry actor export
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.source)?)
+    }
+
+    pub fn character(&self) -> anyhow::Result<Character> {
+        Ok(serde_json::from_value(self.source.clone())?)
+    }
+}
+
+/// The actors of a local Foundry VTT world, read directly from its database
+#[derive(Debug, Clone)]
+pub struct FoundryWorld {
+    /// Title from `world.json`, or the name of the directory
+    pub title: String,
+    /// The actor database that was read
+    pub path: PathBuf,
+    pub store: WorldStore,
+    /// All actors, sorted by name
+    pub actors: Vec<WorldActor>,
+    /// Documents that could not be read
+    pub warnings: Vec<String>,
+}
+
+impl FoundryWorld {
+    /// Open a world from its directory, its `data` directory, `actors.db` or the `actors` LevelDB store.
+    /// If a world has both, the LevelDB store is used: `actors.db` is only left over from before the v11 migration.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let (database, store) = Self::find_actor_database(path)
+            .ok_or_else(|| anyhow::anyhow!("No actor database (data/actors.db or data/actors) found in {}", path.display()))?;
+
+        let mut warnings = Vec::new();
+        let documents = match store {
+            WorldStore::NeDb => read_nedb(&std::fs::read_to_string(&database)?, &mut warnings),
+            WorldStore::LevelDb => read_leveldb(&database, "actors", &mut warnings)?,
+        };
+
+        let mut actors: Vec<WorldActor> = documents
+            .into_iter()
+            .filter_map(|mut source| {
+                migrate_v9_document(&mut source);
+                let id = source.get("_id").and_then(Value::as_str)?.to_string();
+                let name = source.get("name").and_then(Value::as_str).unwrap_or(&id).to_string();
+                let actor_type = source.get("type").and_then(Value::as_str).unwrap_or_default().to_string();
+                Some(WorldActor { id, name, actor_type, source })
+            })
+            .collect();
+        actors.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()).then_with(|| a.id.cmp(&b.id)));
+
+        Ok(Self {
+            title: Self::world_title(&database),
+            path: database,
+            store,
+            actors,
+            warnings,
+        })
+    }
+
+    pub fn player_characters(&self) -> impl Iterator<Item = &WorldActor> {
+        self.actors.iter().filter(|actor| actor.is_player_character())
+    }
+
+    fn find_actor_database(path: &Path) -> Option<(PathBuf, WorldStore)> {
+        if path.is_file() {
+            return Some((path.to_path_buf(), WorldStore::NeDb));
+        }
+        if path.join("CURRENT").is_file() {
+            return Some((path.to_path_buf(), WorldStore::LevelDb));
+        }
+
+        [path.join("data"), path.to_path_buf()].into_iter().find_map(|data| {
+            if data.join("actors").join("CURRENT").is_file() {
+                Some((data.join("actors"), WorldStore::LevelDb))
+            } else if data.join("actors.db").is_file() {
+                Some((data.join("actors.db"), WorldStore::NeDb))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Title from the `world.json` next to the `data` directory of the database
+    fn world_title(database: &Path) -> String {
+        let world_dir = database.ancestors().find(|dir| dir.join("world.json").is_file());
+        let title = world_dir
+            .and_then(|dir| std::fs::read_to_string(dir.join("world.json")).ok())
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            .and_then(|json| json.get("title").and_then(Value::as_str).map(str::to_string));
+
+        title.unwrap_or_else(|| {
+            let dir = world_dir.or_else(|| database.parent().and_then(Path::parent)).unwrap_or(database);
+            dir.file_name().unwrap_or_default().to_string_lossy().to_string()
+        })
+    }
+}
+
+/// NeDB appends every change as a line: the last line of an id wins and `$$deleted` removes the document
+pub(crate) fn read_nedb(content: &str, warnings: &mut Vec<String>) -> Vec<Value> {
+    let mut documents: Vec<Value> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let document: Value = match serde_json::from_str(line) {
+            Ok(document) => document,
+            Err(e) => {
+                warnings.push(format!("Line {} skipped: {}", index + 1, e));
+                continue;
+            }
+        };
+        if document.get("$$indexCreated").is_some() {
+            continue;
+        }
+        let Some(id) = document.get("_id").and_then(Value::as_str).map(str::to_string) else {
+            continue;
+        };
+
+        if document.get("$$deleted").and_then(Value::as_bool).unwrap_or(false) {
+            if let Some(position) = positions.remove(&id) {
+                documents[position] = Value::Null;
+            }
+        } else if let Some(&position) = positions.get(&id) {
+            documents[position] = document;
+        } else {
+            positions.insert(id, documents.len());
+            documents.push(document);
+        }
+    }
+
+    documents.retain(|document| !document.is_null());
+    documents
+}
+
+/// Documents of `collection` ("actors", "items", ...) in a LevelDB store.
+/// Keys are `!actors!<actor id>` for actors and `!actors.items!<actor id>.<item id>` for their items (likewise effects,
+/// and `!actors.items.effects!<actor id>.<item id>.<effect id>` one level deeper). The embedded collections of a document
+/// only hold the ids; the documents are put back in their place as in an export.
+pub(crate) fn read_leveldb(dir: &Path, collection: &str, warnings: &mut Vec<String>) -> anyhow::Result<Vec<Value>> {
+    let mut documents = Vec::new();
+    let mut embedded: HashMap<(String, String), Value> = HashMap::new();
+
+    for (key, value) in leveldb::read_store(dir)? {
+        let key = String::from_utf8_lossy(&key);
+        let Some((key_collection, id_path)) = key.strip_prefix('!').and_then(|key| key.split_once('!')) else {
+            continue;
+        };
+        let document: Value = match serde_json::from_slice(&value) {
+            Ok(document) => document,
+            Err(e) => {
+                warnings.push(format!("Document {} skipped: {}", key, e));
+                continue;
+            }
+        };
+        if key_collection == collection {
+            documents.push((id_path.to_string(), document));
+        } else {
+            embedded.insert((key_collection.to_string(), id_path.to_string()), document);
+        }
+    }
+
+    let embedded_collections: HashSet<String> = embedded.keys().map(|(collection, _)| collection.clone()).collect();
+    Ok(documents
+        .into_iter()
+        .map(|(id, mut document)| {
+            restore_embedded(&mut document, collection, &id, &embedded_collections, &mut embedded, warnings);
+            document
+        })
+        .collect())
+}
+
+/// Replace the id lists of the embedded collections of `document` with the documents.
+/// A field is an embedded collection if the store has documents of `<collection>.<field>` (`embedded_collections`);
+/// all other fields are left as they are. Ids without a document in the store are dropped with a warning.
+fn restore_embedded(
+    document: &mut Value,
+    collection: &str,
+    id_path: &str,
+    embedded_collections: &HashSet<String>,
+    embedded: &mut HashMap<(String, String), Value>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(fields) = document.as_object_mut() else {
+        return;
+    };
+
+    for (field, value) in fields.iter_mut() {
+        let child_collection = format!("{}.{}", collection, field);
+        if !embedded_collections.contains(&child_collection) {
+            continue;
+        }
+        let Some(ids) = value.as_array().filter(|ids| ids.iter().all(Value::is_string)) else {
+            continue;
+        };
+        let children: Vec<Value> = ids
+            .iter()
+            .filter_map(Value::as_str)
+            .filter_map(|id| {
+                let child_path = format!("{}.{}", id_path, id);
+                let Some(mut child) = embedded.remove(&(child_collection.clone(), child_path.clone())) else {
+                    warnings.push(format!("Embedded document {} of {} not found in the database", child_path, child_collection));
+                    return None;
+                };
+                restore_embedded(&mut child, &child_collection, &child_path, embedded_collections, embedded, warnings);
+                Some(child)
+            })
+            .collect();
+        *value = Value::Array(children);
+    }
+}
+
+/// Worlds last saved by Foundry VTT v9 or older store `system` as `data`
+pub(crate) fn migrate_v9_document(document: &mut Value) {
+    let Some(object) = document.as_object_mut() else {
+        return;
+    };
+    if !object.contains_key("system") {
+        if let Some(data) = object.remove("data") {
+            object.insert("system".to_string(), data);
+        }
+    }
+    if let Some(items) = object.get_mut("items").and_then(Value::as_array_mut) {
+        items.iter_mut().for_each(migrate_v9_document);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ids(documents: &[Value]) -> Vec<&str> {
+        documents.iter().filter_map(|document| document.get("_id").and_then(Value::as_str)).collect()
+    }
+
+    #[test]
+    fn read_nedb_keeps_the_last_line_of_an_id_and_drops_deleted_documents() {
+        let content = [
+            r#"{"_id":"a","name":"Alrik"}"#,
+            r#"{"_id":"b","name":"Bosper"}"#,
+            r#"{"$$indexCreated":{"fieldName":"name"}}"#,
+            "",
+            r#"{"_id":"a","name":"Alrik von Gareth"}"#,
+            r#"{"_id":"b","$$deleted":true}"#,
+            r#"{"_id":"c","name":"Cordovan"}"#,
+            "not json",
+        ]
+        .join("\n");
+        let mut warnings = Vec::new();
+        let documents = read_nedb(&content, &mut warnings);
+
+        assert_eq!(ids(&documents), ["a", "c"]);
+        assert_eq!(documents[0]["name"], "Alrik von Gareth");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Line 8 skipped"));
+    }
+
+    #[test]
+    fn read_nedb_adds_a_document_again_after_its_deletion() {
+        let content = [r#"{"_id":"a","name":"Alrik"}"#, r#"{"_id":"a","$$deleted":true}"#, r#"{"_id":"a","name":"Alrik II"}"#].join("\n");
+        let documents = read_nedb(&content, &mut Vec::new());
+
+        assert_eq!(documents, [json!({ "_id": "a", "name": "Alrik II" })]);
+    }
+
+    #[test]
+    fn restore_embedded_replaces_only_collections_with_documents_in_the_store() {
+        let mut actor = json!({
+            "_id": "actor",
+            "items": ["sword", "lost"],
+            "effects": [],
+            "flags": { "tags": ["x"] },
+            "tags": ["hero", "mage"],
+        });
+        let mut embedded = HashMap::from([
+            (("actors.items".to_string(), "actor.sword".to_string()), json!({ "_id": "sword", "effects": ["glow"] })),
+            (("actors.items.effects".to_string(), "actor.sword.glow".to_string()), json!({ "_id": "glow" })),
+        ]);
+        let embedded_collections: HashSet<String> = embedded.keys().map(|(collection, _)| collection.clone()).collect();
+        let mut warnings = Vec::new();
+
+        restore_embedded(&mut actor, "actors", "actor", &embedded_collections, &mut embedded, &mut warnings);
+
+        assert_eq!(actor["items"], json!([{ "_id": "sword", "effects": [{ "_id": "glow" }] }]));
+        assert_eq!(actor["effects"], json!([]));
+        assert_eq!(actor["tags"], json!(["hero", "mage"]));
+        assert_eq!(actor["flags"], json!({ "tags": ["x"] }));
+        assert_eq!(warnings, ["Embedded document actor.lost of actors.items not found in the database"]);
+        assert!(embedded.is_empty());
+    }
+
+    #[test]
+    fn migrate_v9_document_renames_data_to_system_in_the_actor_and_its_items() {
+        let mut actor = json!({
+            "_id": "actor",
+            "data": { "status": {} },
+            "items": [{ "_id": "item", "data": { "step": { "value": 1 } } }, { "_id": "new", "system": {}, "data": "kept" }],
+        });
+        migrate_v9_document(&mut actor);
+
+        assert_eq!(actor, json!({
+            "_id": "actor",
+            "items": [{ "_id": "item", "system": { "step": { "value": 1 } } }, { "_id": "new", "system": {}, "data": "kept" }],
+            "system": { "status": {} },
+        }));
+    }
+}
@@ -0,0 +1,721 @@
+use crate::character::{ApCalculator, ApReport, ChangeKind, Character, CharacterDiff, CharacterFormat, CompendiumIndex, DiffEntry, ExperienceLevelCheck, FoundryWorld, OptolithImport, Rules, SourceComparison, Severity, ValidationReport, VerificationStatus, DEFAULT_RULES_JSON};
+use serde::Serialize;
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage:
+  dsa5-analyzer                                   Start the graphical interface
+  dsa5-analyzer analyze <file> [--format <fmt>] [--rules <rules>] [--compendium <path>]
+                                                  Compare calculated AP with Foundry's spent AP
+  dsa5-analyzer validate <file> [--format <fmt>] [--rules <rules>] [--awarded <ap>] [--compendium <path>]
+                                                  Check the character against the rules (maximums, creation limits)
+  dsa5-analyzer diff <old> <new> [--format <fmt>] [--rules <rules>]
+                                                  Show what changed between two exports
+  dsa5-analyzer crosscheck <optolith> <foundry> [--format <fmt>] [--rules <rules>]
+                                                  Compare an Optolith hero with its Foundry export
+  dsa5-analyzer world <dir> [--all] [--format <fmt>]
+                                                  Analyze all player characters of a local Foundry world
+  dsa5-analyzer rules                             Print the built-in rules file
+  dsa5-analyzer help                              Show this help
+
+Character files can be Foundry VTT actor exports or Optolith heroes (detected automatically).
+
+Options:
+  --format <fmt>    Output format: text (default) or json
+  --rules <rules>   JSON rules file merged over the built-in rules (house rules, errata, Optolith data files)
+  --awarded <ap>    AP awarded since creation, to infer and confirm the experience level (validate)
+  --compendium <path>
+                    DSA5 compendium packs (a pack, module or modules directory) to check the APValue, highest step
+                    and StF of advantages, disadvantages and special abilities against (validate), and to recognize
+                    renamed items with special AP rules by their compendium entry (analyze, validate)
+  --all             Include NPCs and creatures (world)
+
+Exit codes (analyze):
+  0  calculated AP matches Foundry's spent AP
+  1  AP mismatch
+  2  usage or file error
+  3  the file records no spent AP (e.g. an Optolith hero), nothing to compare
+
+Exit codes (validate):
+  0  no errors (warnings only)
+  1  at least one rule error
+  2  usage or file error
+
+Exit codes (world):
+  0  calculated AP matches Foundry's spent AP for every actor
+  1  AP mismatch for at least one actor
+  2  usage or database error
+  3  no mismatch, but at least one actor records no spent AP
+
+Exit codes (crosscheck):
+  0  both files have the same values and AP
+  1  values or AP differ
+  2  usage or file error";
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Result of the headless AP analysis, printed as text or JSON
+#[derive(Debug, Serialize)]
+struct AnalysisSummary {
+    character: String,
+    calculated_spent_ap: i32,
+    /// `None` if the file records no spent AP
+    foundry_spent_ap: Option<i32>,
+    difference: Option<i32>,
+    result: VerificationStatus,
+    report: ApReport,
+}
+
+/// Result of `world`: the AP analysis of every actor
+#[derive(Debug, Serialize)]
+struct WorldSummary {
+    world: String,
+    database: String,
+    characters: Vec<AnalysisSummary>,
+}
+
+/// Entry point for the command-line mode. `args` excludes the program name.
+pub fn run(args: &[String]) -> ExitCode {
+    match args.first().map(String::as_str) {
+        Some("analyze") => match parse_analyze_args(&args[1..]) {
+            Ok(options) => analyze(&options),
+            Err(message) => usage_error(&message),
+        },
+        Some("validate") => match parse_analyze_args(&args[1..]) {
+            Ok(options) => validate(&options),
+            Err(message) => usage_error(&message),
+        },
+        Some("diff") => match parse_file_pair_args(&args[1..], "diff requires an old and a new character file") {
+            Ok(options) => diff(&options),
+            Err(message) => usage_error(&message),
+        },
+        Some("crosscheck") => match parse_file_pair_args(&args[1..], "crosscheck requires an Optolith and a Foundry file") {
+            Ok(options) => cross_check(&options),
+            Err(message) => usage_error(&message),
+        },
+        Some("world") => match parse_world_args(&args[1..]) {
+            Ok((path, include_all, format)) => world(path, include_all, format),
+            Err(message) => usage_error(&message),
+        },
+        Some("rules") => {
+            print!("{}", DEFAULT_RULES_JSON);
+            ExitCode::SUCCESS
+        }
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Some(other) => usage_error(&format!("Unknown command '{}'", other)),
+        None => usage_error("Missing command"),
+    }
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("Error: {}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
+
+/// Options of `analyze` and `validate`
+struct AnalyzeOptions<'a> {
+    path: &'a Path,
+    format: OutputFormat,
+    rules: Option<&'a Path>,
+    awarded_ap: Option<i32>,
+    compendium: Option<&'a Path>,
+}
+
+fn parse_analyze_args(args: &[String]) -> Result<AnalyzeOptions<'_>, String> {
+    let mut path = None;
+    let mut format = OutputFormat::Text;
+    let mut rules = None;
+    let mut awarded_ap = None;
+    let mut compendium = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = parse_format(iter.next())?,
+            "--rules" => {
+                rules = match iter.next() {
+                    Some(rules) => Some(Path::new(rules)),
+                    None => return Err("--rules requires a file".to_string()),
+                };
+            }
+            "--compendium" => {
+                compendium = match iter.next() {
+                    Some(compendium) => Some(Path::new(compendium)),
+                    None => return Err("--compendium requires a pack or directory".to_string()),
+                };
+            }
+            "--awarded" => {
+                awarded_ap = match iter.next().map(|value| value.parse::<i32>()) {
+                    Some(Ok(ap)) => Some(ap),
+                    Some(Err(_)) => return Err("--awarded requires a number of AP".to_string()),
+                    None => return Err("--awarded requires a value".to_string()),
+                };
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let path = path.ok_or_else(|| "Missing character file".to_string())?;
+    Ok(AnalyzeOptions { path: Path::new(path), format, rules, awarded_ap, compendium })
+}
+
+/// Options of `diff` and `crosscheck`
+struct FilePairOptions<'a> {
+    first: &'a Path,
+    second: &'a Path,
+    format: OutputFormat,
+    rules: Option<&'a Path>,
+}
+
+fn parse_file_pair_args<'a>(args: &'a [String], missing: &str) -> Result<FilePairOptions<'a>, String> {
+    let mut paths = Vec::new();
+    let mut format = OutputFormat::Text;
+    let mut rules = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = parse_format(iter.next())?,
+            "--rules" => {
+                rules = match iter.next() {
+                    Some(rules) => Some(Path::new(rules)),
+                    None => return Err("--rules requires a file".to_string()),
+                };
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+            _ if paths.len() < 2 => paths.push(Path::new(arg)),
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    match paths[..] {
+        [first, second] => Ok(FilePairOptions { first, second, format, rules }),
+        _ => Err(missing.to_string()),
+    }
+}
+
+/// World directory, `--all` and `--format`, for `world`
+fn parse_world_args(args: &[String]) -> Result<(&Path, bool, OutputFormat), String> {
+    let mut path = None;
+    let mut include_all = false;
+    let mut format = OutputFormat::Text;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => format = parse_format(iter.next())?,
+            "--all" => include_all = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+            _ if path.is_none() => path = Some(Path::new(arg)),
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let path = path.ok_or_else(|| "Missing world directory".to_string())?;
+    Ok((path, include_all, format))
+}
+
+fn parse_format(value: Option<&String>) -> Result<OutputFormat, String> {
+    match value.map(String::as_str) {
+        Some("text") => Ok(OutputFormat::Text),
+        Some("json") => Ok(OutputFormat::Json),
+        Some(other) => Err(format!("Unknown format '{}'", other)),
+        None => Err("--format requires a value".to_string()),
+    }
+}
+
+/// Load a Foundry export or an Optolith hero; what the Optolith import could not map is reported on stderr
+fn load_character(path: &Path, rules: &Rules) -> Option<Character> {
+    let loaded = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| match CharacterFormat::detect(&content)? {
+            CharacterFormat::Foundry => Character::from_json(&content),
+            CharacterFormat::Optolith => {
+                let import = OptolithImport::from_json(&content, rules)?;
+                for warning in &import.warnings {
+                    eprintln!("Optolith import: {}", warning);
+                }
+                Ok(import.character)
+            }
+        });
+    match loaded {
+        Ok(character) => Some(character),
+        Err(e) => {
+            eprintln!("Error loading character from {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> bool {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => {
+            println!("{}", json);
+            true
+        }
+        Err(e) => {
+            eprintln!("Error serializing result: {}", e);
+            false
+        }
+    }
+}
+
+/// The `--rules` file, if given, otherwise the built-in rules; `None` if the file cannot be loaded
+fn load_rules(rules_path: Option<&Path>) -> Option<Rules> {
+    let Some(rules_path) = rules_path else {
+        return Some(Rules::builtin().clone());
+    };
+
+    match Rules::from_file(rules_path) {
+        Ok(rules) => Some(rules),
+        Err(e) => {
+            eprintln!("Error loading rules from {}: {}", rules_path.display(), e);
+            None
+        }
+    }
+}
+
+/// The `--compendium` packs, if given; `Err` if they cannot be loaded
+fn load_compendium(options: &AnalyzeOptions) -> Result<Option<CompendiumIndex>, ExitCode> {
+    let Some(compendium_path) = options.compendium else {
+        return Ok(None);
+    };
+
+    match CompendiumIndex::load(compendium_path) {
+        Ok(compendium) => {
+            for warning in &compendium.warnings {
+                eprintln!("Compendium: {}", warning);
+            }
+            Ok(Some(compendium))
+        }
+        Err(e) => {
+            eprintln!("Error loading compendium from {}: {}", compendium_path.display(), e);
+            Err(ExitCode::from(2))
+        }
+    }
+}
+
+fn analyze(options: &AnalyzeOptions) -> ExitCode {
+    let Some(rules) = load_rules(options.rules) else {
+        return ExitCode::from(2);
+    };
+    let compendium = match load_compendium(options) {
+        Ok(compendium) => compendium,
+        Err(code) => return code,
+    };
+
+    let Some(character) = load_character(options.path, &rules) else {
+        return ExitCode::from(2);
+    };
+
+    let calculator = ApCalculator::new(&rules).with_compendium(compendium.as_ref());
+    let summary = summarize(&character, &calculator);
+
+    match options.format {
+        OutputFormat::Text => print_text(&summary),
+        OutputFormat::Json => {
+            if !print_json(&summary) {
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    verification_exit_code([summary.result])
+}
+
+/// 1 if any AP check failed, otherwise 3 if any file records no spent AP
+fn verification_exit_code(results: impl IntoIterator<Item = VerificationStatus>) -> ExitCode {
+    let results: Vec<VerificationStatus> = results.into_iter().collect();
+    if results.iter().any(|result| matches!(result, VerificationStatus::Mismatch(_))) {
+        ExitCode::FAILURE
+    } else if results.contains(&VerificationStatus::Unknown) {
+        ExitCode::from(3)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn summarize(character: &Character, calculator: &ApCalculator) -> AnalysisSummary {
+    let report = calculator.build_report(character);
+
+    AnalysisSummary {
+        character: character.name.clone(),
+        calculated_spent_ap: report.total,
+        foundry_spent_ap: report.foundry_spent,
+        difference: report.difference(),
+        result: report.status(),
+        report,
+    }
+}
+
+fn print_text(summary: &AnalysisSummary) {
+    println!("Character: {}", summary.character);
+    println!();
+    println!("AP by category:");
+
+    for category in &summary.report.categories {
+        println!("  {:<24} {:>6} AP", category.category, category.ap_cost);
+    }
+
+    if !summary.report.warnings.is_empty() {
+        println!();
+        println!("Calculation warnings:");
+        for warning in &summary.report.warnings {
+            println!("  {}: {}", warning.name, warning.reason);
+        }
+    }
+
+    println!();
+    println!("Calculated spent AP:      {:>6} AP", summary.calculated_spent_ap);
+    match summary.foundry_spent_ap {
+        Some(spent) => println!("Foundry VTT spent AP:     {:>6} AP", spent),
+        None => println!("Foundry VTT spent AP:     not recorded"),
+    }
+    println!();
+
+    match summary.result {
+        VerificationStatus::Match => println!("Result: match"),
+        VerificationStatus::Mismatch(difference) if difference > 0 => {
+            println!("Result: MISMATCH (Foundry shows {} AP less)", difference)
+        }
+        VerificationStatus::Mismatch(difference) => println!("Result: MISMATCH (Foundry shows {} AP more)", -difference),
+        VerificationStatus::Unknown => println!("Result: not recorded (the file has no spent AP to compare with)"),
+    }
+}
+
+fn validate(options: &AnalyzeOptions) -> ExitCode {
+    let Some(rules) = load_rules(options.rules) else {
+        return ExitCode::from(2);
+    };
+    let compendium = match load_compendium(options) {
+        Ok(compendium) => compendium,
+        Err(code) => return code,
+    };
+
+    let Some(character) = load_character(options.path, &rules) else {
+        return ExitCode::from(2);
+    };
+
+    let calculator = ApCalculator::new(&rules).with_compendium(compendium.as_ref());
+    let report = ValidationReport::validate(&character, options.awarded_ap, &calculator);
+
+    match options.format {
+        OutputFormat::Text => {
+            print_validation_text(&report);
+            if let Some(compendium) = &compendium {
+                print_compendium_text(compendium, &character);
+            }
+        }
+        OutputFormat::Json => {
+            if !print_json(&report) {
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    if report.has_errors() {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Which items the compendium check could not verify
+fn print_compendium_text(compendium: &CompendiumIndex, character: &Character) {
+    println!("Compendium: {} entries from {} packs", compendium.entries.len(), compendium.packs.len());
+    let unmatched = compendium.unmatched(&character.items);
+    if !unmatched.is_empty() {
+        let names: Vec<&str> = unmatched.iter().map(|item| item.name.as_str()).collect();
+        println!("Not in the compendium (not checked): {}", names.join(", "));
+    }
+}
+
+fn print_validation_text(report: &ValidationReport) {
+    println!("Character: {}", report.character);
+    match &report.experience {
+        Some(experience) => print_experience_text(experience),
+        None => println!("Experience level: unknown (creation limits not checked)"),
+    }
+    println!();
+
+    if report.findings.is_empty() {
+        println!("No findings");
+    }
+
+    for finding in &report.findings {
+        println!("  {} [{}] {}: {}", finding.severity.symbol(), finding.rule.id(), finding.subject, finding.message);
+        println!("      {}", finding.reference);
+    }
+
+    println!();
+    println!("Errors: {}, warnings: {}", report.count(Severity::Error), report.count(Severity::Warning));
+}
+
+fn print_experience_text(experience: &ExperienceLevelCheck) {
+    let Some(level) = &experience.level else {
+        println!("Experience level: none below {} starting AP (creation limits not checked)", experience.start_ap);
+        return;
+    };
+
+    match (experience.awarded_ap, experience.confirmed) {
+        (Some(awarded_ap), Some(true)) => println!(
+            "Experience level: {} (total {} AP = {} starting AP + {} awarded AP)",
+            level.name, experience.total_ap, level.ap, awarded_ap
+        ),
+        (Some(awarded_ap), _) => println!(
+            "Experience level: {} (total {} AP − {} awarded AP = {} AP, not a starting AP)",
+            level.name, experience.total_ap, awarded_ap, experience.start_ap
+        ),
+        (None, _) => println!(
+            "Experience level: {} (from {} total AP; pass --awarded to confirm)",
+            level.name, experience.total_ap
+        ),
+    }
+
+    println!();
+    println!("Creation limits of {}:", level.name);
+    for cap in &experience.caps {
+        let current = cap.current.map(|value| value.to_string()).unwrap_or_else(|| "-".to_string());
+        let mut line = format!("  {:<24} {:>4} / {:>4}", cap.name, current, cap.limit);
+        if let Some(subject) = &cap.subject {
+            line.push_str(&format!("  {}", subject));
+        }
+        if cap.exceeded() {
+            line.push_str("  EXCEEDED");
+        }
+        println!("{}", line);
+    }
+}
+
+fn diff(options: &FilePairOptions) -> ExitCode {
+    let Some(rules) = load_rules(options.rules) else {
+        return ExitCode::from(2);
+    };
+    let (Some(old), Some(new)) = (load_character(options.first, &rules), load_character(options.second, &rules)) else {
+        return ExitCode::from(2);
+    };
+
+    let diff = CharacterDiff::between(&old, &new, &ApCalculator::new(&rules));
+
+    match options.format {
+        OutputFormat::Text => print_diff_text(&diff),
+        OutputFormat::Json => {
+            if !print_json(&diff) {
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn print_diff_text(diff: &CharacterDiff) {
+    if diff.old_character == diff.new_character {
+        println!("Character: {}", diff.old_character);
+    } else {
+        println!("Character: {} → {}", diff.old_character, diff.new_character);
+    }
+    println!();
+
+    if diff.changes.is_empty() {
+        println!("No changes");
+    }
+
+    print_changes(diff.changes.iter());
+
+    println!();
+    println!("Calculated spent AP:      {:>6} AP → {} AP", diff.old_total, diff.new_total);
+    println!("AP delta:                 {:>+6} AP", diff.ap_delta());
+}
+
+/// Changes grouped by category, with old → new value and AP delta
+fn print_changes<'a>(changes: impl Iterator<Item = &'a DiffEntry>) {
+    let mut current_category = None;
+    for change in changes {
+        if current_category != Some(&change.category) {
+            println!("{}:", change.category);
+            current_category = Some(&change.category);
+        }
+
+        let values = match (change.old_value, change.new_value) {
+            (Some(old), Some(new)) if old != new => format!("{} → {}", old, new),
+            (None, Some(new)) if change.kind == ChangeKind::Changed => format!("→ {}", new),
+            (Some(old), None) if change.kind == ChangeKind::Changed => format!("{} →", old),
+            _ => String::new(),
+        };
+
+        println!("  {} {:<36} {:<10} {:>+6} AP", change.kind.symbol(), change.name, values, change.ap_delta);
+    }
+}
+
+/// Analyze the actors of a world database; actors that cannot be read are reported on stderr and skipped
+fn world(path: &Path, include_all: bool, format: OutputFormat) -> ExitCode {
+    let world = match FoundryWorld::open(path) {
+        Ok(world) => world,
+        Err(e) => {
+            eprintln!("Error opening world {}: {}", path.display(), e);
+            return ExitCode::from(2);
+        }
+    };
+    for warning in &world.warnings {
+        eprintln!("{}: {}", world.path.display(), warning);
+    }
+
+    let calculator = ApCalculator::default();
+    let characters: Vec<AnalysisSummary> = world.actors
+        .iter()
+        .filter(|actor| include_all || actor.is_player_character())
+        .filter_map(|actor| match actor.character() {
+            Ok(character) => Some(summarize(&character, &calculator)),
+            Err(e) => {
+                eprintln!("Error loading actor {} ({}): {}", actor.name, actor.id, e);
+                None
+            }
+        })
+        .collect();
+
+    let summary = WorldSummary {
+        world: world.title.clone(),
+        database: format!("{} ({})", world.path.display(), world.store.name()),
+        characters,
+    };
+
+    match format {
+        OutputFormat::Text => print_world_text(&summary),
+        OutputFormat::Json => {
+            if !print_json(&summary) {
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    verification_exit_code(summary.characters.iter().map(|character| character.result))
+}
+
+fn print_world_text(summary: &WorldSummary) {
+    println!("World: {}", summary.world);
+    println!("Database: {}", summary.database);
+    println!();
+
+    if summary.characters.is_empty() {
+        println!("No characters");
+        return;
+    }
+
+    println!("  {:<32} {:>10} {:>10} {:>10}", "Character", "Calculated", "Foundry", "Difference");
+    for character in &summary.characters {
+        match (character.foundry_spent_ap, character.difference) {
+            (Some(spent), Some(difference)) => {
+                let result = if difference == 0 { "" } else { "  MISMATCH" };
+                println!("  {:<32} {:>7} AP {:>7} AP {:>+7} AP{}",
+                    character.character, character.calculated_spent_ap, spent, difference, result);
+            }
+            _ => println!("  {:<32} {:>7} AP {:>10} {:>10}", character.character, character.calculated_spent_ap, "-", "not recorded"),
+        }
+    }
+}
+
+/// Compare an Optolith hero with the Foundry export of the same hero; the files may be given in any order
+fn cross_check(options: &FilePairOptions) -> ExitCode {
+    let Some(rules) = load_rules(options.rules) else {
+        return ExitCode::from(2);
+    };
+    let load = |path: &Path| {
+        std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| Ok((CharacterFormat::detect(&content)?, content)))
+            .map_err(|e| eprintln!("Error loading character from {}: {}", path.display(), e))
+            .ok()
+    };
+    let (Some(first), Some(second)) = (load(options.first), load(options.second)) else {
+        return ExitCode::from(2);
+    };
+
+    let (optolith, foundry) = match (first, second) {
+        ((CharacterFormat::Optolith, optolith), (CharacterFormat::Foundry, foundry))
+        | ((CharacterFormat::Foundry, foundry), (CharacterFormat::Optolith, optolith)) => (optolith, foundry),
+        _ => return usage_error("crosscheck requires one Optolith hero and one Foundry export"),
+    };
+    let (optolith, foundry) = match (OptolithImport::from_json(&optolith, &rules), Character::from_json(&foundry)) {
+        (Ok(optolith), Ok(foundry)) => (optolith, foundry),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error loading character: {}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let comparison = SourceComparison::between(&optolith, &foundry, &ApCalculator::new(&rules));
+
+    match options.format {
+        OutputFormat::Text => print_cross_check_text(&comparison),
+        OutputFormat::Json => {
+            if !print_json(&comparison) {
+                return ExitCode::from(2);
+            }
+        }
+    }
+
+    if comparison.is_consistent() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+fn print_cross_check_text(comparison: &SourceComparison) {
+    println!("Optolith: {}", comparison.optolith_character);
+    println!("Foundry:  {}", comparison.foundry_character);
+    for warning in &comparison.import_warnings {
+        println!("  Optolith import: {}", warning);
+    }
+    println!();
+
+    println!("AP by category:               Optolith  Foundry  Difference");
+    for category in &comparison.categories {
+        let mut line = format!("  {:<26} {:>8} {:>8} {:>+11}",
+                               category.category, category.optolith_ap, category.foundry_ap, category.difference());
+        if let Some(suspect) = category.suspect {
+            line.push_str(&format!("  check {}", suspect.name()));
+        }
+        if category.placeholders > 0 {
+            line.push_str(&format!(" ({} unmapped Optolith ids)", category.placeholders));
+        }
+        if category.explained_ap != category.difference() {
+            line.push_str(&format!(" ({:+} AP not explained by the differences below)", category.difference() - category.explained_ap));
+        }
+        println!("{}", line);
+    }
+    println!();
+
+    let mut differences = comparison.value_differences().peekable();
+    if differences.peek().is_none() {
+        println!("No differences");
+    } else {
+        println!("Differences (− only in Optolith, + only in Foundry, ~ Optolith → Foundry):");
+        print_changes(differences);
+    }
+
+    if !comparison.unmatched.is_empty() {
+        println!();
+        println!("Not matched (− unmapped Optolith ids, + Foundry entries of the same type they may correspond to):");
+        print_changes(comparison.unmatched.iter());
+    }
+
+    println!();
+    println!("Calculated spent AP:      {:>6} AP (Optolith) / {} AP (Foundry)", comparison.optolith_total, comparison.foundry_total);
+    if let Some(spent) = comparison.foundry_spent {
+        println!("Foundry spent AP:         {:>6} AP", spent);
+    }
+    println!();
+    println!("Result: {}", if comparison.is_consistent() { "consistent" } else { "differences found" });
+}
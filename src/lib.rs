@@ -0,0 +1,30 @@
+//! Domain library of the DSA5 character analyzer.
+//!
+//! Parses DSA5 (Das Schwarze Auge) characters exported from Foundry VTT and
+//! recalculates the adventure points (AP) spent on them, independently of the
+//! graphical interface:
+//!
+//! ```no_run
+//! use dsa5_analyzer::{ApCalculator, Character};
+//!
+//! let character = Character::from_file("hero.json".as_ref())?;
+//! let spent = ApCalculator::default().calculate_total_spent_ap(&character);
+//! println!("{} spent {} AP", character.name, spent);
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! Build with `default-features = false` to leave out the GUI dependencies.
+//!
+//! **Breaking change in 2.0:** `ApCalculator` holds the rule tables it calculates with
+//! (`ApCalculator::new(&rules)`, `ApCalculator::default()` for the built-in rules) and
+//! its functions are methods; the rules are passed to everything that depends on them.
+//! `Experience::spent` and the energy `max` fields are `Option<i32>`, `None` if the
+//! source does not record them (Optolith heroes); `Experience::spent()` returns the option.
+//! `Item::item_type: String` was replaced by `Item::kind: ItemKind` (`kind.as_str()`
+//! gives the Foundry type string); the deprecated `Item::item_type()` accessor returns
+//! the same string for existing code. The typed view of the item's `system` data is
+//! available through `Item::data()`.
+
+pub mod character;
+
+pub use character::{ApCalculator, ApReport, ApReportEntry, ApWarning, Character, CharacterDiff, Item};
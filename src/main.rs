@@ -1,11 +1,46 @@
-#![windows_subsystem = "windows"] // do not display a console window on startup on Windows
+#![cfg_attr(feature = "gui", windows_subsystem = "windows")] // do not display a console window on startup on Windows
+#[cfg(feature = "gui")]
 mod app;
-mod character;
+mod cli;
+#[cfg(feature = "gui")]
 mod ui;
 
-use eframe::egui;
+use dsa5_analyzer::character;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    // Any command-line argument switches to headless mode
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        #[cfg(all(windows, feature = "gui"))]
+        attach_parent_console();
+        return cli::run(&args);
+    }
+
+    run_gui()
+}
+
+/// A GUI-subsystem executable starts without a console, so the output of the command-line mode would be lost.
+/// Write it to the console of the shell the program was started from, if there is one; redirected output is not affected.
+#[cfg(all(windows, feature = "gui"))]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // Fails if there is no parent console (e.g. started from Explorer); nothing to attach to then
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_gui() -> ExitCode {
+    use eframe::egui;
 
-fn main() -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1024.0, 768.0])
@@ -13,9 +48,22 @@ fn main() -> Result<(), eframe::Error> {
         ..Default::default()
     };
 
-    eframe::run_native(
+    let result = eframe::run_native(
         "DSA5 character analyzer",
         options,
         Box::new(|_cc| Ok(Box::new(app::App::new()))),
-    )
+    );
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error running application: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(feature = "gui"))]
+fn run_gui() -> ExitCode {
+    cli::run(&[])
 }
@@ -1,5 +1,6 @@
 use crate::character::Character;
-use crate::character::ApCalculator;
+use crate::character::{category, ApCalculator, ApCategoryTotal, ApReport};
+use crate::ui::FileDialog;
 use eframe::egui;
 use egui::Ui;
 use std::f32::consts::PI;
@@ -28,43 +29,60 @@ impl ApAnalysis {
         Self
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, character: &Character) {
-        ui.heading("🔮 AP Analysis");
+    pub fn show(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        // Calculate once per frame and share the result between all panels
+        let report = calculator.build_report(character);
+
+        ui.horizontal(|ui| {
+            ui.heading("🔮 AP Analysis");
+            if ui.button("💾 Export Report").on_hover_text("Save the AP report as JSON").clicked() {
+                self.export_report(&report, character);
+            }
+        });
         ui.separator();
 
         // Totally spent AP section
-        self.show_total_spent(ui, character);
+        self.show_total_spent(ui, &report);
+
+        // Entries that could not be costed (only shown if there are any)
+        self.show_calculation_warnings(ui, &report);
 
         ui.add_space(20.0);
 
         // AP by category section
-        self.show_ap_by_category(ui, character);
+        self.show_ap_by_category(ui, &report);
 
         // Collapsable detailed AP items section
-        self.show_ap_items_details(ui, character);
+        self.show_ap_items_details(ui, &report);
 
         // Add debug info
         ui.add_space(15.0);
-        self.show_debug_characteristics_breakdown(ui, character);
-        self.show_debug_skills_breakdown(ui, character);
-        self.show_debug_combat_skills_breakdown(ui, character);
-        self.show_debug_spells_rituals_breakdown(ui, character);
-        self.show_debug_liturgies_ceremonies_breakdown(ui, character);
+        self.show_debug_characteristics_breakdown(ui, &report);
+        self.show_debug_skills_breakdown(ui, &report);
+        self.show_debug_combat_skills_breakdown(ui, &report);
+        self.show_debug_spells_rituals_breakdown(ui, &report);
+        self.show_debug_liturgies_ceremonies_breakdown(ui, &report);
         self.show_debug_item_info(ui, character);
 
         ui.add_space(15.0);
     }
 
-    fn show_total_spent(&self, ui: &mut Ui, character: &Character) {
-        let calculated_total_spent_ap = ApCalculator::calculate_total_spent_ap(character);
-
-        let foundry_spent_ap = character.system
-            .as_ref()
-            .and_then(|s| s.details.as_ref())
-            .and_then(|d| d.experience.as_ref())
-            .map(|e| e.spent())
-            .unwrap_or(0);
+    fn export_report(&self, report: &ApReport, character: &Character) {
+        let default_name = format!("{} AP report.json", character.name);
+        if let Some(path) = FileDialog::new().save_file(&default_name) {
+            let result = serde_json::to_string_pretty(report)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| std::fs::write(&path, json).map_err(anyhow::Error::from));
+            match result {
+                Ok(_) => println!("Saved AP report to: {:?}", path),
+                Err(e) => eprintln!("Error saving AP report: {}", e),
+            }
+        }
+    }
 
+    fn show_total_spent(&self, ui: &mut Ui, report: &ApReport) {
+        let calculated_total_spent_ap = report.total;
+        let foundry_spent_ap = report.foundry_spent.unwrap_or(0);
         let difference = calculated_total_spent_ap - foundry_spent_ap;
 
         // AP Comparison Section - Prominent display
@@ -124,11 +142,44 @@ impl ApAnalysis {
             });
     }
 
-    fn show_ap_by_category(&self, ui: &mut Ui, character: &Character) {
+    fn show_calculation_warnings(&self, ui: &mut Ui, report: &ApReport) {
+        if report.warnings.is_empty() {
+            return;
+        }
+
+        ui.add_space(10.0);
+        egui::CollapsingHeader::new(
+            egui::RichText::new(format!("⚠ Calculation warnings ({})", report.warnings.len()))
+                .color(egui::Color32::from_rgb(200, 150, 50))
+        )
+            .id_salt("calculation_warnings")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.small("These entries were skipped or counted as 0 AP and may explain a mismatch.");
+                egui::ScrollArea::vertical()
+                    .id_salt("calculation_warnings_scroll")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("calculation_warnings_grid")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for warning in &report.warnings {
+                                    ui.strong(&warning.name);
+                                    ui.label(&warning.reason);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+    }
+
+    fn show_ap_by_category(&self, ui: &mut Ui, report: &ApReport) {
         ui.heading("📊 AP by Category");
         ui.separator();
 
-        let ap_by_category = ApCalculator::get_ap_by_category(character);
+        let ap_by_category = &report.categories;
 
         if ap_by_category.is_empty() {
             ui.label("No AP items found.");
@@ -139,7 +190,7 @@ impl ApAnalysis {
         ui.horizontal(|ui| {
             // Left side: Pie chart
             ui.vertical(|ui| {
-                let pie_slices = self.prepare_pie_data(&ap_by_category);
+                let pie_slices = self.prepare_pie_data(ap_by_category);
                 self.draw_pie_chart(ui, &pie_slices, 120.0);
             });
 
@@ -150,18 +201,11 @@ impl ApAnalysis {
                 ui.heading("📋 Breakdown");
                 ui.separator();
 
-                let mut sorted_categories: Vec<_> = ap_by_category.iter().collect();
-                // Sort by AP value descending, then by category name ascending for stable ordering
-                sorted_categories.sort_by(|a, b| {
-                    match b.1.cmp(a.1) {
-                        std::cmp::Ordering::Equal => a.0.cmp(b.0), // Secondary sort by name
-                        other => other,
-                    }
-                });
-
-                let total_ap: i32 = sorted_categories.iter().map(|(_, &ap)| ap).sum();
+                // Categories are sorted by AP value descending, then by category name
+                let total_ap = report.total;
 
-                for (category, &ap_value) in sorted_categories {
+                for (i, category) in ap_by_category.iter().enumerate() {
+                    let ap_value = category.ap_cost;
                     let percentage = if total_ap > 0 {
                         (ap_value as f32 / total_ap as f32) * 100.0
                     } else {
@@ -170,7 +214,7 @@ impl ApAnalysis {
 
                     ui.horizontal(|ui| {
                         // Color indicator (small rectangle)
-                        let color = self.get_category_color(category, &ap_by_category);
+                        let color = self.get_category_color(i);
                         let rect = egui::Rect::from_min_size(
                             ui.cursor().min,
                             egui::Vec2::new(12.0, 12.0)
@@ -178,7 +222,7 @@ impl ApAnalysis {
                         ui.painter().rect_filled(rect, 2.0, color);
                         ui.add_space(16.0);
 
-                        ui.label(format!("{}:", category));
+                        ui.label(format!("{}:", category.category));
                         ui.label(format!("{} AP ({:.1}%)", ap_value, percentage));
                     });
                 }
@@ -186,11 +230,9 @@ impl ApAnalysis {
         });
     }
 
-    fn prepare_pie_data(&self, ap_by_category: &std::collections::HashMap<String, i32>) -> Vec<PieSlice> {
-        let mut sorted_categories: Vec<_> = ap_by_category.iter().collect();
-        sorted_categories.sort_by(|a, b| b.1.cmp(a.1)); // Sort by value descending
-
-        let total_ap: i32 = sorted_categories.iter().map(|(_, &ap)| ap).sum();
+    fn prepare_pie_data(&self, sorted_categories: &[ApCategoryTotal]) -> Vec<PieSlice> {
+        // Categories are already sorted by value descending
+        let total_ap: i32 = sorted_categories.iter().map(|c| c.ap_cost).sum();
         if total_ap == 0 {
             return Vec::new();
         }
@@ -201,12 +243,13 @@ impl ApAnalysis {
         // Take top 3 categories
         let top_categories = sorted_categories.iter().take(3);
 
-        for (i, (category, &ap_value)) in top_categories.enumerate() {
+        for (i, category) in top_categories.enumerate() {
+            let ap_value = category.ap_cost;
             let percentage = ap_value as f32 / total_ap as f32;
             let angle_size = percentage * 2.0 * PI;
 
             slices.push(PieSlice {
-                label: category.to_string(),
+                label: category.category.clone(),
                 value: ap_value,
                 percentage: percentage * 100.0,
                 color: self.get_slice_color(i),
@@ -218,7 +261,7 @@ impl ApAnalysis {
         }
 
         // Calculate remaining AP for "Rest" slice
-        let remaining_ap = sorted_categories.iter().skip(3).map(|(_, &ap)| ap).sum();
+        let remaining_ap = sorted_categories.iter().skip(3).map(|c| c.ap_cost).sum();
 
         if remaining_ap > 0 {
             let percentage = remaining_ap as f32 / total_ap as f32;
@@ -338,22 +381,13 @@ impl ApAnalysis {
         }
     }
 
-    fn get_category_color(&self, category: &str, ap_by_category: &std::collections::HashMap<String, i32>) -> egui::Color32 {
-        let mut sorted_categories: Vec<_> = ap_by_category.iter().collect();
-        sorted_categories.sort_by(|a, b| b.1.cmp(a.1));
-
-        // Find the index of this category in the sorted list
-        for (i, (cat_name, _)) in sorted_categories.iter().enumerate() {
-            if *cat_name == category {
-                return if i < 3 {
-                    self.get_slice_color(i)
-                } else {
-                    self.get_slice_color(3) // "Others" color
-                };
-            }
+    /// Color of the category at `index` in the sorted category list (matches the pie slices)
+    fn get_category_color(&self, index: usize) -> egui::Color32 {
+        if index < 3 {
+            self.get_slice_color(index)
+        } else {
+            self.get_slice_color(3) // "Others" color
         }
-
-        self.get_slice_color(4) // Fallback
     }
 
     // OLD Method
@@ -361,7 +395,7 @@ impl ApAnalysis {
     //     ui.heading("📊 AP by Category");
     //     ui.separator();
     //
-    //     let ap_by_category = ApCalculator::get_ap_by_category(character);
+    //     let ap_by_category = calculator.get_ap_by_category(character);
     //
     //     if ap_by_category.is_empty() {
     //         ui.label("No AP items found.");
@@ -384,10 +418,10 @@ impl ApAnalysis {
     //     }
     // }
 
-    fn show_ap_items_details(&self, ui: &mut Ui, character: &Character) {
+    fn show_ap_items_details(&self, ui: &mut Ui, report: &ApReport) {
         ui.add_space(15.0);
         ui.collapsing("📝 AP Items Details", |ui| {
-            let ap_items = ApCalculator::get_ap_items_breakdown(character);
+            let ap_items: Vec<_> = report.ap_item_entries().collect();
 
             // Use the remaining available height for the scroll area
             let available_height = ui.available_height();
@@ -423,23 +457,23 @@ impl ApAnalysis {
                                             ui.label(&item.name);
                                         });
                                         row.col(|ui| {
-                                            ui.label(&item.raw_value);
+                                            ui.label(item.raw_ap_value.as_deref().unwrap_or_default());
                                         });
                                         row.col(|ui| {
-                                            ui.label(item.step.unwrap_or(1).to_string());
+                                            ui.label(item.value.unwrap_or(1).to_string());
                                         });
                                         row.col(|ui| {
-                                            ui.label(&item.calculation);
+                                            ui.label(&item.formula);
                                         });
                                         row.col(|ui| {
-                                            if item.was_excluded {
+                                            if item.excluded {
                                                 ui.colored_label(egui::Color32::GRAY, format!("({} AP - excluded)", item.ap_cost));
                                             } else {
                                                 ui.label(format!("{} AP", item.ap_cost));
                                             }
                                         });
                                         row.col(|ui| {
-                                            if item.was_excluded {
+                                            if item.excluded {
                                                 ui.colored_label(egui::Color32::RED, "(duplicate, lower step)");
                                             }
                                             ui.label(format!("[{}]", item.item_type));
@@ -452,104 +486,57 @@ impl ApAnalysis {
         });
     }
 
-    fn show_debug_skills_breakdown(&self, ui: &mut Ui, character: &Character) {
-        let skills_ap = ApCalculator::calculate_skills_ap(character);
-        if skills_ap > 0 {
-            ui.collapsing("Debug: Skills AP Breakdown", |ui| {
-                let skills_breakdown = ApCalculator::get_skills_ap_breakdown(character);
-
-                egui::ScrollArea::vertical()
-                    .id_salt("skills_ap_scroll")
-                    .max_height(200.0)
-                    .show(ui, |ui| {
-                        for (name, talent_value, stf, ap_cost) in skills_breakdown {
-                            ui.horizontal(|ui| {
-                                ui.label(&name);
-                                ui.label(format!("({}×{} = {})", talent_value, stf, ap_cost));
-                            });
-                        }
-                    });
-            });
-        }
+    fn show_debug_skills_breakdown(&self, ui: &mut Ui, report: &ApReport) {
+        self.show_debug_talent_breakdown(ui, report, category::SKILLS, "Debug: Skills AP Breakdown", "skills_ap_scroll");
     }
 
-    fn show_debug_combat_skills_breakdown(&self, ui: &mut Ui, character: &Character) {
-        let combat_skills_ap = ApCalculator::calculate_combat_skills_ap(character);
-        if combat_skills_ap > 0 {
-            ui.collapsing("Debug: Combat Skills AP Breakdown", |ui| {
-                let combat_skills_breakdown = ApCalculator::get_combat_skills_ap_breakdown(character);
-
-                egui::ScrollArea::vertical()
-                    .id_salt("combat_skills_ap_scroll")
-                    .max_height(200.0)
-                    .show(ui, |ui| {
-                        for (name, talent_value, stf, ap_cost) in combat_skills_breakdown {
-                            ui.horizontal(|ui| {
-                                ui.label(&name);
-                                ui.label(format!("({}×{} = {})", talent_value, stf, ap_cost));
-                            });
-                        }
-                    });
-            });
-        }
+    fn show_debug_combat_skills_breakdown(&self, ui: &mut Ui, report: &ApReport) {
+        self.show_debug_talent_breakdown(ui, report, category::COMBAT_SKILLS, "Debug: Combat Skills AP Breakdown", "combat_skills_ap_scroll");
     }
 
-    fn show_debug_characteristics_breakdown(&self, ui: &mut Ui, character: &Character) {
-        let characteristics_ap = ApCalculator::calculate_characteristics_ap(character);
-        if characteristics_ap > 0 {
+    fn show_debug_characteristics_breakdown(&self, ui: &mut Ui, report: &ApReport) {
+        if report.category_total(category::CHARACTERISTICS) > 0 {
             ui.collapsing("Debug: Characteristics AP Breakdown", |ui| {
-                let characteristics_breakdown = ApCalculator::get_characteristics_ap_breakdown(character);
-
                 egui::ScrollArea::vertical()
                     .id_salt("characteristics_ap_scroll")
                     .max_height(200.0)
                     .show(ui, |ui| {
-                        for (name, value, ap_cost) in characteristics_breakdown {
+                        for entry in report.entries_in(category::CHARACTERISTICS) {
                             ui.horizontal(|ui| {
-                                ui.label(&name);
-                                ui.label(format!("(Value: {}, Cost: {} AP)", value, ap_cost));
+                                ui.label(&entry.name);
+                                ui.label(format!("(Value: {}, Cost: {} AP)", entry.value.unwrap_or_default(), entry.ap_cost))
+                                    .on_hover_text(&entry.formula);
                             });
                         }
                     });
             });
         }
     }
-    
-    fn show_debug_spells_rituals_breakdown(&self, ui: &mut Ui, character: &Character) {
-        let spells_rituals_ap = ApCalculator::calculate_spells_and_rituals_ap(character);
-        if spells_rituals_ap > 0 {
-            ui.collapsing("Debug: Spells & Rituals AP Breakdown", |ui| {
-                let spells_rituals_breakdown = ApCalculator::get_spells_and_rituals_ap_breakdown(character);
 
-                egui::ScrollArea::vertical()
-                    .id_salt("spells_rituals_ap_scroll")
-                    .max_height(200.0)
-                    .show(ui, |ui| {
-                        for (name, talent_value, stf, ap_cost) in spells_rituals_breakdown {
-                            ui.horizontal(|ui| {
-                                ui.label(&name);
-                                ui.label(format!("({}×{} = {} AP)", talent_value, stf, ap_cost));
-                            });
-                        }
-                    });
-            });
-        }
+    fn show_debug_spells_rituals_breakdown(&self, ui: &mut Ui, report: &ApReport) {
+        self.show_debug_talent_breakdown(ui, report, category::SPELLS_RITUALS, "Debug: Spells & Rituals AP Breakdown", "spells_rituals_ap_scroll");
     }
 
-    fn show_debug_liturgies_ceremonies_breakdown(&self, ui: &mut Ui, character: &Character) {
-        let liturgies_ceremonies_ap = ApCalculator::calculate_liturgies_and_ceremonies_ap(character);
-        if liturgies_ceremonies_ap > 0 {
-            ui.collapsing("Debug: Liturgies & Ceremonies AP Breakdown", |ui| {
-                let liturgies_ceremonies_breakdown = ApCalculator::get_liturgies_and_ceremonies_ap_breakdown(character);
+    fn show_debug_liturgies_ceremonies_breakdown(&self, ui: &mut Ui, report: &ApReport) {
+        self.show_debug_talent_breakdown(ui, report, category::LITURGIES_CEREMONIES, "Debug: Liturgies & Ceremonies AP Breakdown", "liturgies_ceremonies_ap_scroll");
+    }
 
+    /// Collapsible list of talent-like entries of one category: "name (value×StF = cost AP)"
+    fn show_debug_talent_breakdown(&self, ui: &mut Ui, report: &ApReport, category: &str, title: &str, scroll_id: &str) {
+        if report.category_total(category) > 0 {
+            ui.collapsing(title, |ui| {
                 egui::ScrollArea::vertical()
-                    .id_salt("liturgies_ceremonies_ap_scroll")
+                    .id_salt(scroll_id)
                     .max_height(200.0)
                     .show(ui, |ui| {
-                        for (name, talent_value, stf, ap_cost) in liturgies_ceremonies_breakdown {
+                        for entry in report.entries_in(category) {
                             ui.horizontal(|ui| {
-                                ui.label(&name);
-                                ui.label(format!("({}×{} = {} AP)", talent_value, stf, ap_cost));
+                                ui.label(&entry.name);
+                                ui.label(format!("({}×{} = {} AP)",
+                                                 entry.value.unwrap_or_default(),
+                                                 entry.stf.as_deref().unwrap_or("-"),
+                                                 entry.ap_cost))
+                                    .on_hover_text(&entry.formula);
                             });
                         }
                     });
@@ -557,7 +544,6 @@ impl ApAnalysis {
         }
     }
 
-
     fn show_debug_item_info(&self, ui: &mut egui::Ui, character: &Character) {
         ui.collapsing("Debug: Item Type Analysis", |ui| {
             // Count all item types
@@ -565,10 +551,10 @@ impl ApAnalysis {
             let mut types_with_ap = std::collections::HashMap::new();
 
             for item in &character.items {
-                *all_types.entry(item.item_type.clone()).or_insert(0) += 1;
+                *all_types.entry(item.kind.to_string()).or_insert(0) += 1;
 
                 if item.system.get_ap_value().is_some() {
-                    *types_with_ap.entry(item.item_type.clone()).or_insert(0) += 1;
+                    *types_with_ap.entry(item.kind.to_string()).or_insert(0) += 1;
                 }
             }
 
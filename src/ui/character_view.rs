@@ -1,4 +1,5 @@
-use crate::character::Character;
+use crate::character::{ApCalculator, Character, CheckProbability, CombatGear, CombatValues, DerivedValue, DerivedValueKind, DerivedValues, Encumbrance, ExperienceLevelCheck, Item, Severity, SkillCheck, ValidationReport, Wealth, FoundryExport, item_price, item_value, MAX_QUALITY_LEVEL};
+use crate::ui::{EditView, PlannerView};
 use eframe::egui;
 use egui_extras::{TableBuilder, Column};
 use std::collections::HashMap;
@@ -12,12 +13,40 @@ enum CharacterTab {
     Magic,
     Karma,
     Items,
+    Planner,
+    Edit,
+    Validation,
+}
+
+/// Column the inventory table is sorted by
+#[derive(Clone, Copy, PartialEq)]
+enum InventoryColumn {
+    Name,
+    Type,
+    Quantity,
+    Weight,
+    Price,
+    Value,
 }
 
 pub struct CharacterView {
     image_cache: HashMap<String, egui::TextureHandle>,
     selected_tab: CharacterTab,
     load_images: bool,
+    planner: PlannerView,
+    editor: EditView,
+    affordable_only: bool,
+    check_modifier: i32,
+    /// Skill check shown in the quality level popup
+    check_details: Option<(String, SkillCheck)>,
+    /// AP awarded since creation, used to infer the experience level from the starting AP
+    awarded_ap: i32,
+    awards_known: bool,
+    inventory_filter: String,
+    /// Item type shown in the inventory table, `None` for all
+    inventory_type: Option<String>,
+    inventory_sort: InventoryColumn,
+    inventory_sort_descending: bool,
 }
 
 impl CharacterView {
@@ -26,10 +55,22 @@ impl CharacterView {
             image_cache: HashMap::new(),
             selected_tab: CharacterTab::Overview,
             load_images: false, // Default to false for faster startup
+            planner: PlannerView::new(),
+            editor: EditView::new(),
+            affordable_only: false,
+            check_modifier: 0,
+            check_details: None,
+            awarded_ap: 0,
+            awards_known: false,
+            inventory_filter: String::new(),
+            inventory_type: None,
+            inventory_sort: InventoryColumn::Name,
+            inventory_sort_descending: false,
         }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, character: &Character) {
+    /// Returns whether the character was edited
+    pub fn show(&mut self, ui: &mut egui::Ui, character: &mut Character, export: Option<&FoundryExport>, calculator: &ApCalculator) -> bool {
         // Tab selection
         ui.horizontal(|ui| {
             ui.selectable_value(&mut self.selected_tab, CharacterTab::Overview, "Overview");
@@ -39,23 +80,34 @@ impl CharacterView {
             ui.selectable_value(&mut self.selected_tab, CharacterTab::Magic, "Magic");
             ui.selectable_value(&mut self.selected_tab, CharacterTab::Karma, "Karma");
             ui.selectable_value(&mut self.selected_tab, CharacterTab::Items, "Items");
+            ui.selectable_value(&mut self.selected_tab, CharacterTab::Planner, "Planner");
+            ui.selectable_value(&mut self.selected_tab, CharacterTab::Edit, "Edit");
+            ui.selectable_value(&mut self.selected_tab, CharacterTab::Validation, "Validation");
         });
 
         ui.separator();
 
+        let mut edited = false;
+
         // Show content based on selected tab
         match self.selected_tab {
-            CharacterTab::Overview => self.show_overview_tab(ui, character),
-            CharacterTab::System => self.show_system_tab(ui, character),
-            CharacterTab::Skills => self.show_skills_tab(ui, character),
-            CharacterTab::CombatSkills => self.show_combat_skills_tab(ui, character),
-            CharacterTab::Magic => self.show_magic_tab(ui, character),
-            CharacterTab::Karma => self.show_karma_tab(ui, character),
-            CharacterTab::Items => self.show_items_tab(ui, character),
+            CharacterTab::Overview => self.show_overview_tab(ui, character, calculator),
+            CharacterTab::System => self.show_system_tab(ui, character, calculator),
+            CharacterTab::Skills => self.show_skills_tab(ui, character, calculator),
+            CharacterTab::CombatSkills => self.show_combat_skills_tab(ui, character, calculator),
+            CharacterTab::Magic => self.show_magic_tab(ui, character, calculator),
+            CharacterTab::Karma => self.show_karma_tab(ui, character, calculator),
+            CharacterTab::Items => self.show_items_tab(ui, character, calculator),
+            CharacterTab::Planner => self.planner.show(ui, character, calculator),
+            CharacterTab::Edit => edited = self.editor.show(ui, character, export, calculator),
+            CharacterTab::Validation => self.show_validation_tab(ui, character, calculator),
         }
+
+        self.show_check_details_window(ui.ctx());
+        edited
     }
 
-    fn show_overview_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    fn show_overview_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
         // Image loading toggle and display
         if character.has_image() {
             ui.horizontal(|ui| {
@@ -89,6 +141,21 @@ impl CharacterView {
                 ui.label(&character.character_type);
                 ui.end_row();
 
+                ui.label("Species:");
+                match (character.species_name(), character.species(calculator.rules())) {
+                    (_, Some(species)) => {
+                        ui.label(format!("{} ({} AP)", species.name, species.ap_cost));
+                    }
+                    (Some(name), None) => {
+                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("{} (unknown species)", name))
+                            .on_hover_text("Species AP cost and base values cannot be applied");
+                    }
+                    (None, None) => {
+                        ui.label("-");
+                    }
+                }
+                ui.end_row();
+
                 ui.label("Total Items:");
                 ui.label(character.items.len().to_string());
                 ui.end_row();
@@ -152,9 +219,18 @@ impl CharacterView {
                             ui.end_row();
 
                             ui.label("Spent AP (Foundry VTT):");
-                            ui.label(experience.spent().to_string());
+                            ui.label(experience.spent().map_or_else(|| "not recorded".to_string(), |spent| spent.to_string()));
+                            ui.end_row();
+
+                            ui.label("AP awarded since creation:");
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.awards_known, "known");
+                                ui.add_enabled(self.awards_known, egui::DragValue::new(&mut self.awarded_ap).range(0..=experience.total()));
+                            });
                             ui.end_row();
                         });
+
+                    self.show_experience_level(ui, character, calculator);
                 }
             }
         }
@@ -162,7 +238,7 @@ impl CharacterView {
     }
 
 
-    fn show_system_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    fn show_system_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
 
         if let Some(system) = &character.system {
             egui::ScrollArea::vertical()
@@ -201,9 +277,14 @@ impl CharacterView {
                                                 ui.label(format!("{}:", name));
                                             });
                                             row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&char_value.nominal_value().to_string())
+                                                let label = ui.add(egui::Label::new(
+                                                    egui::RichText::new(char_value.value().to_string())
                                                 ));
+                                                if char_value.species_bonus() != 0 {
+                                                    label.on_hover_text(format!("{} + {} (species)",
+                                                                                char_value.nominal_value(),
+                                                                                char_value.species_bonus()));
+                                                }
                                             });
                                         });
                                     }
@@ -213,6 +294,38 @@ impl CharacterView {
                         ui.colored_label(egui::Color32::from_rgb(180, 100, 100), "⚠ No characteristics found");
                     }
 
+                    // Species base values
+                    if let Some(species) = character.species(calculator.rules()) {
+                        ui.add_space(20.0);
+                        ui.heading(format!("🧬 Species: {}", species.name));
+                        ui.separator();
+
+                        egui::Grid::new("species_base_values")
+                            .num_columns(2)
+                            .spacing([10.0, 5.0])
+                            .show(ui, |ui| {
+                                ui.label("AP Cost:");
+                                ui.label(species.ap_cost.to_string());
+                                ui.end_row();
+
+                                ui.label("LeP Base Value:");
+                                ui.label(species.life_points_base.to_string());
+                                ui.end_row();
+
+                                ui.label("SK Base Value:");
+                                ui.label(species.soul_power_base.to_string());
+                                ui.end_row();
+
+                                ui.label("ZK Base Value:");
+                                ui.label(species.toughness_base.to_string());
+                                ui.end_row();
+
+                                ui.label("GS:");
+                                ui.label(species.speed.to_string());
+                                ui.end_row();
+                            });
+                    }
+
                     // Status Values section with consistent table approach
                     ui.add_space(20.0);
                     ui.heading("🔋 Status Values");
@@ -244,34 +357,11 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&wounds.value().to_string())
+                                                egui::RichText::new(wounds.value().to_string())
                                             ));
                                         });
                                     });
 
-                                    if let Some(characteristics) = &system.characteristics {
-                                        let ko_value = characteristics
-                                            .ko
-                                            .as_ref()
-                                            .map(|ko| ko.nominal_value())
-                                            .unwrap_or_default();
-                                        let base_value = wounds.initial() + 2 * ko_value;
-
-                                        body.row(18.0, |mut row| {
-                                            row.col(|_ui| {}); // Empty first column
-                                            row.col(|ui| {
-                                                ui.label("Base Value (Tbd):");
-                                            });
-                                            row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&base_value.to_string())
-                                                        .strong()
-                                                        .color(egui::Color32::from_rgb(70, 130, 180))
-                                                )).on_hover_text("BV = 5 + 2*Ko (human; no advantages/disadvantages considered!)");
-                                            });
-                                        });
-                                    }
-
                                     body.row(18.0, |mut row| {
                                         row.col(|_ui| {}); // Empty first column
                                         row.col(|ui| {
@@ -279,7 +369,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&wounds.advances().to_string())
+                                                egui::RichText::new(wounds.advances().to_string())
                                             ));
                                         });
                                     });
@@ -291,7 +381,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&wounds.modifier().to_string())
+                                                egui::RichText::new(wounds.modifier().to_string())
                                                     .color(if wounds.modifier() > 0 {
                                                         egui::Color32::from_rgb(50, 150, 50)
                                                     } else if wounds.modifier() < 0 {
@@ -302,30 +392,6 @@ impl CharacterView {
                                             ));
                                         });
                                     });
-
-                                    if let Some(characteristics) = &system.characteristics {
-                                        let ko_value = characteristics
-                                            .ko
-                                            .as_ref()
-                                            .map(|ko| ko.nominal_value())
-                                            .unwrap_or_default();
-                                        let base_value = wounds.initial() + 2 * ko_value;
-                                        let max_value = base_value + wounds.advances() + wounds.modifier();
-
-                                        body.row(18.0, |mut row| {
-                                            row.col(|_ui| {}); // Empty first column
-                                            row.col(|ui| {
-                                                ui.label("Max Value (Tbd):");
-                                            });
-                                            row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&max_value.to_string())
-                                                        .strong()
-                                                        .color(egui::Color32::from_rgb(70, 130, 180))
-                                                )).on_hover_text("MV = BV + A + M (human; no advantages/disadvantages considered!)");
-                                            });
-                                        });
-                                    }
                                 }
 
                                 // Astral Energy (ASP)
@@ -342,34 +408,11 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&astral_energy.value().to_string())
+                                                egui::RichText::new(astral_energy.value().to_string())
                                             ));
                                         });
                                     });
 
-                                    if let Some(characteristics) = &system.characteristics {
-                                        let kl_value = characteristics
-                                            .kl
-                                            .as_ref()
-                                            .map(|kl| kl.nominal_value())
-                                            .unwrap_or_default();
-                                        // Formula for human wizard (other characters may vary)
-                                        let base_value = 20 + kl_value;
-
-                                        body.row(18.0, |mut row| {
-                                            row.col(|_ui| {}); // Empty first column
-                                            row.col(|ui| {
-                                                ui.label("Base Value (Tbd):");
-                                            });
-                                            row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&base_value.to_string())
-                                                        .color(egui::Color32::from_rgb(70, 130, 180))
-                                                )).on_hover_text("BV = 20 + Kl (human wizard assumed; no advantages/disadvantages considered!)");
-                                            });
-                                        });
-                                    }
-
                                     body.row(18.0, |mut row| {
                                         row.col(|_ui| {}); // Empty first column
                                         row.col(|ui| {
@@ -377,7 +420,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&astral_energy.advances().to_string())
+                                                egui::RichText::new(astral_energy.advances().to_string())
                                             ));
                                         });
                                     });
@@ -389,7 +432,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&astral_energy.permanent_loss().to_string())
+                                                egui::RichText::new(astral_energy.permanent_loss().to_string())
                                                     .color(if astral_energy.permanent_loss() > 0 {
                                                         egui::Color32::from_rgb(200, 80, 80)
                                                     } else {
@@ -406,7 +449,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&astral_energy.rebuy_points().to_string())
+                                                egui::RichText::new(astral_energy.rebuy_points().to_string())
                                                     .color(if astral_energy.rebuy_points() > 0 {
                                                         egui::Color32::from_rgb(200, 150, 50)
                                                     } else {
@@ -415,32 +458,6 @@ impl CharacterView {
                                             ));
                                         });
                                     });
-
-                                    if let Some(characteristics) = &system.characteristics {
-                                        let kl_value = characteristics
-                                            .kl
-                                            .as_ref()
-                                            .map(|kl| kl.nominal_value())
-                                            .unwrap_or_default();
-                                        // Formula for human wizard (other characters may vary)
-                                        let base_value = 20 + kl_value;
-                                        let loss_value = (astral_energy.rebuy_points() - astral_energy.permanent_loss()).min(0);
-                                        let max_value = base_value + astral_energy.advances() + loss_value;
-
-                                        body.row(18.0, |mut row| {
-                                            row.col(|_ui| {}); // Empty first column
-                                            row.col(|ui| {
-                                                ui.label("Max Value (Tbd):");
-                                            });
-                                            row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&max_value.to_string())
-                                                        .strong()
-                                                        .color(egui::Color32::from_rgb(70, 130, 180))
-                                                )).on_hover_text("MV = BV + A + min(RP - PL, 0) (human wizard assumed; no advantages/disadvantages considered!)");
-                                            });
-                                        });
-                                    }
                                 }
 
                                 // Karma Energy (KaP)
@@ -457,34 +474,11 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&karma_energy.value().to_string())
+                                                egui::RichText::new(karma_energy.value().to_string())
                                             ));
                                         });
                                     });
 
-                                    if let Some(characteristics) = &system.characteristics {
-                                        let in_value = characteristics
-                                            .in_
-                                            .as_ref()
-                                            .map(|in_| in_.nominal_value())
-                                            .unwrap_or_default();
-                                        // Formula for blessed ones with lead characteristic intuition (others vary)
-                                        let base_value = 20 + in_value;
-
-                                        body.row(18.0, |mut row| {
-                                            row.col(|_ui| {}); // Empty first column
-                                            row.col(|ui| {
-                                                ui.label("Base Value (Tbd):");
-                                            });
-                                            row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&base_value.to_string())
-                                                        .color(egui::Color32::from_rgb(70, 130, 180))
-                                                )).on_hover_text("BV = 20 + In (lead characteristic intuition assumed; no advanteges/disadvantes considered!)");
-                                            });
-                                        });
-                                    }
-
                                     body.row(18.0, |mut row| {
                                         row.col(|_ui| {}); // Empty first column
                                         row.col(|ui| {
@@ -492,7 +486,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&karma_energy.advances().to_string())
+                                                egui::RichText::new(karma_energy.advances().to_string())
                                             ));
                                         });
                                     });
@@ -504,7 +498,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&karma_energy.permanent_loss().to_string())
+                                                egui::RichText::new(karma_energy.permanent_loss().to_string())
                                                     .color(if karma_energy.permanent_loss() > 0 {
                                                         egui::Color32::from_rgb(200, 80, 80)
                                                     } else {
@@ -521,7 +515,7 @@ impl CharacterView {
                                         });
                                         row.col(|ui| {
                                             ui.add(egui::Label::new(
-                                                egui::RichText::new(&karma_energy.rebuy_points().to_string())
+                                                egui::RichText::new(karma_energy.rebuy_points().to_string())
                                                     .color(if karma_energy.rebuy_points() > 0 {
                                                         egui::Color32::from_rgb(200, 150, 50)
                                                     } else {
@@ -530,38 +524,17 @@ impl CharacterView {
                                             ));
                                         });
                                     });
-
-                                    if let Some(characteristics) = &system.characteristics {
-                                        let in_value = characteristics
-                                            .in_
-                                            .as_ref()
-                                            .map(|in_| in_.nominal_value())
-                                            .unwrap_or_default();
-                                        // Formula for blessed ones with lead characteristic intuition (others vary)
-                                        let base_value = 20 + in_value;
-                                        let loss_value = (karma_energy.rebuy_points() - karma_energy.permanent_loss()).min(0);
-                                        let max_value = base_value + karma_energy.advances() + loss_value;
-
-                                        body.row(18.0, |mut row| {
-                                            row.col(|_ui| {}); // Empty first column
-                                            row.col(|ui| {
-                                                ui.label("Max Value (Tbd):");
-                                            });
-                                            row.col(|ui| {
-                                                ui.add(egui::Label::new(
-                                                    egui::RichText::new(&max_value.to_string())
-                                                        .strong()
-                                                        .color(egui::Color32::from_rgb(70, 130, 180))
-                                                )).on_hover_text("MV = BV + A + min(RP - PL, 0) (lead characteristic intuition assumed; no advantages/disadvantages considered!)");
-                                            });
-                                        });
-                                    }
                                 }
                             });
                     } else {
                         ui.colored_label(egui::Color32::from_rgb(180, 100, 100), "⚠ No status values found");
                     }
 
+                    ui.add_space(20.0);
+                    ui.heading("📐 Derived Values");
+                    ui.separator();
+                    Self::show_derived_values(ui, character, calculator);
+
                 });
         } else {
             ui.vertical_centered(|ui| {
@@ -571,8 +544,11 @@ impl CharacterView {
         }
     }
 
-    fn show_skills_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    fn show_skills_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
         ui.heading("🎯 Skills");
+        let available_ap = self.show_affordable_filter(ui, character, calculator);
+        let check_modifier = self.show_check_modifier(ui);
+        let mut clicked_check = None;
         ui.separator();
 
         egui::ScrollArea::vertical()
@@ -586,6 +562,10 @@ impl CharacterView {
                     .column(Column::auto().at_least(90.0))  // Characteristics
                     .column(Column::auto().at_least(40.0))  // StF
                     .column(Column::auto().at_least(40.0))  // Talent Value
+                    .column(Column::auto().at_least(50.0))  // Next +1 cost
+                    .column(Column::auto().at_least(40.0))  // Max reachable
+                    .column(Column::auto().at_least(60.0))  // Success probability
+                    .column(Column::auto().at_least(40.0))  // Expected QS
                     .header(20.0, |mut header| {
                         header.col(|ui| {
                             ui.strong("Skill");
@@ -599,9 +579,11 @@ impl CharacterView {
                         header.col(|ui| {
                             ui.strong("Value");
                         });
+                        Self::show_cost_headers(&mut header);
+                        Self::show_check_headers(&mut header);
                     })
                     .body(|mut body| {
-                        for skill in character.get_skills() {
+                        for skill in self.filter_affordable(character.get_skills(), available_ap, calculator) {
                             body.row(18.0, |mut row| {
                                 row.col(|ui| {
                                     ui.label(&skill.name);
@@ -631,14 +613,29 @@ impl CharacterView {
                                         ui.label("0");
                                     }
                                 });
+                                Self::show_cost_cells(&mut row, skill, available_ap, calculator);
+                                if Self::show_check_cells(&mut row, skill, character, check_modifier) {
+                                    clicked_check = SkillCheck::for_item(skill, character, check_modifier)
+                                        .map(|check| (skill.name.clone(), check));
+                                }
                             });
                         }
                     });
             });
+
+        if clicked_check.is_some() {
+            self.check_details = clicked_check;
+        }
     }
 
-    fn show_combat_skills_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    fn show_combat_skills_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
         ui.heading("⚔ Combat Skills");
+        let combat_values = CombatValues::calculate(character);
+        Self::show_combat_summary(ui, &combat_values);
+        ui.add_space(5.0);
+        Self::show_combat_gear(ui, character);
+        ui.add_space(5.0);
+        let available_ap = self.show_affordable_filter(ui, character, calculator);
         ui.separator();
 
         egui::ScrollArea::vertical()
@@ -652,6 +649,11 @@ impl CharacterView {
                     .column(Column::auto().at_least(40.0))  // Guidevalue
                     .column(Column::auto().at_least(40.0))  // StF
                     .column(Column::auto().at_least(40.0))  // Talent Value
+                    .column(Column::auto().at_least(50.0))  // Next +1 cost
+                    .column(Column::auto().at_least(40.0))  // Max reachable
+                    .column(Column::auto().at_least(30.0))  // AT
+                    .column(Column::auto().at_least(30.0))  // PA
+                    .column(Column::auto().at_least(30.0))  // FK
                     .header(20.0, |mut header| {
                         header.col(|ui| {
                             ui.strong("Combat Skill");
@@ -665,9 +667,15 @@ impl CharacterView {
                         header.col(|ui| {
                             ui.strong("Value");
                         });
+                        Self::show_cost_headers(&mut header);
+                        for (label, hover) in [("AT", "Attacke"), ("PA", "Parade"), ("FK", "Fernkampf")] {
+                            header.col(|ui| {
+                                ui.strong(label).on_hover_text(hover);
+                            });
+                        }
                     })
                     .body(|mut body| {
-                        for combat_skill in character.get_combat_skills() {
+                        for combat_skill in self.filter_affordable(character.get_combat_skills(), available_ap, calculator) {
                             body.row(18.0, |mut row| {
                                 row.col(|ui| {
                                     ui.label(&combat_skill.name);
@@ -693,6 +701,20 @@ impl CharacterView {
                                         ui.label("0");
                                     }
                                 });
+                                Self::show_cost_cells(&mut row, combat_skill, available_ap, calculator);
+
+                                let technique = combat_values.techniques
+                                    .iter()
+                                    .find(|technique| technique.name == combat_skill.name);
+                                for value in [
+                                    technique.and_then(|t| t.attack.as_ref()),
+                                    technique.and_then(|t| t.parry.as_ref()),
+                                    technique.and_then(|t| t.ranged_attack.as_ref()),
+                                ] {
+                                    row.col(|ui| {
+                                        Self::show_derived_value(ui, value);
+                                    });
+                                }
                             });
                         }
                     });
@@ -700,8 +722,11 @@ impl CharacterView {
     }
 
     // show_magic_tab code is a duplicate of most of show_skills_tab. Can we do better?
-    fn show_magic_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    fn show_magic_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
         ui.heading("✨ Spells & Rituals");
+        let available_ap = self.show_affordable_filter(ui, character, calculator);
+        let check_modifier = self.show_check_modifier(ui);
+        let mut clicked_check = None;
         ui.separator();
 
         // First ScrollArea for spells/rituals table
@@ -717,6 +742,10 @@ impl CharacterView {
                     .column(Column::auto().at_least(90.0))  // Characteristics
                     .column(Column::auto().at_least(40.0))  // StF
                     .column(Column::auto().at_least(40.0))  // Talent Value
+                    .column(Column::auto().at_least(50.0))  // Next +1 cost
+                    .column(Column::auto().at_least(40.0))  // Max reachable
+                    .column(Column::auto().at_least(60.0))  // Success probability
+                    .column(Column::auto().at_least(40.0))  // Expected QS
                     .header(20.0, |mut header| {
                         header.col(|ui| {
                             ui.strong("Spell/Ritual");
@@ -730,9 +759,11 @@ impl CharacterView {
                         header.col(|ui| {
                             ui.strong("Value");
                         });
+                        Self::show_cost_headers(&mut header);
+                        Self::show_check_headers(&mut header);
                     })
                     .body(|mut body| {
-                        for spell in character.get_spells_and_rituals() {
+                        for spell in self.filter_affordable(character.get_spells_and_rituals(), available_ap, calculator) {
                             body.row(18.0, |mut row| {
                                 row.col(|ui| {
                                     ui.label(&spell.name);
@@ -762,11 +793,20 @@ impl CharacterView {
                                         ui.label("0");
                                     }
                                 });
+                                Self::show_cost_cells(&mut row, spell, available_ap, calculator);
+                                if Self::show_check_cells(&mut row, spell, character, check_modifier) {
+                                    clicked_check = SkillCheck::for_item(spell, character, check_modifier)
+                                        .map(|check| (spell.name.clone(), check));
+                                }
                             });
                         }
                     });
             });
 
+        if clicked_check.is_some() {
+            self.check_details = clicked_check;
+        }
+
         // Magic tricks section outside the first scroll area
         ui.add_space(20.0);
         ui.heading("🎪 Magic Tricks");
@@ -792,8 +832,11 @@ impl CharacterView {
     }
 
     // show_magic_tab code is a duplicate of show_magic_tab. Can we do better?
-    fn show_karma_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    fn show_karma_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
         ui.heading("🕯 Liturgies & Ceremonies");
+        let available_ap = self.show_affordable_filter(ui, character, calculator);
+        let check_modifier = self.show_check_modifier(ui);
+        let mut clicked_check = None;
         ui.separator();
 
         // First ScrollArea for spells/rituals table
@@ -809,6 +852,10 @@ impl CharacterView {
                     .column(Column::auto().at_least(90.0))  // Characteristics
                     .column(Column::auto().at_least(40.0))  // StF
                     .column(Column::auto().at_least(40.0))  // Talent Value
+                    .column(Column::auto().at_least(50.0))  // Next +1 cost
+                    .column(Column::auto().at_least(40.0))  // Max reachable
+                    .column(Column::auto().at_least(60.0))  // Success probability
+                    .column(Column::auto().at_least(40.0))  // Expected QS
                     .header(20.0, |mut header| {
                         header.col(|ui| {
                             ui.strong("Liturgy/Ceremony");
@@ -822,9 +869,11 @@ impl CharacterView {
                         header.col(|ui| {
                             ui.strong("Value");
                         });
+                        Self::show_cost_headers(&mut header);
+                        Self::show_check_headers(&mut header);
                     })
                     .body(|mut body| {
-                        for item in character.get_liturgies_and_ceremonies() {
+                        for item in self.filter_affordable(character.get_liturgies_and_ceremonies(), available_ap, calculator) {
                             body.row(18.0, |mut row| {
                                 row.col(|ui| {
                                     ui.label(&item.name);
@@ -854,11 +903,20 @@ impl CharacterView {
                                         ui.label("0");
                                     }
                                 });
+                                Self::show_cost_cells(&mut row, item, available_ap, calculator);
+                                if Self::show_check_cells(&mut row, item, character, check_modifier) {
+                                    clicked_check = SkillCheck::for_item(item, character, check_modifier)
+                                        .map(|check| (item.name.clone(), check));
+                                }
                             });
                         }
                     });
             });
 
+        if clicked_check.is_some() {
+            self.check_details = clicked_check;
+        }
+
         // Blessings section outside the first scroll area
         ui.add_space(20.0);
         ui.heading("📚 Blessings");
@@ -884,7 +942,558 @@ impl CharacterView {
     }
 
 
-    fn show_items_tab(&mut self, ui: &mut egui::Ui, character: &Character) {
+    /// Initiative and dodge with their formulas
+    fn show_combat_summary(ui: &mut egui::Ui, combat_values: &CombatValues) {
+        egui::Grid::new("combat_summary_grid")
+            .num_columns(4)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Initiative (INI):");
+                Self::show_derived_value(ui, combat_values.initiative.as_ref());
+                ui.label("Ausweichen (AW):");
+                Self::show_derived_value(ui, combat_values.dodge.as_ref());
+                ui.end_row();
+            });
+    }
+
+    /// Weapons with their final AT/PA/FK and TP, worn armor and ammunition
+    fn show_combat_gear(ui: &mut egui::Ui, character: &Character) {
+        let gear = CombatGear::calculate(character);
+        if gear.weapons.is_empty() && gear.armor.is_empty() {
+            return;
+        }
+
+        egui::CollapsingHeader::new("🗡 Combat Gear")
+            .default_open(true)
+            .show(ui, |ui| {
+                if !gear.weapons.is_empty() {
+                    egui::Grid::new("combat_gear_weapons_grid")
+                        .num_columns(7)
+                        .spacing([15.0, 4.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in ["Weapon", "Technique", "AT", "PA", "FK", "TP", "Reach"] {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+
+                            for weapon in &gear.weapons {
+                                let name = match weapon.equipped {
+                                    Some(true) => format!("{} ✋", weapon.name),
+                                    _ => weapon.name.clone(),
+                                };
+                                ui.label(name).on_hover_text(match weapon.equipped {
+                                    Some(true) => "Equipped",
+                                    Some(false) => "Not equipped",
+                                    None => "",
+                                });
+
+                                match (&weapon.technique, weapon.technique_missing) {
+                                    (Some(technique), true) => {
+                                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {}", technique))
+                                            .on_hover_text("The character has no combat technique with this name");
+                                    }
+                                    (Some(technique), false) => {
+                                        ui.label(technique);
+                                    }
+                                    (None, _) => {
+                                        ui.label("-");
+                                    }
+                                }
+
+                                Self::show_derived_value(ui, weapon.attack.as_ref());
+                                Self::show_derived_value(ui, weapon.parry.as_ref());
+                                Self::show_derived_value(ui, weapon.ranged_attack.as_ref());
+
+                                match (weapon.damage, weapon.hit_points) {
+                                    (Some(damage), Some(hit_points)) => {
+                                        let threshold = match weapon.damage_threshold {
+                                            Some(threshold) if !weapon.ranged => format!(
+                                                "\nDamage threshold: {} {}, bonus +{}",
+                                                weapon.damage_attributes.join("/").to_uppercase(), threshold, weapon.damage_bonus),
+                                            _ => String::new(),
+                                        };
+                                        ui.label(egui::RichText::new(hit_points.to_string()).strong()).on_hover_text(format!(
+                                            "Weapon: {}{}\nRange {} to {} (average {:.1})",
+                                            damage, threshold, hit_points.minimum(), hit_points.maximum(), hit_points.average()));
+                                    }
+                                    _ => {
+                                        ui.label("-");
+                                    }
+                                }
+
+                                match (weapon.reach, weapon.range) {
+                                    (Some(reach), _) => ui.label(reach.name()),
+                                    (None, Some(range)) => ui.label(format!("{} Schritt", range))
+                                        .on_hover_text("Close / medium / far"),
+                                    (None, None) => ui.label("-"),
+                                };
+                                ui.end_row();
+
+                                if weapon.ranged {
+                                    let ammunition = gear.ammunition_for(weapon);
+                                    if !ammunition.is_empty() {
+                                        ui.label("");
+                                        ui.label(egui::RichText::new(format!("Ammunition: {}", ammunition
+                                            .iter()
+                                            .map(|stock| format!("{} × {}", stock.quantity, stock.name))
+                                            .collect::<Vec<_>>()
+                                            .join(", "))).small().color(egui::Color32::GRAY));
+                                        ui.end_row();
+                                    }
+                                }
+                            }
+                        });
+                }
+
+                if !gear.armor.is_empty() {
+                    let armor = gear.armor
+                        .iter()
+                        .map(|armor| format!("{}{} (RS {}, BE {})",
+                                             armor.name,
+                                             if armor.worn == Some(true) { " ✋" } else { "" },
+                                             armor.protection.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string()),
+                                             armor.encumbrance.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    ui.label(format!("Armor: {}", armor));
+                }
+            });
+    }
+
+    fn show_derived_value(ui: &mut egui::Ui, value: Option<&DerivedValue>) {
+        match value {
+            Some(value) => {
+                ui.add(egui::Label::new(
+                    egui::RichText::new(value.value.to_string())
+                        .strong()
+                        .color(egui::Color32::from_rgb(70, 130, 180))
+                )).on_hover_text(format!("{} (no special abilities, advantages or equipment considered!)", value.formula));
+            }
+            None => {
+                ui.label("-");
+            }
+        }
+    }
+
+    fn awarded_ap(&self) -> Option<i32> {
+        self.awards_known.then_some(self.awarded_ap)
+    }
+
+    /// Experience level inferred from the starting AP and the creation limits it implies
+    fn show_experience_level(&self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let Some(check) = ExperienceLevelCheck::evaluate(character, self.awarded_ap(), calculator) else {
+            return;
+        };
+
+        ui.add_space(10.0);
+        let Some(level) = &check.level else {
+            ui.colored_label(
+                egui::Color32::from_rgb(200, 150, 50),
+                format!("⚠ {} starting AP is below every experience level", check.start_ap),
+            );
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Experience level:");
+            ui.strong(&level.name);
+            match check.confirmed {
+                Some(true) => {
+                    ui.colored_label(egui::Color32::from_rgb(100, 200, 100), "✔")
+                        .on_hover_text(format!("{} total AP = {} starting AP + {} awarded AP", check.total_ap, level.ap, check.awarded_ap.unwrap_or(0)));
+                }
+                Some(false) => {
+                    ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {} starting AP is not the starting AP of an experience level", check.start_ap));
+                }
+                None => {
+                    ui.label(egui::RichText::new("(from the total AP; enter the awarded AP to confirm)").small().color(egui::Color32::GRAY));
+                }
+            }
+        });
+
+        egui::Grid::new("experience_level_caps")
+            .num_columns(3)
+            .spacing([15.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Creation limit");
+                ui.strong("Current");
+                ui.strong("Maximum");
+                ui.end_row();
+
+                for cap in &check.caps {
+                    ui.label(cap.name);
+                    let current = match (cap.current, &cap.subject) {
+                        (Some(value), Some(subject)) => format!("{} ({})", value, subject),
+                        (Some(value), None) => value.to_string(),
+                        (None, _) => "-".to_string(),
+                    };
+                    if cap.exceeded() {
+                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {}", current));
+                    } else {
+                        ui.label(current);
+                    }
+                    ui.label(cap.limit.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn show_validation_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let report = ValidationReport::validate(character, self.awarded_ap(), calculator);
+
+        ui.heading("✅ Validation");
+        ui.separator();
+
+        egui::Grid::new("validation_summary_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Experience level:");
+                match report.experience_level() {
+                    Some(level) => ui.label(level),
+                    None => ui.label("unknown (creation limits not checked)"),
+                };
+                ui.end_row();
+
+                ui.label("Errors:");
+                ui.label(egui::RichText::new(report.count(Severity::Error).to_string()).strong().color(Self::severity_color(Severity::Error)));
+                ui.end_row();
+
+                ui.label("Warnings:");
+                ui.label(egui::RichText::new(report.count(Severity::Warning).to_string()).strong().color(Self::severity_color(Severity::Warning)));
+                ui.end_row();
+            });
+
+        ui.label(egui::RichText::new("Warnings refer to limits that only apply at character creation, or to values that differ from the compendium.").small().color(egui::Color32::GRAY));
+        Self::show_compendium_status(ui, character, calculator);
+        ui.separator();
+
+        if report.findings.is_empty() {
+            ui.colored_label(egui::Color32::from_rgb(100, 200, 100), "✔ No rule violations found");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("validation_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("validation_findings_grid")
+                    .num_columns(4)
+                    .spacing([15.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("");
+                        ui.strong("Subject");
+                        ui.strong("Finding");
+                        ui.strong("Rule");
+                        ui.end_row();
+
+                        for finding in &report.findings {
+                            ui.label(egui::RichText::new(finding.severity.symbol()).strong().color(Self::severity_color(finding.severity)));
+                            ui.label(&finding.subject);
+                            ui.label(&finding.message);
+                            ui.label(finding.rule.id()).on_hover_text(finding.reference);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    /// Size of the loaded compendium and the items it does not know
+    fn show_compendium_status(ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let Some(compendium) = calculator.compendium() else {
+            ui.label(egui::RichText::new("Load a compendium to check APValues, steps and StF against the official entries.").small().color(egui::Color32::GRAY));
+            return;
+        };
+
+        let unmatched = compendium.unmatched(&character.items);
+        ui.horizontal(|ui| {
+            ui.label(format!("Compendium: {} entries from {} packs", compendium.entries.len(), compendium.packs.len()));
+            if !unmatched.is_empty() {
+                let names: Vec<&str> = unmatched.iter().map(|item| item.name.as_str()).collect();
+                ui.colored_label(egui::Color32::GRAY, format!("{} not found", unmatched.len()))
+                    .on_hover_text(format!("Not checked:\n{}", names.join("\n")));
+            }
+        });
+    }
+
+    fn severity_color(severity: Severity) -> egui::Color32 {
+        match severity {
+            Severity::Error => egui::Color32::from_rgb(220, 100, 100),
+            Severity::Warning => egui::Color32::from_rgb(200, 150, 50),
+        }
+    }
+
+    /// Calculated derived values next to the values stored by Foundry, with the pain thresholds
+    fn show_derived_values(ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let derived_values = DerivedValues::calculate(character, calculator.rules());
+
+        egui::Grid::new("derived_values_grid")
+            .num_columns(4)
+            .spacing([15.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Value");
+                ui.strong("Calculated");
+                ui.strong("Foundry");
+                ui.strong("");
+                ui.end_row();
+
+                for check in &derived_values.values {
+                    // Values the character does not have (e.g. AsP without a tradition)
+                    if check.calculated.is_none() && check.missing_reason.is_none() && check.foundry.unwrap_or(0) == 0 {
+                        continue;
+                    }
+
+                    ui.label(check.kind.name());
+                    match (&check.calculated, &check.missing_reason) {
+                        (Some(calculated), _) => {
+                            ui.add(egui::Label::new(
+                                egui::RichText::new(calculated.value.to_string())
+                                    .strong()
+                                    .color(egui::Color32::from_rgb(70, 130, 180))
+                            )).on_hover_text(&calculated.formula);
+                        }
+                        (None, Some(reason)) => {
+                            ui.label("-").on_hover_text(reason);
+                        }
+                        (None, None) => {
+                            ui.label("-");
+                        }
+                    }
+                    ui.label(check.foundry.map(|value| value.to_string()).unwrap_or_else(|| "not recorded".to_string()));
+                    if check.disagrees() {
+                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), "⚠ differs")
+                            .on_hover_text("Foundry stores a different value. Check the advantages, species and manual modifiers.");
+                    } else if let Some(reason) = &check.missing_reason {
+                        ui.colored_label(egui::Color32::GRAY, reason);
+                    } else {
+                        ui.label("");
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if !derived_values.pain_thresholds.is_empty() {
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.strong("Pain thresholds:");
+                for threshold in &derived_values.pain_thresholds {
+                    ui.label(format!("{} at ≤ {} LeP", threshold.name(), threshold.life_points));
+                }
+            });
+        }
+    }
+
+    /// Checkbox to show only entries whose next increase is affordable; returns the AP not yet spent
+    fn show_affordable_filter(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) -> i32 {
+        let available_ap = character.remaining_ap(calculator).unwrap_or(0);
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.affordable_only, "Affordable now")
+                .on_hover_text("Only show entries whose next increase costs at most the remaining AP");
+            ui.label(format!("Remaining: {} AP", available_ap));
+        });
+
+        available_ap
+    }
+
+    fn filter_affordable<'a>(&self, items: Vec<&'a Item>, available_ap: i32, calculator: &ApCalculator) -> Vec<&'a Item> {
+        if !self.affordable_only {
+            return items;
+        }
+
+        items
+            .into_iter()
+            .filter(|item| calculator.next_talent_value_cost(item).is_ok_and(|cost| cost <= available_ap))
+            .collect()
+    }
+
+    fn show_cost_headers(header: &mut egui_extras::TableRow) {
+        header.col(|ui| {
+            ui.strong("Next +1").on_hover_text("AP cost of the next increase");
+        });
+        header.col(|ui| {
+            ui.strong("Max").on_hover_text("Highest value reachable with the remaining AP");
+        });
+    }
+
+    fn show_cost_cells(row: &mut egui_extras::TableRow, item: &Item, available_ap: i32, calculator: &ApCalculator) {
+        row.col(|ui| {
+            match calculator.next_talent_value_cost(item) {
+                Ok(cost) if cost > available_ap => {
+                    ui.colored_label(egui::Color32::GRAY, format!("{} AP", cost));
+                }
+                Ok(cost) => {
+                    ui.label(format!("{} AP", cost));
+                }
+                Err(reason) => {
+                    ui.label("-").on_hover_text(reason);
+                }
+            }
+        });
+        row.col(|ui| {
+            match calculator.max_reachable_talent_value(item, available_ap) {
+                Ok(value) => {
+                    ui.label(value.to_string());
+                }
+                Err(reason) => {
+                    ui.label("-").on_hover_text(reason);
+                }
+            }
+        });
+    }
+
+    /// Input for the modifier applied to all skill checks; returns the modifier
+    fn show_check_modifier(&mut self, ui: &mut egui::Ui) -> i32 {
+        ui.horizontal(|ui| {
+            ui.label("Check modifier:");
+            ui.add(egui::DragValue::new(&mut self.check_modifier).range(-10..=10))
+                .on_hover_text("Positive values make the check easier, negative values harder");
+        });
+
+        self.check_modifier
+    }
+
+    fn show_check_headers(header: &mut egui_extras::TableRow) {
+        header.col(|ui| {
+            ui.strong("Success").on_hover_text("Probability to pass the check (click for the QS distribution)");
+        });
+        header.col(|ui| {
+            ui.strong("Ø QS").on_hover_text("Expected quality level if the check succeeds");
+        });
+    }
+
+    /// Success probability and expected QS of an item's check; returns true if the details were requested
+    fn show_check_cells(row: &mut egui_extras::TableRow, item: &Item, character: &Character, modifier: i32) -> bool {
+        let probability = SkillCheck::for_item(item, character, modifier).map(|check| check.probability());
+        let mut clicked = false;
+
+        row.col(|ui| {
+            match &probability {
+                Some(probability) => {
+                    clicked = ui.link(format!("{:.1} %", probability.success * 100.0))
+                        .on_hover_text("Show quality level distribution")
+                        .clicked();
+                }
+                None => {
+                    ui.label("-").on_hover_text("Characteristics of the check not found");
+                }
+            }
+        });
+        row.col(|ui| {
+            match &probability {
+                Some(probability) if probability.success > 0.0 => {
+                    ui.label(format!("{:.1}", probability.expected_quality_level()));
+                }
+                _ => {
+                    ui.label("-");
+                }
+            }
+        });
+
+        clicked
+    }
+
+    fn show_check_details_window(&mut self, ctx: &egui::Context) {
+        let Some((name, check)) = &self.check_details else {
+            return;
+        };
+
+        let probability = check.probability();
+        let mut open = true;
+
+        egui::Window::new(format!("🎲 {}", name))
+            .id(egui::Id::new("skill_check_details_window"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("skill_check_details_grid")
+                    .num_columns(2)
+                    .spacing([20.0, 4.0])
+                    .show(ui, |ui| {
+                        let [c1, c2, c3] = check.characteristics;
+                        ui.label("Characteristics:");
+                        ui.label(format!("{}/{}/{}", c1, c2, c3));
+                        ui.end_row();
+
+                        ui.label("Skill value:");
+                        ui.label(check.skill_value.to_string());
+                        ui.end_row();
+
+                        ui.label("Modifier:");
+                        ui.label(format!("{:+}", check.modifier));
+                        ui.end_row();
+
+                        ui.label("Success:");
+                        ui.strong(format!("{:.2} %", probability.success * 100.0));
+                        ui.end_row();
+
+                        ui.label("Critical success:");
+                        ui.label(format!("{:.2} %", probability.critical_success * 100.0));
+                        ui.end_row();
+
+                        ui.label("Botch:");
+                        ui.label(format!("{:.2} %", probability.botch * 100.0));
+                        ui.end_row();
+                    });
+
+                ui.add_space(10.0);
+                Self::draw_quality_level_histogram(ui, &probability);
+            });
+
+        if !open {
+            self.check_details = None;
+        }
+    }
+
+    /// Bar chart of the probability of each quality level
+    fn draw_quality_level_histogram(ui: &mut egui::Ui, probability: &CheckProbability) {
+        let bar_width = 40.0;
+        let chart_height = 120.0;
+        let label_height = 36.0;
+        let size = egui::vec2(bar_width * MAX_QUALITY_LEVEL as f32 + 10.0, chart_height + label_height);
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+        let rect = response.rect;
+
+        let max_probability = probability.quality_levels.iter().cloned().fold(0.0, f64::max);
+        let text_color = ui.visuals().text_color();
+
+        for (index, quality_probability) in probability.quality_levels.iter().enumerate() {
+            let left = rect.left() + 5.0 + index as f32 * bar_width;
+            let bottom = rect.top() + chart_height;
+            let height = if max_probability > 0.0 {
+                (quality_probability / max_probability) as f32 * (chart_height - 5.0)
+            } else {
+                0.0
+            };
+
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(left + 4.0, bottom - height),
+                egui::pos2(left + bar_width - 4.0, bottom),
+            );
+            painter.rect_filled(bar, 2.0, egui::Color32::from_rgb(100, 150, 220));
+
+            painter.text(
+                egui::pos2(left + bar_width / 2.0, bottom + 4.0),
+                egui::Align2::CENTER_TOP,
+                format!("QS {}", index + 1),
+                egui::FontId::proportional(12.0),
+                text_color,
+            );
+            painter.text(
+                egui::pos2(left + bar_width / 2.0, bottom + 20.0),
+                egui::Align2::CENTER_TOP,
+                format!("{:.1}%", quality_probability * 100.0),
+                egui::FontId::proportional(10.0),
+                text_color,
+            );
+        }
+    }
+
+    fn show_items_tab(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
         ui.heading("🎒 Items");
         ui.separator();
 
@@ -892,19 +1501,287 @@ impl CharacterView {
             .id_salt("items_scroll")
             .auto_shrink([false; 2])
             .show(ui, |ui| {
-                for item in &character.items {
-                    ui.horizontal(|ui| {
-                        ui.label(&item.name);
-                        ui.label(format!("[{}]", item.item_type));
-                        if let Some(quantity) = item.system.get_quantity() {
-                            ui.label(format!("({})", quantity));
+                Self::show_encumbrance(ui, character, calculator);
+                ui.separator();
+
+                Self::show_wealth(ui, character, calculator);
+                ui.separator();
+
+                self.show_inventory_table(ui, character, calculator);
+            });
+    }
+
+    /// Coins with their value and the value of the equipment by item type
+    fn show_wealth(ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let rules = calculator.rules();
+        let wealth = Wealth::calculate(character, rules);
+
+        ui.strong("💰 Wealth");
+        ui.horizontal(|ui| {
+            egui::Grid::new("wealth_coins_grid")
+                .num_columns(3)
+                .spacing([15.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Coin");
+                    ui.strong("Quantity");
+                    ui.strong("Value");
+                    ui.end_row();
+
+                    for coin in &wealth.coins {
+                        ui.label(format!("{} ({})", coin.name, coin.key));
+                        ui.label(coin.quantity.to_string());
+                        ui.label(coin.value.format(rules));
+                        ui.end_row();
+                    }
+
+                    ui.strong("Money");
+                    ui.label("");
+                    ui.strong(wealth.money.format(rules))
+                        .on_hover_text(format!("{:.2} Silbertaler", wealth.money.in_price_unit(rules)));
+                    ui.end_row();
+                });
+
+            ui.add_space(30.0);
+
+            egui::Grid::new("wealth_equipment_grid")
+                .num_columns(3)
+                .spacing([15.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Equipment");
+                    ui.strong("Items");
+                    ui.strong("Value");
+                    ui.end_row();
+
+                    for group in &wealth.equipment {
+                        ui.label(&group.item_type);
+                        ui.label(group.count.to_string());
+                        ui.label(group.value.format(rules));
+                        ui.end_row();
+                    }
+
+                    ui.strong("Total");
+                    ui.label("");
+                    ui.strong(wealth.equipment_value.format(rules));
+                    ui.end_row();
+                });
+        });
+
+        ui.label(format!("Total wealth: {} ({:.2} Silbertaler)", wealth.total().format(rules), wealth.total().in_price_unit(rules)));
+        if !wealth.unknown_coins.is_empty() {
+            ui.colored_label(
+                egui::Color32::from_rgb(200, 150, 50),
+                format!("⚠ Unknown currency: {}", wealth.unknown_coins.join(", ")),
+            );
+        }
+    }
+
+    /// Inventory with weight, price and quantity, sortable by clicking a column header
+    fn show_inventory_table(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let rules = calculator.rules();
+        let inventory = character.get_inventory();
+
+        let mut item_types: Vec<&str> = inventory.iter().map(|item| item.kind.as_str()).collect();
+        item_types.sort();
+        item_types.dedup();
+
+        ui.horizontal(|ui| {
+            ui.strong("📦 Inventory");
+            ui.add(egui::TextEdit::singleline(&mut self.inventory_filter).hint_text("Filter by name").desired_width(150.0));
+            egui::ComboBox::from_id_salt("inventory_type_filter")
+                .selected_text(self.inventory_type.as_deref().unwrap_or("All types"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.inventory_type, None, "All types");
+                    for item_type in &item_types {
+                        ui.selectable_value(&mut self.inventory_type, Some(item_type.to_string()), *item_type);
+                    }
+                });
+        });
+
+        let filter = self.inventory_filter.trim().to_lowercase();
+        let mut items: Vec<&Item> = inventory
+            .into_iter()
+            .filter(|item| filter.is_empty() || item.name.to_lowercase().contains(&filter))
+            .filter(|item| self.inventory_type.as_ref().is_none_or(|item_type| item.kind.as_str() == item_type))
+            .collect();
+
+        let quantity = |item: &Item| item.data().equipment().map_or(1, |equipment| equipment.quantity);
+        let unit_weight = |item: &Item| item.data().equipment().and_then(|equipment| equipment.weight);
+        let weight = |item: &Item| unit_weight(item).unwrap_or(0.0) * quantity(item) as f64;
+        items.sort_by(|a, b| {
+            let ordering = match self.inventory_sort {
+                InventoryColumn::Name => a.name.cmp(&b.name),
+                InventoryColumn::Type => a.kind.as_str().cmp(b.kind.as_str()).then_with(|| a.name.cmp(&b.name)),
+                InventoryColumn::Quantity => quantity(a).cmp(&quantity(b)),
+                InventoryColumn::Weight => weight(a).total_cmp(&weight(b)),
+                InventoryColumn::Price => item_price(a, rules).cmp(&item_price(b, rules)),
+                InventoryColumn::Value => item_value(a, rules).cmp(&item_value(b, rules)),
+            };
+            if self.inventory_sort_descending { ordering.reverse() } else { ordering }
+        });
+
+        if items.is_empty() {
+            ui.label("No items found.");
+            return;
+        }
+
+        let columns = [
+            (InventoryColumn::Name, "Name"),
+            (InventoryColumn::Type, "Type"),
+            (InventoryColumn::Quantity, "Qty"),
+            (InventoryColumn::Weight, "Weight"),
+            (InventoryColumn::Price, "Price"),
+            (InventoryColumn::Value, "Value"),
+        ];
+
+        TableBuilder::new(ui)
+            .id_salt("inventory_table")
+            .striped(true)
+            .column(Column::auto().at_least(180.0))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::auto().at_least(40.0))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(80.0))
+            .column(Column::auto().at_least(80.0))
+            .header(20.0, |mut header| {
+                for (column, label) in columns {
+                    header.col(|ui| {
+                        let arrow = match (self.inventory_sort == column, self.inventory_sort_descending) {
+                            (true, false) => " ⬆",
+                            (true, true) => " ⬇",
+                            (false, _) => "",
+                        };
+                        if ui.selectable_label(self.inventory_sort == column, format!("{}{}", label, arrow)).clicked() {
+                            if self.inventory_sort == column {
+                                self.inventory_sort_descending = !self.inventory_sort_descending;
+                            } else {
+                                self.inventory_sort = column;
+                                self.inventory_sort_descending = false;
+                            }
                         }
                     });
                 }
+            })
+            .body(|mut body| {
+                for item in items {
+                    body.row(18.0, |mut row| {
+                        row.col(|ui| { ui.label(&item.name); });
+                        row.col(|ui| { ui.label(item.kind.as_str()); });
+                        row.col(|ui| { ui.label(quantity(item).to_string()); });
+                        row.col(|ui| {
+                            match unit_weight(item) {
+                                Some(_) => ui.label(format!("{:.2}", weight(item))),
+                                None => ui.label("-"),
+                            };
+                        });
+                        row.col(|ui| { ui.label(item_price(item, rules).map(|price| price.format(rules)).unwrap_or_else(|| "-".to_string())); });
+                        row.col(|ui| { ui.label(item_value(item, rules).map(|value| value.format(rules)).unwrap_or_else(|| "-".to_string())); });
+                    });
+                }
             });
     }
 
 
+    /// Carried weight, carrying capacity, armor and the resulting encumbrance with its effects
+    fn show_encumbrance(ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let encumbrance = Encumbrance::calculate(character, calculator.rules());
+        let warning_color = egui::Color32::from_rgb(200, 150, 50);
+
+        ui.strong("⚖ Encumbrance (Belastung)");
+        egui::Grid::new("encumbrance_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Carried weight:");
+                match encumbrance.carrying_capacity {
+                    Some(capacity) => ui.label(format!("{:.2} / {:.2} Stein (KK × 2)", encumbrance.carried_weight, capacity)),
+                    None => ui.label(format!("{:.2} Stein (no KK, carrying capacity unknown)", encumbrance.carried_weight)),
+                };
+                ui.end_row();
+
+                ui.label("From weight:");
+                ui.label(format!("{} level(s)", encumbrance.weight_level));
+                ui.end_row();
+
+                ui.label("From armor:");
+                if encumbrance.armor.is_empty() {
+                    ui.label("no armor worn");
+                } else {
+                    let armor = encumbrance.armor
+                        .iter()
+                        .map(|armor| format!("{} (RS {}, BE {})", armor.name, armor.protection, armor.encumbrance))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    ui.label(format!("{} level(s): {}", encumbrance.armor_level, armor));
+                }
+                ui.end_row();
+
+                ui.label("Encumbrance:");
+                let text = egui::RichText::new(format!("Belastung {}", encumbrance.level)).strong();
+                if encumbrance.is_incapacitated() {
+                    ui.label(text.color(egui::Color32::from_rgb(220, 100, 100))).on_hover_text("Incapacitated (handlungsunfähig)");
+                } else if encumbrance.level > 0 {
+                    ui.label(text.color(warning_color));
+                } else {
+                    ui.label(text);
+                }
+                ui.end_row();
+            });
+
+        let excluded: Vec<String> = encumbrance.items
+            .iter()
+            .filter_map(|item| item.excluded_reason.as_ref().map(|reason| format!("{} ({})", item.name, reason)))
+            .collect();
+        if !excluded.is_empty() {
+            ui.label(egui::RichText::new(format!("Not counted: {}", excluded.join(", "))).small().color(egui::Color32::GRAY));
+        }
+
+        if encumbrance.level == 0 {
+            return;
+        }
+
+        ui.add_space(5.0);
+        ui.label(format!("Effects (−{} each):", encumbrance.level));
+
+        let combat_values = CombatValues::calculate(character);
+        let derived_values = DerivedValues::calculate(character, calculator.rules());
+        let speed = derived_values.get(DerivedValueKind::Speed)
+            .and_then(|check| check.calculated.as_ref().map(|value| value.value).or(check.foundry));
+
+        egui::Grid::new("encumbrance_effects_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                let effect_row = |ui: &mut egui::Ui, name: &str, value: Option<i32>| {
+                    let Some(value) = value else {
+                        return;
+                    };
+                    ui.label(name);
+                    ui.colored_label(warning_color, format!("{} → {}", value, encumbrance.apply(value)));
+                    ui.end_row();
+                };
+
+                effect_row(ui, "Initiative (INI)", combat_values.initiative.as_ref().map(|v| v.value));
+                effect_row(ui, "Ausweichen (AW)", combat_values.dodge.as_ref().map(|v| v.value));
+                effect_row(ui, "Speed (GS)", speed);
+                for technique in &combat_values.techniques {
+                    effect_row(ui, &format!("{} AT", technique.name), technique.attack.as_ref().map(|v| v.value));
+                    effect_row(ui, &format!("{} PA", technique.name), technique.parry.as_ref().map(|v| v.value));
+                    effect_row(ui, &format!("{} FK", technique.name), technique.ranged_attack.as_ref().map(|v| v.value));
+                }
+            });
+
+        if !encumbrance.affected_skills.is_empty() {
+            ui.label(format!("Skill checks −{}: {}", encumbrance.level, encumbrance.affected_skills.join(", ")));
+        }
+        if !encumbrance.possibly_affected_skills.is_empty() {
+            ui.label(egui::RichText::new(format!("Possibly affected (situational): {}", encumbrance.possibly_affected_skills.join(", ")))
+                .color(egui::Color32::GRAY));
+        }
+    }
+
     fn show_character_image(&mut self, ui: &mut egui::Ui, character: &Character) {
         if let Some(image_url) = character.get_image_url() {
             // Check if image is already cached
@@ -0,0 +1,135 @@
+use crate::character::{ChangeKind, SourceComparison};
+use crate::ui::DiffView;
+use eframe::egui;
+
+/// Window showing where the Optolith file and the Foundry export of the same hero diverge
+pub struct CrossCheckView {
+    comparison: Option<SourceComparison>,
+    open: bool,
+}
+
+impl CrossCheckView {
+    pub fn new() -> Self {
+        Self {
+            comparison: None,
+            open: false,
+        }
+    }
+
+    pub fn set_comparison(&mut self, comparison: SourceComparison) {
+        self.comparison = Some(comparison);
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+
+        egui::Window::new(format!("⚖ Optolith vs. Foundry: {}", comparison.foundry_character))
+            .id(egui::Id::new("cross_check_window"))
+            .open(&mut self.open)
+            .default_width(600.0)
+            .default_height(500.0)
+            .show(ctx, |ui| {
+                Self::show_summary(ui, comparison);
+                ui.separator();
+                Self::show_categories(ui, comparison);
+                ui.separator();
+                DiffView::show_changes(ui, &comparison.differences);
+                Self::show_unmatched(ui, comparison);
+            });
+    }
+
+    /// Optolith ids without table entry and the Foundry entries they may correspond to
+    fn show_unmatched(ui: &mut egui::Ui, comparison: &SourceComparison) {
+        if comparison.unmatched.is_empty() {
+            return;
+        }
+
+        ui.separator();
+        egui::CollapsingHeader::new(format!("Not matched ({})", comparison.unmatched.len()))
+            .id_salt("cross_check_unmatched")
+            .show(ui, |ui| {
+                ui.label("Unmapped Optolith ids cannot be matched by name; the Foundry entries of the same type may be their counterparts.");
+                for change in &comparison.unmatched {
+                    let side = match change.kind {
+                        ChangeKind::Removed => "Optolith",
+                        _ => "Foundry",
+                    };
+                    ui.label(format!("{} {} ({}, {}): {:+} AP", change.kind.symbol(), change.name, change.category, side, change.ap_delta));
+                }
+            });
+    }
+
+    fn show_summary(ui: &mut egui::Ui, comparison: &SourceComparison) {
+        egui::Grid::new("cross_check_summary_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Calculated spent AP:");
+                ui.label(format!("Optolith {} AP, Foundry {} AP", comparison.optolith_total, comparison.foundry_total));
+                ui.end_row();
+
+                ui.label("Spent AP recorded by Foundry:");
+                match comparison.foundry_spent {
+                    Some(spent) => ui.label(format!("{} AP", spent)),
+                    None => ui.label("-"),
+                };
+                ui.end_row();
+
+                ui.label("Result:");
+                if comparison.is_consistent() {
+                    ui.colored_label(egui::Color32::from_rgb(100, 200, 100), "✔ Both sources agree");
+                } else {
+                    ui.colored_label(egui::Color32::from_rgb(200, 150, 50),
+                        format!("⚠ {} categories diverge", comparison.diverging_categories().count()));
+                }
+                ui.end_row();
+            });
+
+        if !comparison.import_warnings.is_empty() {
+            ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {} import warnings", comparison.import_warnings.len()))
+                .on_hover_text(comparison.import_warnings.join("\n"));
+        }
+    }
+
+    fn show_categories(ui: &mut egui::Ui, comparison: &SourceComparison) {
+        egui::Grid::new("cross_check_categories_grid")
+            .num_columns(5)
+            .spacing([15.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Category");
+                ui.strong("Optolith");
+                ui.strong("Foundry");
+                ui.strong("Difference");
+                ui.strong("Check");
+                ui.end_row();
+
+                for category in &comparison.categories {
+                    ui.label(&category.category);
+                    ui.label(format!("{} AP", category.optolith_ap));
+                    ui.label(format!("{} AP", category.foundry_ap));
+                    if category.difference() == 0 {
+                        ui.label("-");
+                    } else {
+                        ui.strong(format!("{:+} AP", category.difference()));
+                    }
+
+                    let mut notes = Vec::new();
+                    if let Some(side) = category.suspect {
+                        notes.push(format!("check {}", side.name()));
+                    }
+                    if category.placeholders > 0 {
+                        notes.push(format!("{} unmapped ids", category.placeholders));
+                    }
+                    if category.explained_ap != category.difference() {
+                        notes.push(format!("{:+} AP unexplained", category.difference() - category.explained_ap));
+                    }
+                    ui.label(notes.join(", "));
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -0,0 +1,126 @@
+use crate::character::{ChangeKind, CharacterDiff};
+use eframe::egui;
+use std::path::PathBuf;
+
+/// Window showing the changes between the loaded character and another export
+pub struct DiffView {
+    diff: Option<CharacterDiff>,
+    compared_file: Option<PathBuf>,
+    open: bool,
+}
+
+impl DiffView {
+    pub fn new() -> Self {
+        Self {
+            diff: None,
+            compared_file: None,
+            open: false,
+        }
+    }
+
+    pub fn set_diff(&mut self, diff: CharacterDiff, compared_file: PathBuf) {
+        self.diff = Some(diff);
+        self.compared_file = Some(compared_file);
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let Some(diff) = &self.diff else {
+            return;
+        };
+
+        let title = match &self.compared_file {
+            Some(path) => format!("🔀 Changes in {}", path.file_name().unwrap_or_default().to_string_lossy()),
+            None => "🔀 Changes".to_string(),
+        };
+
+        egui::Window::new(title)
+            .id(egui::Id::new("character_diff_window"))
+            .open(&mut self.open)
+            .default_width(550.0)
+            .default_height(450.0)
+            .show(ctx, |ui| {
+                Self::show_summary(ui, diff);
+                ui.separator();
+                Self::show_changes(ui, diff);
+            });
+    }
+
+    fn show_summary(ui: &mut egui::Ui, diff: &CharacterDiff) {
+        egui::Grid::new("diff_summary_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Character:");
+                if diff.old_character == diff.new_character {
+                    ui.strong(&diff.old_character);
+                } else {
+                    ui.strong(format!("{} → {}", diff.old_character, diff.new_character));
+                }
+                ui.end_row();
+
+                ui.label("Calculated spent AP:");
+                ui.label(format!("{} AP → {} AP", diff.old_total, diff.new_total));
+                ui.end_row();
+
+                ui.label("AP delta:");
+                ui.label(egui::RichText::new(format!("{:+} AP", diff.ap_delta())).strong().color(Self::delta_color(diff.ap_delta())));
+                ui.end_row();
+            });
+    }
+
+    pub(crate) fn show_changes(ui: &mut egui::Ui, diff: &CharacterDiff) {
+        if diff.changes.is_empty() {
+            ui.label("No changes found.");
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("diff_changes_scroll")
+            .show(ui, |ui| {
+                egui::Grid::new("diff_changes_grid")
+                    .num_columns(5)
+                    .spacing([15.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("");
+                        ui.strong("Name");
+                        ui.strong("Category");
+                        ui.strong("Value");
+                        ui.strong("AP");
+                        ui.end_row();
+
+                        for change in &diff.changes {
+                            let kind_color = match change.kind {
+                                ChangeKind::Added => egui::Color32::from_rgb(100, 200, 100),
+                                ChangeKind::Removed => egui::Color32::from_rgb(220, 100, 100),
+                                ChangeKind::Changed => egui::Color32::from_rgb(200, 150, 50),
+                            };
+                            ui.label(egui::RichText::new(change.kind.symbol()).strong().color(kind_color));
+                            ui.label(&change.name);
+                            ui.label(&change.category);
+
+                            let values = match (change.old_value, change.new_value) {
+                                (Some(old), Some(new)) if old != new => format!("{} → {}", old, new),
+                                (Some(value), _) | (_, Some(value)) => value.to_string(),
+                                (None, None) => "-".to_string(),
+                            };
+                            ui.label(values);
+
+                            ui.label(egui::RichText::new(format!("{:+}", change.ap_delta)).color(Self::delta_color(change.ap_delta)));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn delta_color(delta: i32) -> egui::Color32 {
+        if delta > 0 {
+            egui::Color32::from_rgb(220, 100, 100)
+        } else if delta < 0 {
+            egui::Color32::from_rgb(100, 200, 100)
+        } else {
+            egui::Color32::GRAY
+        }
+    }
+}
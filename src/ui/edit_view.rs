@@ -0,0 +1,249 @@
+use crate::character::{ApCalculator, Character, CharacterDiff, Characteristics, FoundryExport, ItemData};
+use crate::ui::{DiffView, FileDialog};
+use eframe::egui;
+use std::path::PathBuf;
+
+/// Highest talent value and characteristic advances offered in the editor
+const MAX_EDIT_VALUE: i32 = 25;
+
+/// Edit talent values, characteristic advances and steps, and write them back into the Foundry export
+pub struct EditView {
+    file_dialog: FileDialog,
+    /// File the last export was written to
+    saved_file: Option<PathBuf>,
+    /// Error of the last export or edit
+    error: Option<String>,
+}
+
+/// A value changed in the editor, applied after the widgets are drawn
+enum Edit {
+    TalentValue(String, i32),
+    Step(String, i32),
+    Advances(&'static str, i32),
+}
+
+impl EditView {
+    pub fn new() -> Self {
+        Self {
+            file_dialog: FileDialog::new(),
+            saved_file: None,
+            error: None,
+        }
+    }
+
+    /// Returns whether the character was edited or reset
+    pub fn show(&mut self, ui: &mut egui::Ui, character: &mut Character, export: Option<&FoundryExport>, calculator: &ApCalculator) -> bool {
+        ui.heading("✏ Edit");
+        ui.label("Changes are applied to the analysis right away. The export writes them and the recomputed spent AP into a copy of the loaded Foundry file.");
+        ui.separator();
+
+        let Some(export) = export else {
+            ui.label("The loaded file cannot be written back.");
+            return false;
+        };
+
+        let diff = CharacterDiff::between(&export.original, character, calculator);
+        let reset = self.show_export_buttons(ui, character, export, &diff, calculator);
+        ui.add_space(5.0);
+
+        egui::Grid::new("edit_spent_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Spent AP in Foundry:");
+                match export.original.experience().and_then(|experience| experience.spent()) {
+                    Some(spent) => ui.label(format!("{} AP", spent)),
+                    None => ui.label("not recorded"),
+                };
+                ui.end_row();
+
+                ui.label("Spent AP written on export:");
+                ui.strong(format!("{} AP", calculator.calculate_total_spent_ap(character)));
+                ui.end_row();
+            });
+
+        if !diff.changes.is_empty() {
+            ui.add_space(5.0);
+            egui::CollapsingHeader::new(format!("Changes ({}, {:+} AP)", diff.changes.len(), diff.ap_delta()))
+                .default_open(false)
+                .show(ui, |ui| {
+                    DiffView::show_changes(ui, &diff);
+                });
+        }
+        ui.separator();
+
+        let mut edits = Vec::new();
+        egui::ScrollArea::vertical()
+            .id_salt("edit_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                Self::show_characteristics(ui, character, &mut edits);
+                Self::show_talents(ui, character, &mut edits);
+                Self::show_steps(ui, character, &mut edits);
+            });
+
+        let edited = reset || !edits.is_empty();
+        for edit in edits {
+            let result = match edit {
+                Edit::TalentValue(id, value) => character.set_talent_value(&id, value),
+                Edit::Step(id, step) => character.set_step(&id, step),
+                Edit::Advances(key, advances) => character.set_characteristic_advances(key, advances),
+            };
+            if let Err(e) = result {
+                self.error = Some(format!("Error editing character: {}", e));
+            }
+        }
+        edited
+    }
+
+    /// Returns whether the edits were discarded
+    fn show_export_buttons(&mut self, ui: &mut egui::Ui, character: &mut Character, export: &FoundryExport, diff: &CharacterDiff, calculator: &ApCalculator) -> bool {
+        let mut reset = false;
+        ui.horizontal(|ui| {
+            if ui.button("💾 Export Foundry JSON…").clicked() {
+                let default_name = format!("{}.json", character.name);
+                if let Some(path) = self.file_dialog.save_file(&default_name) {
+                    match export.save(character, &path, calculator) {
+                        Ok(_) => {
+                            self.saved_file = Some(path);
+                            self.error = None;
+                        }
+                        Err(e) => {
+                            self.saved_file = None;
+                            self.error = Some(format!("Error exporting to {}: {}", path.display(), e));
+                        }
+                    }
+                }
+            }
+
+            if ui.add_enabled(!diff.changes.is_empty(), egui::Button::new("↺ Reset")).on_hover_text("Discard all edits").clicked() {
+                *character = export.original.clone();
+                reset = true;
+            }
+
+            if let Some(path) = &self.saved_file {
+                ui.label(egui::RichText::new(format!("Saved: {}", path.file_name().unwrap_or_default().to_string_lossy()))
+                    .color(egui::Color32::GRAY));
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::DARK_RED, format!("⚠ {}", error));
+        }
+        reset
+    }
+
+    fn show_characteristics(ui: &mut egui::Ui, character: &Character, edits: &mut Vec<Edit>) {
+        let Some(characteristics) = character.system.as_ref().and_then(|s| s.characteristics.as_ref()) else {
+            return;
+        };
+
+        egui::CollapsingHeader::new("Characteristics")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("edit_characteristics_grid")
+                    .num_columns(3)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Characteristic");
+                        ui.strong("Advances");
+                        ui.strong("Value");
+                        ui.end_row();
+
+                        for (key, name) in Characteristics::NAMES {
+                            let Some(characteristic) = characteristics.get(key) else {
+                                continue;
+                            };
+                            ui.label(format!("{} ({})", name, key.to_uppercase()));
+                            let mut advances = characteristic.advances();
+                            if ui.add(egui::DragValue::new(&mut advances).range(0..=MAX_EDIT_VALUE)).changed() {
+                                edits.push(Edit::Advances(key, advances));
+                            }
+                            ui.label(characteristic.value().to_string());
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn show_talents(ui: &mut egui::Ui, character: &Character, edits: &mut Vec<Edit>) {
+        let mut talents: Vec<_> = character.items
+            .iter()
+            .filter(|item| item.kind.has_talent_value())
+            .collect();
+        if talents.is_empty() {
+            return;
+        }
+        talents.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+
+        egui::CollapsingHeader::new("Skills, Combat Techniques, Spells and Liturgies")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("edit_talents_grid")
+                    .num_columns(3)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Name");
+                        ui.strong("Type");
+                        ui.strong("Value");
+                        ui.end_row();
+
+                        for item in talents {
+                            ui.label(&item.name);
+                            ui.label(item.kind.as_str());
+                            match item.data().talent_value() {
+                                Some(mut value) => {
+                                    if ui.add(egui::DragValue::new(&mut value).range(0..=MAX_EDIT_VALUE)).changed() {
+                                        edits.push(Edit::TalentValue(item._id.clone(), value));
+                                    }
+                                }
+                                None => {
+                                    ui.label("-").on_hover_text("Missing or unparseable talent value");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn show_steps(ui: &mut egui::Ui, character: &Character, edits: &mut Vec<Edit>) {
+        let items: Vec<_> = character.items
+            .iter()
+            .filter_map(|item| match &item.data() {
+                ItemData::Purchase(purchase) => purchase.step.map(|step| (item, step, purchase.max_step.unwrap_or(1))),
+                _ => None,
+            })
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+
+        egui::CollapsingHeader::new("Steps of Advantages, Disadvantages and Special Abilities")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("edit_steps_grid")
+                    .num_columns(3)
+                    .spacing([20.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Name");
+                        ui.strong("Type");
+                        ui.strong("Step");
+                        ui.end_row();
+
+                        for (item, mut step, max_step) in items {
+                            ui.label(&item.name);
+                            ui.label(item.kind.as_str());
+                            let response = ui.add(egui::DragValue::new(&mut step).range(1..=max_step))
+                                .on_hover_text(format!("Highest step: {}", max_step));
+                            if response.changed() {
+                                edits.push(Edit::Step(item._id.clone(), step));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
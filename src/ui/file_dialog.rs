@@ -8,9 +8,45 @@ impl FileDialog {
     }
 
     pub fn open_file(&self) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("Foundry VTT or Optolith JSON", &["json"])
+            .set_title("Select DSA Character File (Foundry VTT or Optolith)")
+            .pick_file()
+    }
+
+    /// Directory of a Foundry world (`Data/worlds/<world>`)
+    pub fn open_world_dir(&self) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .set_title("Select Foundry VTT World Directory")
+            .pick_folder()
+    }
+
+    /// Directory with DSA5 compendium packs (a pack, a module or Foundry's `Data/modules`)
+    pub fn open_compendium_dir(&self) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .set_title("Select DSA5 Compendium Packs Directory")
+            .pick_folder()
+    }
+
+    pub fn open_rules_file(&self) -> Option<PathBuf> {
         rfd::FileDialog::new()
             .add_filter("JSON files", &["json"])
-            .set_title("Select DSA Character File")
+            .set_title("Select Rules File")
             .pick_file()
     }
+
+    pub fn open_plan_file(&self) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("JSON files", &["json"])
+            .set_title("Select Advancement Plan")
+            .pick_file()
+    }
+
+    pub fn save_file(&self, default_name: &str) -> Option<PathBuf> {
+        rfd::FileDialog::new()
+            .add_filter("JSON files", &["json"])
+            .set_file_name(default_name)
+            .set_title("Save File")
+            .save_file()
+    }
 }
\ No newline at end of file
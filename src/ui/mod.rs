@@ -1,7 +1,19 @@
 pub mod file_dialog;
 pub mod character_view;
 pub mod analysis_view;
+pub mod diff_view;
+pub mod planner_view;
+pub mod edit_view;
+pub mod cross_check_view;
+pub mod world_view;
+pub mod party_view;
 
 pub use file_dialog::FileDialog;
 pub use character_view::CharacterView;
-pub use analysis_view::ApAnalysis;
\ No newline at end of file
+pub use analysis_view::ApAnalysis;
+pub use diff_view::DiffView;
+pub use planner_view::PlannerView;
+pub use edit_view::EditView;
+pub use cross_check_view::CrossCheckView;
+pub use world_view::WorldPicker;
+pub use party_view::PartyView;
\ No newline at end of file
@@ -0,0 +1,241 @@
+use crate::character::{party_categories, HeroSummary, VerificationStatus};
+use eframe::egui;
+
+/// Height of the bar area of the category chart
+const CHART_HEIGHT: f32 = 220.0;
+/// Width of a single bar; a category group is one bar per hero wide
+const BAR_WIDTH: f32 = 14.0;
+/// Space between two category groups
+const GROUP_GAP: f32 = 24.0;
+/// Space left of the bars for the AP scale
+const AXIS_WIDTH: f32 = 48.0;
+/// Space below the bars for the category names
+const LABEL_HEIGHT: f32 = 36.0;
+
+/// Window comparing the AP of all heroes of the party side by side
+pub struct PartyView {
+    open: bool,
+}
+
+impl PartyView {
+    pub fn new() -> Self {
+        Self { open: false }
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, heroes: &[&HeroSummary]) {
+        if heroes.is_empty() {
+            return;
+        }
+
+        egui::Window::new(format!("👥 Party ({} heroes)", heroes.len()))
+            .id(egui::Id::new("party_window"))
+            .open(&mut self.open)
+            .default_width(700.0)
+            .default_height(550.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("party_scroll")
+                    .show(ui, |ui| {
+                        Self::show_overview(ui, heroes);
+                        ui.add_space(15.0);
+                        ui.heading("📊 AP by Category");
+                        ui.separator();
+                        Self::show_legend(ui, heroes);
+                        ui.add_space(8.0);
+                        Self::show_category_chart(ui, heroes);
+                    });
+            });
+    }
+
+    /// Total, spent and remaining AP and the result of the AP comparison per hero
+    fn show_overview(ui: &mut egui::Ui, heroes: &[&HeroSummary]) {
+        let ap_label = |ap: Option<i32>| ap.map(|ap| format!("{} AP", ap)).unwrap_or_else(|| "-".to_string());
+
+        egui::Grid::new("party_overview_grid")
+            .num_columns(6)
+            .spacing([20.0, 4.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Hero");
+                ui.strong("Total");
+                ui.strong("Spent");
+                ui.strong("Remaining");
+                ui.strong("Calculated");
+                ui.strong("AP Check");
+                ui.end_row();
+
+                for hero in heroes {
+                    ui.label(&hero.name);
+                    ui.label(ap_label(hero.total_ap));
+                    ui.label(ap_label(hero.recorded_spent));
+                    ui.label(ap_label(hero.remaining_ap()));
+                    ui.label(format!("{} AP", hero.calculated_spent));
+                    Self::show_status(ui, hero);
+                    ui.end_row();
+                }
+
+                if heroes.len() > 1 {
+                    let sum = |ap: fn(&HeroSummary) -> Option<i32>| heroes.iter().map(|hero| ap(hero)).sum::<Option<i32>>();
+                    ui.strong("Party");
+                    ui.strong(ap_label(sum(|hero| hero.total_ap)));
+                    ui.strong(ap_label(sum(|hero| hero.recorded_spent)));
+                    ui.strong(ap_label(sum(HeroSummary::remaining_ap)));
+                    ui.strong(format!("{} AP", heroes.iter().map(|hero| hero.calculated_spent).sum::<i32>()));
+                    let mismatches = heroes.iter().filter(|hero| matches!(hero.status(), VerificationStatus::Mismatch(_))).count();
+                    if mismatches == 0 {
+                        ui.label("");
+                    } else {
+                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {} of {} heroes differ", mismatches, heroes.len()));
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    pub fn show_status(ui: &mut egui::Ui, hero: &HeroSummary) {
+        let response = match hero.status() {
+            VerificationStatus::Match => ui.colored_label(egui::Color32::from_rgb(100, 200, 100), "✔ Match"),
+            VerificationStatus::Mismatch(difference) => {
+                ui.colored_label(egui::Color32::from_rgb(220, 100, 100), format!("⚠ {:+} AP", difference))
+                    .on_hover_text(if difference > 0 {
+                        "The export shows less spent AP than calculated"
+                    } else {
+                        "The export shows more spent AP than calculated"
+                    })
+            }
+            VerificationStatus::Unknown => ui.label("-").on_hover_text("The export records no spent AP"),
+        };
+        if hero.warnings > 0 {
+            response.on_hover_text(format!("{} entries could not be costed", hero.warnings));
+        }
+    }
+
+    fn show_legend(ui: &mut egui::Ui, heroes: &[&HeroSummary]) {
+        ui.horizontal_wrapped(|ui| {
+            for (i, hero) in heroes.iter().enumerate() {
+                let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(12.0), egui::Sense::hover());
+                ui.painter().rect_filled(rect, 2.0, Self::hero_color(i));
+                ui.label(&hero.name);
+                ui.add_space(10.0);
+            }
+        });
+    }
+
+    /// Grouped bar chart: one group per category, one bar per hero in each group.
+    /// Categories with negative AP (disadvantages) are drawn below the zero line.
+    fn show_category_chart(ui: &mut egui::Ui, heroes: &[&HeroSummary]) {
+        let categories = party_categories(heroes.iter().copied());
+        if categories.is_empty() {
+            ui.label("No AP items found.");
+            return;
+        }
+
+        let values = || heroes.iter().flat_map(|hero| hero.categories.iter().map(|total| total.ap_cost));
+        let max_ap = values().max().unwrap_or(0).max(0);
+        let min_ap = values().min().unwrap_or(0).min(0);
+        let scale_step = Self::scale_step((max_ap - min_ap).max(1));
+        let scale_max = (max_ap + scale_step - 1).div_euclid(scale_step) * scale_step;
+        let scale_min = min_ap.div_euclid(scale_step) * scale_step;
+        let scale_range = (scale_max - scale_min).max(scale_step) as f32;
+
+        let group_width = BAR_WIDTH * heroes.len() as f32;
+        let width = AXIS_WIDTH + categories.len() as f32 * (group_width + GROUP_GAP);
+
+        egui::ScrollArea::horizontal()
+            .id_salt("party_chart_scroll")
+            .show(ui, |ui| {
+                let desired_size = egui::vec2(width.max(ui.available_width()), CHART_HEIGHT + LABEL_HEIGHT);
+                let (response, painter) = ui.allocate_painter(desired_size, egui::Sense::hover());
+                let rect = response.rect;
+                let visuals = ui.visuals();
+                let font = egui::FontId::proportional(11.0);
+
+                let origin = egui::pos2(rect.left() + AXIS_WIDTH, rect.top() + CHART_HEIGHT);
+                let y_for = |ap: i32| origin.y - ((ap - scale_min) as f32 / scale_range) * (CHART_HEIGHT - 10.0);
+
+                // AP scale with grid lines
+                for step in (scale_min..=scale_max).step_by(scale_step as usize) {
+                    let y = y_for(step);
+                    painter.line_segment([egui::pos2(origin.x, y), egui::pos2(rect.right(), y)], visuals.widgets.noninteractive.bg_stroke);
+                    painter.text(egui::pos2(origin.x - 6.0, y), egui::Align2::RIGHT_CENTER, step.to_string(), font.clone(), visuals.weak_text_color());
+                }
+                painter.line_segment([egui::pos2(origin.x, origin.y), egui::pos2(origin.x, rect.top())], visuals.widgets.noninteractive.fg_stroke);
+                painter.line_segment([egui::pos2(origin.x, y_for(0)), egui::pos2(rect.right(), y_for(0))], visuals.widgets.noninteractive.fg_stroke);
+
+                let hover_pos = response.hover_pos();
+                let mut hovered = None;
+
+                for (group, category) in categories.iter().enumerate() {
+                    let group_left = origin.x + GROUP_GAP / 2.0 + group as f32 * (group_width + GROUP_GAP);
+
+                    for (i, hero) in heroes.iter().enumerate() {
+                        let ap = hero.category_ap(category);
+                        let left = group_left + i as f32 * BAR_WIDTH;
+                        let bar = egui::Rect::from_two_pos(egui::pos2(left + 1.0, y_for(ap)), egui::pos2(left + BAR_WIDTH - 1.0, y_for(0)));
+                        painter.rect_filled(bar, 1.0, Self::hero_color(i));
+
+                        // The whole column above the bar counts, so small values can be hovered as well
+                        let column = egui::Rect::from_x_y_ranges(bar.x_range(), rect.top()..=origin.y);
+                        if hover_pos.is_some_and(|pos| column.contains(pos)) {
+                            hovered = Some(format!("{} – {}: {} AP", hero.name, category, ap));
+                        }
+                    }
+
+                    let label_rect = egui::Rect::from_min_size(
+                        egui::pos2(group_left - GROUP_GAP / 2.0, origin.y + 4.0),
+                        egui::vec2(group_width + GROUP_GAP, LABEL_HEIGHT - 4.0),
+                    );
+                    let label = Self::fit_label(category, label_rect.width(), &font, ui);
+                    painter.with_clip_rect(label_rect).text(label_rect.center_top(), egui::Align2::CENTER_TOP, label, font.clone(), visuals.text_color());
+                }
+
+                if let Some(text) = hovered {
+                    response.on_hover_text_at_pointer(text);
+                }
+            });
+    }
+
+    /// Distance between the lines of the AP scale, about five lines over the range of the bars
+    fn scale_step(range: i32) -> i32 {
+        [10, 25, 50, 100, 250, 500, 1000, 2500]
+            .into_iter()
+            .find(|&step| range / step <= 5)
+            .unwrap_or(5000)
+    }
+
+    /// Shorten a category name with "…" until it fits below its bar group
+    fn fit_label(label: &str, width: f32, font: &egui::FontId, ui: &egui::Ui) -> String {
+        let text_width = |text: &str| ui.fonts(|fonts| fonts.layout_no_wrap(text.to_string(), font.clone(), egui::Color32::WHITE).size().x);
+        if text_width(label) <= width {
+            return label.to_string();
+        }
+
+        let mut chars: Vec<char> = label.chars().collect();
+        while !chars.is_empty() {
+            chars.pop();
+            let shortened = format!("{}…", chars.iter().collect::<String>().trim_end());
+            if text_width(&shortened) <= width {
+                return shortened;
+            }
+        }
+        "…".to_string()
+    }
+
+    pub fn hero_color(index: usize) -> egui::Color32 {
+        const COLORS: [egui::Color32; 8] = [
+            egui::Color32::from_rgb(52, 152, 219),  // Blue
+            egui::Color32::from_rgb(46, 204, 113),  // Green
+            egui::Color32::from_rgb(231, 76, 60),   // Red
+            egui::Color32::from_rgb(241, 196, 15),  // Yellow
+            egui::Color32::from_rgb(155, 89, 182),  // Purple
+            egui::Color32::from_rgb(230, 126, 34),  // Orange
+            egui::Color32::from_rgb(26, 188, 156),  // Turquoise
+            egui::Color32::from_rgb(236, 112, 160), // Pink
+        ];
+        COLORS[index % COLORS.len()]
+    }
+}
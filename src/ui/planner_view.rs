@@ -0,0 +1,305 @@
+use crate::character::{ApCalculator, Character, Characteristics, Energy, Plan, PlanStatus, PlannedChange};
+use crate::ui::FileDialog;
+use eframe::egui;
+
+/// Kind of change selected in the "add change" form
+#[derive(Clone, Copy, PartialEq)]
+enum ChangeForm {
+    Talent,
+    Characteristic,
+    Energy,
+    Item,
+}
+
+/// Item types that can be added via their APValue
+const AP_ITEM_TYPES: [&str; 3] = ["advantage", "disadvantage", "specialability"];
+
+/// What-if planning of advancements with their incremental AP cost
+pub struct PlannerView {
+    file_dialog: FileDialog,
+    plan: Plan,
+    form: ChangeForm,
+    // Inputs of the "add change" form
+    talent_index: usize,
+    characteristic_index: usize,
+    energy_index: usize,
+    item_type_index: usize,
+    item_name: String,
+    item_ap_value: String,
+    target: i32,
+}
+
+impl PlannerView {
+    pub fn new() -> Self {
+        Self {
+            file_dialog: FileDialog::new(),
+            plan: Plan::default(),
+            form: ChangeForm::Talent,
+            talent_index: 0,
+            characteristic_index: 0,
+            energy_index: 0,
+            item_type_index: 0,
+            item_name: String::new(),
+            item_ap_value: String::new(),
+            target: 1,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        ui.heading("📈 Advancement Planner");
+        ui.separator();
+
+        self.show_plan_buttons(ui, character, calculator);
+
+        if !self.plan.changes.is_empty() && !self.plan.is_for(character) {
+            ui.colored_label(
+                egui::Color32::from_rgb(200, 150, 50),
+                format!("⚠ This plan was made for '{}'", self.plan.character),
+            );
+        }
+
+        ui.add_space(10.0);
+        self.show_add_change(ui, character, calculator);
+
+        ui.add_space(10.0);
+        self.show_planned_changes(ui, character, calculator);
+    }
+
+    fn show_plan_buttons(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        ui.horizontal(|ui| {
+            if ui.button("💾 Save Plan").clicked() {
+                let default_name = format!("{} plan.json", character.name);
+                if let Some(path) = self.file_dialog.save_file(&default_name) {
+                    if let Err(e) = self.plan.save(&path) {
+                        eprintln!("Error saving plan: {}", e);
+                    }
+                }
+            }
+
+            if ui.button("📂 Load Plan").on_hover_text("Load a saved plan and check it against this character").clicked() {
+                if let Some(path) = self.file_dialog.open_plan_file() {
+                    match Plan::from_file(&path) {
+                        Ok(plan) => self.plan = plan,
+                        Err(e) => eprintln!("Error loading plan: {}", e),
+                    }
+                }
+            }
+
+            if ui.button("Remove reached").on_hover_text("Remove changes the character already has").clicked() {
+                self.plan.remove_reached(character, calculator);
+            }
+
+            if ui.button("🗑 Clear").clicked() {
+                self.plan = Plan::new(&character.name);
+            }
+        });
+    }
+
+    fn show_add_change(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let talents: Vec<_> = character.items
+            .iter()
+            .filter(|item| item.kind.has_talent_value())
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.form, ChangeForm::Talent, "Skill/Technique/Spell");
+            ui.selectable_value(&mut self.form, ChangeForm::Characteristic, "Characteristic");
+            ui.selectable_value(&mut self.form, ChangeForm::Energy, "Energy");
+            ui.selectable_value(&mut self.form, ChangeForm::Item, "Advantage/Ability");
+        });
+
+        let change = ui.horizontal(|ui| {
+            match self.form {
+                ChangeForm::Talent => {
+                    if talents.is_empty() {
+                        ui.label("No skills, combat techniques, spells or liturgies found.");
+                        return None;
+                    }
+                    self.talent_index = self.talent_index.min(talents.len() - 1);
+                    egui::ComboBox::from_id_salt("planner_talent")
+                        .width(200.0)
+                        .selected_text(&talents[self.talent_index].name)
+                        .show_ui(ui, |ui| {
+                            for (index, item) in talents.iter().enumerate() {
+                                ui.selectable_value(&mut self.talent_index, index, format!("{} ({})", item.name, item.kind));
+                            }
+                        });
+                    ui.label("to");
+                    ui.add(egui::DragValue::new(&mut self.target).range(0..=25));
+
+                    let item = talents[self.talent_index];
+                    Some(PlannedChange::RaiseTalent {
+                        item_type: item.kind.to_string(),
+                        name: item.name.clone(),
+                        target: self.target,
+                    })
+                }
+                ChangeForm::Characteristic => {
+                    egui::ComboBox::from_id_salt("planner_characteristic")
+                        .selected_text(Characteristics::NAMES[self.characteristic_index].1)
+                        .show_ui(ui, |ui| {
+                            for (index, (_, name)) in Characteristics::NAMES.iter().enumerate() {
+                                ui.selectable_value(&mut self.characteristic_index, index, *name);
+                            }
+                        });
+                    ui.label("to");
+                    ui.add(egui::DragValue::new(&mut self.target).range(8..=25));
+
+                    Some(PlannedChange::RaiseCharacteristic {
+                        key: Characteristics::NAMES[self.characteristic_index].0.to_string(),
+                        target: self.target,
+                    })
+                }
+                ChangeForm::Energy => {
+                    egui::ComboBox::from_id_salt("planner_energy")
+                        .selected_text(Energy::ALL[self.energy_index].name())
+                        .show_ui(ui, |ui| {
+                            for (index, energy) in Energy::ALL.iter().enumerate() {
+                                ui.selectable_value(&mut self.energy_index, index, energy.name());
+                            }
+                        });
+                    ui.label("to");
+                    ui.add(egui::DragValue::new(&mut self.target).range(0..=50));
+                    ui.label("advances");
+
+                    Some(PlannedChange::RaiseEnergy {
+                        energy: Energy::ALL[self.energy_index],
+                        target: self.target,
+                    })
+                }
+                ChangeForm::Item => {
+                    egui::ComboBox::from_id_salt("planner_item_type")
+                        .selected_text(AP_ITEM_TYPES[self.item_type_index])
+                        .show_ui(ui, |ui| {
+                            for (index, item_type) in AP_ITEM_TYPES.iter().enumerate() {
+                                ui.selectable_value(&mut self.item_type_index, index, *item_type);
+                            }
+                        });
+                    ui.add(egui::TextEdit::singleline(&mut self.item_name).hint_text("Name").desired_width(140.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.item_ap_value).hint_text("APValue, e.g. 10;20").desired_width(90.0));
+                    ui.label("step");
+                    ui.add(egui::DragValue::new(&mut self.target).range(1..=10));
+
+                    if self.item_name.trim().is_empty() || self.item_ap_value.trim().is_empty() {
+                        return None;
+                    }
+                    Some(PlannedChange::AddItem {
+                        item_type: AP_ITEM_TYPES[self.item_type_index].to_string(),
+                        name: self.item_name.trim().to_string(),
+                        ap_value: self.item_ap_value.trim().to_string(),
+                        step: self.target.max(1),
+                    })
+                }
+            }
+        }).inner;
+
+        let Some(change) = change else {
+            return;
+        };
+
+        // Preview the cost as if the change were appended to the plan
+        let mut preview_plan = self.plan.clone();
+        preview_plan.changes.push(change.clone());
+        let preview = preview_plan.evaluate(character, calculator);
+
+        ui.horizontal(|ui| {
+            if let Some(cost) = preview.costs.last() {
+                match &cost.status {
+                    PlanStatus::Open => {
+                        ui.label(format!("{} → {}: {} AP", cost.current.unwrap_or(0), cost.target, cost.ap_cost));
+                    }
+                    PlanStatus::Reached => {
+                        ui.label("Already reached");
+                    }
+                    PlanStatus::Invalid(reason) => {
+                        ui.colored_label(egui::Color32::from_rgb(220, 100, 100), reason);
+                    }
+                }
+            }
+
+            if ui.button("➕ Add to plan").clicked() {
+                if self.plan.changes.is_empty() {
+                    self.plan.character = character.name.clone();
+                }
+                self.plan.changes.push(change);
+            }
+        });
+    }
+
+    fn show_planned_changes(&mut self, ui: &mut egui::Ui, character: &Character, calculator: &ApCalculator) {
+        let evaluation = self.plan.evaluate(character, calculator);
+
+        egui::Grid::new("planner_summary_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("Available AP:");
+                ui.strong(format!("{} AP", evaluation.available_ap));
+                ui.end_row();
+
+                ui.label("Planned AP:");
+                ui.strong(format!("{} AP", evaluation.planned_ap));
+                ui.end_row();
+
+                ui.label("Remaining AP:");
+                let remaining = evaluation.remaining_ap();
+                let color = if remaining < 0 {
+                    egui::Color32::from_rgb(220, 100, 100)
+                } else {
+                    egui::Color32::from_rgb(100, 200, 100)
+                };
+                ui.label(egui::RichText::new(format!("{} AP", remaining)).strong().color(color));
+                ui.end_row();
+            });
+
+        ui.separator();
+
+        if evaluation.costs.is_empty() {
+            ui.label("No changes planned yet.");
+            return;
+        }
+
+        let mut remove_index = None;
+
+        egui::ScrollArea::vertical()
+            .id_salt("planner_scroll")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("planner_changes_grid")
+                    .num_columns(4)
+                    .spacing([15.0, 4.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Change");
+                        ui.strong("AP");
+                        ui.strong("Status");
+                        ui.strong("");
+                        ui.end_row();
+
+                        for (index, cost) in evaluation.costs.iter().enumerate() {
+                            ui.label(cost.change.description());
+                            ui.label(cost.ap_cost.to_string());
+                            match &cost.status {
+                                PlanStatus::Open => {
+                                    ui.label(format!("from {}", cost.current.unwrap_or(0)));
+                                }
+                                PlanStatus::Reached => {
+                                    ui.colored_label(egui::Color32::from_rgb(100, 200, 100), "✔ reached");
+                                }
+                                PlanStatus::Invalid(reason) => {
+                                    ui.colored_label(egui::Color32::from_rgb(220, 100, 100), format!("⚠ {}", reason));
+                                }
+                            }
+                            if ui.small_button("✖").on_hover_text("Remove from plan").clicked() {
+                                remove_index = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some(index) = remove_index {
+            self.plan.changes.remove(index);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::character::{ApCalculator, FoundryWorld, WorldActor};
+use eframe::egui;
+
+/// Calculated and recorded spent AP of an actor
+struct ActorStatus {
+    calculated_ap: i32,
+    foundry_spent: Option<i32>,
+}
+
+/// Window listing the actors of a Foundry world to pick one for the analysis
+pub struct WorldPicker {
+    world: Option<FoundryWorld>,
+    /// Status per actor of the world, in the same order; the error if it cannot be read as a character
+    statuses: Vec<Result<ActorStatus, String>>,
+    /// Also list NPCs and creatures
+    show_all: bool,
+    open: bool,
+}
+
+impl WorldPicker {
+    pub fn new() -> Self {
+        Self {
+            world: None,
+            statuses: Vec::new(),
+            show_all: false,
+            open: false,
+        }
+    }
+
+    pub fn set_world(&mut self, world: FoundryWorld, calculator: &ApCalculator) {
+        self.statuses = world.actors
+            .iter()
+            .map(|actor| {
+                actor.character()
+                    .map(|character| ActorStatus {
+                        calculated_ap: calculator.calculate_total_spent_ap(&character),
+                        foundry_spent: character.experience().and_then(|experience| experience.spent()),
+                    })
+                    .map_err(|e| e.to_string())
+            })
+            .collect();
+        self.world = Some(world);
+        self.open = true;
+    }
+
+    pub fn open(&mut self) {
+        self.open = self.world.is_some();
+    }
+
+    pub fn has_world(&self) -> bool {
+        self.world.is_some()
+    }
+
+    /// Show the window; returns the actor picked for the analysis
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<(String, WorldActor)> {
+        let world = self.world.as_ref()?;
+        let mut picked = None;
+
+        egui::Window::new(format!("🌍 {}", world.title))
+            .id(egui::Id::new("world_picker_window"))
+            .open(&mut self.open)
+            .default_width(500.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(format!("{} ({})", world.path.display(), world.store.name())).color(egui::Color32::GRAY));
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.show_all, "Show NPCs and creatures");
+                    if !world.warnings.is_empty() {
+                        ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("⚠ {} documents skipped", world.warnings.len()))
+                            .on_hover_text(world.warnings.join("\n"));
+                    }
+                });
+                ui.separator();
+
+                let actors: Vec<_> = world.actors
+                    .iter()
+                    .zip(&self.statuses)
+                    .filter(|(actor, _)| self.show_all || actor.is_player_character())
+                    .collect();
+                if actors.is_empty() {
+                    ui.label("No player characters in this world.");
+                    return;
+                }
+
+                egui::ScrollArea::vertical()
+                    .id_salt("world_picker_scroll")
+                    .show(ui, |ui| {
+                        egui::Grid::new("world_picker_grid")
+                            .num_columns(5)
+                            .spacing([15.0, 4.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.strong("Name");
+                                ui.strong("Type");
+                                ui.strong("Calculated");
+                                ui.strong("Foundry");
+                                ui.strong("");
+                                ui.end_row();
+
+                                for (actor, status) in actors {
+                                    ui.label(&actor.name);
+                                    ui.label(&actor.actor_type);
+                                    match status {
+                                        Ok(status) => {
+                                            ui.label(format!("{} AP", status.calculated_ap));
+                                            match status.foundry_spent {
+                                                Some(spent) if spent == status.calculated_ap => ui.label(format!("{} AP", spent)),
+                                                Some(spent) => ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("{} AP", spent))
+                                                    .on_hover_text("Differs from the calculated AP"),
+                                                None => ui.label("-"),
+                                            };
+                                            if ui.button("Analyze").clicked() {
+                                                picked = Some((world.title.clone(), actor.clone()));
+                                            }
+                                        }
+                                        Err(e) => {
+                                            ui.colored_label(egui::Color32::from_rgb(220, 100, 100), "unreadable").on_hover_text(e);
+                                            ui.label("");
+                                            ui.label("");
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        picked
+    }
+}